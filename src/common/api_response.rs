@@ -182,6 +182,16 @@ impl<T> ApiResponse<T> {
             errors: None,
         }
     }
+    pub fn conflict(message: &str) -> ApiResponse<T> {
+        ApiResponse {
+            data: None,
+            meta: Meta {
+                code: 409,
+                message: message.to_string(),
+            },
+            errors: None,
+        }
+    }
     pub fn access_denied(message: &str) -> ApiResponse<T> {
         ApiResponse {
             data: None,
@@ -1,6 +1,6 @@
 use bson::oid::ObjectId;
 use bson::DateTime;
-use serde::{Deserialize, Deserializer, Serializer};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 // Custom serializer to convert ObjectId to string
 pub fn serialize_object_id<S>(
@@ -93,6 +93,17 @@ where
     }
 }
 
+pub fn serialize_vec_datetime<S>(val: &Vec<DateTime>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let formatted = val
+        .iter()
+        .filter_map(|value| value.try_to_rfc3339_string().ok())
+        .collect::<Vec<String>>();
+    formatted.serialize(serializer)
+}
+
 pub fn non_empty<'de, D, T>(d: D) -> Result<Vec<T>, D::Error>
 where
     D: Deserializer<'de>,
@@ -15,6 +15,7 @@ pub const BUCKET_THREAD: &str = "thread";
 pub const BUCKET_PROFILE_PICTURE: &str = "thread";
 
 pub const PATH_PROFILE_PICTURE: &str = "profile";
+pub const PATH_EVENT_ALBUM: &str = "album";
 
 pub const REDIS_KEY_USER_TOKEN: &str = "token";
 pub const REDIS_KEY_OTP: &str = "otp";
@@ -23,6 +24,7 @@ pub const REDIS_KEY_OTP_EXPIRED: &str = "expired_at";
 pub const REDIS_KEY_OTP_ATTEMPT: &str = "otp_attempt";
 pub const REDIS_KEY_OTP_TYPE: &str = "otp_type";
 pub const REDIS_KEY_OTP_PHONE_NUMBER: &str = "otp_phone_number";
+pub const REDIS_KEY_OTP_NEW_PHONE: &str = "otp_new_phone";
 pub const REDIS_KEY_USER_PHONE_NUMBER: &str = "user_phone_number";
 pub const REDIS_KEY_USER_EMAIL: &str = "email";
 pub const REDIS_KEY_USER_ID: &str = "id";
@@ -37,9 +39,41 @@ pub const USER_STATUS_ACTIVE: &str = "ACTIVE";
 pub const USER_STATUS_SUSPENDED: &str = "SUSPENDED";
 pub const USER_STATUS_INACTIVE: &str = "INACTIVE";
 
+pub const USER_AUDIT_PHONE_CHANGED: &str = "phone_changed";
+
 pub const EVENT_STATUS_INACTIVE: &str = "INACTIVE";
 pub const EVENT_STATUS_DRAFT: &str = "DRAFT";
 pub const EVENT_STATUS_PUBLISHED: &str = "PUBLISHED";
+pub const EVENT_STATUS_CANCELLED: &str = "CANCELLED";
+pub const EVENT_STATUS_COMPLETED: &str = "COMPLETED";
+pub const EVENT_STATUS_ARCHIVED: &str = "ARCHIVED";
+
+pub const DEFAULT_EVENT_TIMEZONE: &str = "UTC";
+pub const EVENT_FILTER_UPCOMING: &str = "upcoming";
+pub const EVENT_FILTER_PAST: &str = "past";
+
+pub const EVENT_VISIBILITY_PUBLIC: &str = "PUBLIC";
+pub const EVENT_VISIBILITY_UNLISTED: &str = "UNLISTED";
+pub const EVENT_VISIBILITY_PRIVATE: &str = "PRIVATE";
+
+pub const GEO_POINT_TYPE: &str = "Point";
+pub const NEARBY_DEFAULT_RADIUS: f64 = 5000.0;
+pub const NEARBY_MAX_RADIUS: f64 = 50000.0;
+
+pub const EVENT_SLUG_MAX_LENGTH: usize = 60;
+pub const EVENT_SLUG_FALLBACK: &str = "event";
+pub const SHORT_CODE_LENGTH: usize = 8;
+//no 0 o 1 i l, easier to read out loud
+pub const SHORT_CODE_ALPHABET: &str = "23456789abcdefghjkmnpqrstuvwxyz";
+
+pub const APP_NAME: &str = "Sirkel";
+pub const APP_DEEP_LINK_SCHEME: &str = "sirkel://";
+
+pub const DISCOVERY_DEFAULT_SIZE: i64 = 20;
+pub const DISCOVERY_MAX_SIZE: i64 = 50;
+pub const DISCOVERY_WEIGHT_DATE: f64 = 3.0;
+pub const DISCOVERY_WEIGHT_POPULARITY: f64 = 1.0;
+pub const DISCOVERY_WEIGHT_FRIEND: f64 = 2.0;
 
 pub const DEFAULT_ID_NON_MEMBER: &str = "6742c74a15e68b0e7ee06145";
 
@@ -47,7 +81,75 @@ pub const EVENT_GUEST_ROLE_HOST:&str="host";
 pub const EVENT_GUEST_ROLE_CO_HOST:&str="co-host";
 pub const EVENT_GUEST_ROLE_GUEST:&str="guest";
 
+pub const ROLE_INVITATION_STATUS_PENDING:&str="pending";
+pub const ROLE_INVITATION_STATUS_ACCEPTED:&str="accepted";
+pub const ROLE_INVITATION_STATUS_DECLINED:&str="declined";
+pub const ROLE_INVITATION_STATUS_CANCELLED:&str="cancelled";
+
+pub const EVENT_GUEST_RSVP_GOING:&str="going";
+pub const EVENT_GUEST_RSVP_MAYBE:&str="maybe";
+pub const EVENT_GUEST_RSVP_NOT_GOING:&str="not-going";
+
+pub const SERIES_FREQUENCY_DAILY:&str="daily";
+pub const SERIES_FREQUENCY_WEEKLY:&str="weekly";
+pub const SERIES_FREQUENCY_MONTHLY:&str="monthly";
+pub const SERIES_MAX_OCCURRENCE:i64=52;
+
+pub const CONTACT_RETENTION_DEFAULT_DAYS: i64 = 30;
+pub const CONTACT_SYNC_MAX_SIZE: u64 = 2000;
+pub const CONTACT_INVITE_MAX_SIZE: u64 = 200;
+pub const CONTACT_SYNC_RATE_LIMIT: i64 = 5;
+pub const CONTACT_SYNC_RATE_WINDOW_SECONDS: i64 = 86400;
+pub const INVITATION_PHONE_RATE_LIMIT: i64 = 50;
+pub const INVITATION_PHONE_RATE_WINDOW_SECONDS: i64 = 86400;
+pub const CHANGE_PHONE_RATE_LIMIT: i64 = 3;
+pub const CHANGE_PHONE_RATE_WINDOW_SECONDS: i64 = 3600;
+//account status is checked on every request, cached shortly so it does not hit mongo each time
+pub const USER_STATUS_CACHE_SECONDS: i64 = 60;
+//check in qr stay valid until the event end plus this grace, unscheduled event fall back to a week
+pub const CHECK_IN_TOKEN_GRACE_SECONDS: i64 = 21600;
+pub const CHECK_IN_TOKEN_UNSCHEDULED_SECONDS: i64 = 604800;
+
+//mutual without status are friends made before suggestion exist
+//every relation is kept as two documents, one per side
+pub const MUTUAL_STATUS_SUGGESTED: &str = "suggested";
+pub const MUTUAL_STATUS_REQUESTED: &str = "requested";
+pub const MUTUAL_STATUS_PENDING: &str = "pending";
+pub const MUTUAL_STATUS_FRIEND: &str = "friend";
+pub const MUTUAL_STATUS_BLOCKED: &str = "blocked";
+pub const MUTUAL_SOURCE_CONTACT: &str = "contact";
+pub const MUTUAL_SOURCE_EVENT: &str = "event";
+pub const MUTUAL_CO_ATTENDEE_MAX_SIZE: usize = 100;
+
+pub const REPORT_TARGET_USER: &str = "user";
+pub const REPORT_TARGET_EVENT: &str = "event";
+pub const REPORT_TARGET_ALBUM: &str = "album";
+pub const REPORT_STATUS_OPEN: &str = "open";
+pub const REPORT_STATUS_RESOLVED: &str = "resolved";
+pub const REPORT_STATUS_DISMISSED: &str = "dismissed";
+
+pub const SERIES_EDIT_SCOPE_THIS:&str="this";
+pub const SERIES_EDIT_SCOPE_FOLLOWING:&str="following";
+pub const SERIES_EDIT_SCOPE_ALL:&str="all";
+
+pub const EVENT_ACTIVITY_RSVP:&str="rsvp";
+pub const EVENT_ACTIVITY_GUEST_ADDED:&str="guest-added";
+pub const EVENT_ACTIVITY_VENUE_CHANGED:&str="venue-changed";
+pub const EVENT_ACTIVITY_IMAGE_UPDATED:&str="image-updated";
+pub const EVENT_ACTIVITY_INVITATION_SENT:&str="invitation-sent";
+pub const EVENT_ACTIVITY_STATUS_CHANGED:&str="status-changed";
+pub const EVENT_ACTIVITY_HOST_TRANSFERRED:&str="host-transferred";
+pub const EVENT_ACTIVITY_GUEST_LEFT:&str="guest-left";
+pub const EVENT_ACTIVITY_GUEST_REMOVED:&str="guest-removed";
+
 pub const NOTIFICATION_TYPE_INVITATION:&str="invitation";
+pub const NOTIFICATION_TYPE_EVENT_CANCELLED:&str="event-cancelled";
+pub const NOTIFICATION_TYPE_EVENT_COMPLETED:&str="event-completed";
+pub const NOTIFICATION_TYPE_ROLE_INVITATION:&str="role-invitation";
+pub const NOTIFICATION_TYPE_GUEST_LEFT:&str="guest-left";
+pub const NOTIFICATION_TYPE_GUEST_REMOVED:&str="guest-removed";
+pub const NOTIFICATION_TYPE_MUTUAL_REQUEST:&str="mutual-request";
+pub const NOTIFICATION_TYPE_MUTUAL_ACCEPTED:&str="mutual-accepted";
 
 pub const COLLECTION_USERS: &str = "users";
 pub const COLLECTION_EVENTS: &str = "events";
@@ -59,6 +161,18 @@ pub const COLLECTION_EVENT_GUEST: &str = "event-host";
 pub const COLLECTION_EVENT_THEMES: &str = "event-themes";
 pub const COLLECTION_NOTIFICATION: &str = "notifications";
 pub const COLLECTION_NOTIFICATION_LOG: &str = "notification-logs";
+pub const COLLECTION_EVENT_ALBUM: &str = "event-album";
+pub const COLLECTION_EVENT_ACTIVITY: &str = "event-activity";
+pub const COLLECTION_EVENT_SERIES: &str = "event-series";
+pub const COLLECTION_CALENDAR_FEED: &str = "calendar-feed";
+pub const COLLECTION_EVENT_QUESTIONNAIRE: &str = "event-questionnaire";
+pub const COLLECTION_EVENT_TEMPLATE: &str = "event-template";
+pub const COLLECTION_EVENT_ROLE_INVITATION: &str = "event-role-invitation";
+pub const COLLECTION_EVENT_BAN: &str = "event-ban";
+pub const COLLECTION_USER_CONTACT: &str = "user-contact";
+pub const COLLECTION_USER_REPORT: &str = "user-report";
+pub const COLLECTION_USER_STATUS_LOG: &str = "user-status-log";
+pub const COLLECTION_USER_AUDIT_LOG: &str = "user-audit-log";
 
 
 pub const COLLECTION_USER_PROFILE: &str = "user-profile";
@@ -72,6 +186,13 @@ pub const SSE_EVENT_UPDATE_EVENT_DATA: &str = "update-event-data";
 pub const SSE_EVENT_UPDATE_EVENT_VENUE: &str = "update-event-venue";
 pub const SSE_EVENT_UPDATE_EVENT_CONFIG: &str = "update-event-config";
 pub const SSE_EVENT_UPDATE_EVENT_HOST: &str = "update-event-host";
+pub const SSE_EVENT_UPDATE_EVENT_ALBUM: &str = "update-event-album";
+pub const SSE_EVENT_UPDATE_EVENT_RSVP: &str = "update-event-rsvp";
+pub const SSE_EVENT_UPDATE_EVENT_CHECK_IN: &str = "update-event-check-in";
+pub const SSE_EVENT_UPDATE_EVENT_STATUS: &str = "update-event-status";
+pub const SSE_EVENT_UPDATE_EVENT_GUEST: &str = "update-event-guest";
 
 pub const INVITATION_TYPE_PUBLIC: &str = "invitation-public";
-pub const INVITATION_TYPE_USER: &str = "invitation-user";
\ No newline at end of file
+pub const INVITATION_TYPE_USER: &str = "invitation-user";
+//addressed to a phone number that has no account yet, user_id hold DEFAULT_ID_NON_MEMBER until sign up
+pub const INVITATION_TYPE_PHONE: &str = "invitation-phone";
\ No newline at end of file
@@ -1,3 +1,4 @@
+use crate::common::constant::CONTACT_RETENTION_DEFAULT_DAYS;
 use dotenv::dotenv;
 use std::sync::Arc;
 
@@ -14,6 +15,7 @@ pub struct EnvConfig {
     pub minio_secret_key: String,
     pub mode: String,
     pub google_client_id: String,
+    pub contact_retention_days: i64,
 }
 
 impl EnvConfig {
@@ -31,6 +33,7 @@ impl EnvConfig {
         let minio_env_secret_key = format!("MINIO_SECRET_KEY_{}", env_mode);
         let jwt_env_key = format!("JWT_SECRET_{}", env_mode);
         let base_url_key = format!("BASE_URL_{}", env_mode);
+        let contact_retention_key = format!("CONTACT_RETENTION_DAYS_{}", env_mode);
 
         let env_base_url = std::env::var(base_url_key.clone());
         let env_database = std::env::var(database_env_key.clone());
@@ -41,6 +44,10 @@ impl EnvConfig {
         let env_minio_access_key = std::env::var(minio_env_access_key.clone());
         let env_minio_secret_key = std::env::var(minio_env_secret_key.clone());
         let env_jwt = std::env::var(jwt_env_key.clone());
+        let env_contact_retention = std::env::var(contact_retention_key.clone())
+            .ok()
+            .and_then(|value| value.parse::<i64>().ok())
+            .unwrap_or(CONTACT_RETENTION_DEFAULT_DAYS);
 
         if env_base_url.is_err() {
             panic!(
@@ -121,6 +128,7 @@ impl EnvConfig {
             jwt_secret: env_jwt.unwrap(),
             mode: env_mode,
             google_client_id: "".to_string(),
+            contact_retention_days: env_contact_retention,
         })
     }
 }
@@ -0,0 +1,147 @@
+use bson::DateTime;
+
+use crate::common::constant::{
+    DEFAULT_EVENT_TIMEZONE, EVENT_STATUS_CANCELLED, EVENT_STATUS_DRAFT,
+};
+use crate::dto::event_dto::EventDTO;
+
+const PRODID: &str = "-//Sirkel//Rumpy Event//EN";
+const UID_DOMAIN: &str = "sirkel.com";
+
+pub struct ICalendar {
+    name: String,
+    timezone: Option<String>,
+    events: Vec<String>,
+}
+
+impl ICalendar {
+    pub fn new(name: &str) -> Self {
+        ICalendar {
+            name: name.to_string(),
+            timezone: None,
+            events: Vec::new(),
+        }
+    }
+
+    pub fn set_timezone(mut self, timezone: Option<String>) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    //event without start cannot be represented in calendar and skipped
+    pub fn add_event(mut self, event: &EventDTO) -> Self {
+        if event.id.is_none() || event.datetime.is_none() {
+            return self;
+        }
+        let mut lines = vec![
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:{}@{}", event.id.unwrap().to_hex(), UID_DOMAIN),
+            format!("DTSTAMP:{}", format_utc(event.updated_at)),
+            format!("LAST-MODIFIED:{}", format_utc(event.updated_at)),
+            format!("SEQUENCE:{}", event.revision),
+            format!("DTSTART:{}", format_utc(event.datetime.unwrap())),
+        ];
+        if let Some(end) = event.end_datetime {
+            lines.push(format!("DTEND:{}", format_utc(end)));
+        }
+        lines.push(format!("SUMMARY:{}", escape_text(&event.event_name)));
+        if !event.event_description.is_empty() {
+            lines.push(format!(
+                "DESCRIPTION:{}",
+                escape_text(&event.event_description)
+            ));
+        }
+        if let Some(venue) = &event.venue_location {
+            let location = [venue.venue_name.clone(), venue.venue_address.clone()]
+                .into_iter()
+                .flatten()
+                .filter(|value| !value.is_empty())
+                .collect::<Vec<String>>()
+                .join(", ");
+            if !location.is_empty() {
+                lines.push(format!("LOCATION:{}", escape_text(&location)));
+            }
+        }
+        if let Some(host) = &event.host {
+            if !host.email.is_empty() {
+                lines.push(format!(
+                    "ORGANIZER;CN={}:mailto:{}",
+                    escape_param(&host.display_name),
+                    host.email
+                ));
+            }
+        }
+        let status = match event.status.as_str() {
+            EVENT_STATUS_CANCELLED => "CANCELLED",
+            EVENT_STATUS_DRAFT => "TENTATIVE",
+            _ => "CONFIRMED",
+        };
+        lines.push(format!("STATUS:{}", status));
+        lines.push("END:VEVENT".to_string());
+
+        self.events.push(
+            lines
+                .iter()
+                .map(|line| fold_line(line))
+                .collect::<Vec<String>>()
+                .join("\r\n"),
+        );
+        self
+    }
+
+    pub fn build(&self) -> String {
+        let mut lines = vec![
+            "BEGIN:VCALENDAR".to_string(),
+            "VERSION:2.0".to_string(),
+            format!("PRODID:{}", PRODID),
+            "CALSCALE:GREGORIAN".to_string(),
+            "METHOD:PUBLISH".to_string(),
+            fold_line(&format!("X-WR-CALNAME:{}", escape_text(&self.name))),
+            format!(
+                "X-WR-TIMEZONE:{}",
+                self.timezone
+                    .clone()
+                    .unwrap_or(DEFAULT_EVENT_TIMEZONE.to_string())
+            ),
+        ];
+        lines.extend(self.events.clone());
+        lines.push("END:VCALENDAR".to_string());
+        let mut calendar = lines.join("\r\n");
+        calendar.push_str("\r\n");
+        calendar
+    }
+}
+
+//times are written in utc so every client show the right local time
+fn format_utc(datetime: DateTime) -> String {
+    datetime.to_chrono().format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn escape_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+fn escape_param(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "'"))
+}
+
+//rfc5545 limit content line to 75 octets
+fn fold_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut length = 0;
+    for char in line.chars() {
+        let size = char.len_utf8();
+        if length + size > 75 {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(char);
+        length += size;
+    }
+    folded
+}
@@ -3,7 +3,7 @@ use std::ops::Add;
 use std::string::ToString;
 
 use axum::RequestPartsExt;
-use axum::extract::{FromRef, FromRequestParts};
+use axum::extract::{FromRef, FromRequestParts, OptionalFromRequestParts};
 use axum::http::request::Parts;
 use axum::response::{IntoResponse, Response};
 use bson::oid::ObjectId;
@@ -16,10 +16,16 @@ use serde::{Deserialize, Serialize};
 
 use super::app_state::AppState;
 use crate::common::api_response::ApiResponse;
-use crate::common::constant::REDIS_KEY_USER_ID;
+use crate::common::constant::{
+    COLLECTION_USERS, REDIS_KEY_USER_ID, USER_STATUS_CACHE_SECONDS, USER_STATUS_INACTIVE,
+    USER_STATUS_SUSPENDED,
+};
 use crate::common::env_config::EnvConfig;
+use crate::common::mongo::DB;
+use crate::common::mongo::filter::is;
 use crate::common::permission::permission::app;
 use crate::common::utils::create_object_id_option;
+use crate::dto::user_dto::UserDTO;
 
 pub struct JwtUtil {
     pub claims: JwtClaims,
@@ -34,6 +40,16 @@ pub struct JwtClaims {
     pub exp: i64,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CheckInClaims {
+    pub iss: String,
+    pub sub: String,
+    pub event_id: String,
+    pub purpose: String,
+    pub iat: i64,
+    pub exp: i64,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AuthContext {
     pub claims: JwtClaims,
@@ -47,9 +63,11 @@ pub enum AuthError {
     MissingCredentials,
     TokenCreation,
     InvalidToken,
+    AccountDisabled,
 }
 
 const ISS: &str = "strong-teams.id";
+const PURPOSE_CHECK_IN: &str = "check-in";
 
 impl JwtUtil {
     pub fn encode(sub: String, provider: String) -> Option<String> {
@@ -92,6 +110,51 @@ impl JwtUtil {
     }
 }
 
+impl JwtUtil {
+    //sub is the event guest id, the token is rendered as qr code and scanned by host
+    //exp is a unix timestamp in seconds, derived from the event schedule by the caller
+    pub fn encode_check_in(guest_id: String, event_id: String, exp: i64) -> Option<String> {
+        let secret = EnvConfig::init();
+        let claims = CheckInClaims {
+            iss: ISS.to_string(),
+            sub: guest_id,
+            event_id,
+            purpose: PURPOSE_CHECK_IN.to_string(),
+            iat: Local::now().timestamp(),
+            exp,
+        };
+
+        match jsonwebtoken::encode(
+            &Header::new(Algorithm::HS256),
+            &claims,
+            &EncodingKey::from_secret(secret.jwt_secret.as_ref()),
+        ) {
+            Ok(token) => Some(token),
+            Err(why) => {
+                info!(target:"app::Jwt","encode check in error {}",why);
+                None
+            }
+        }
+    }
+
+    pub fn decode_check_in(token: String) -> Result<CheckInClaims, AuthError> {
+        let secret = EnvConfig::init();
+        let decoded = jsonwebtoken::decode::<CheckInClaims>(
+            &token,
+            &DecodingKey::from_secret(secret.jwt_secret.as_ref()),
+            &Validation::new(Algorithm::HS256),
+        );
+        if decoded.is_err() {
+            return Err(AuthError::InvalidToken);
+        }
+        let claims = decoded.unwrap().claims;
+        if claims.purpose != PURPOSE_CHECK_IN {
+            return Err(AuthError::InvalidToken);
+        }
+        Ok(claims)
+    }
+}
+
 impl AuthContext {
     pub fn authorize(&self, permission: &str) -> bool {
         if self.permissions.contains_key(app::admin::ALL) {
@@ -197,10 +260,48 @@ where
                         return Err(AuthError::MissingCredentials);
                     }
 
-                    if let None = session.get(REDIS_KEY_USER_ID) {
+                    let user_id = session
+                        .get(REDIS_KEY_USER_ID)
+                        .map_or_else(|| None, |id| create_object_id_option(id));
+                    if let None = user_id {
                         return Err(AuthError::MissingCredentials);
                     }
 
+                    //session can outlive a status change, the account is checked on every request.
+                    //suspend and reactivate drop the cached value so the change apply right away
+                    let user_id = user_id.unwrap();
+                    let cached_status = state
+                        .redis
+                        .get_cache_user_status(&user_id.to_string())
+                        .unwrap_or(None);
+                    let status = match cached_status {
+                        Some(status) => Some(status),
+                        None => {
+                            let find_user = DB::get(COLLECTION_USERS)
+                                .filter(vec![is("_id", user_id)])
+                                .get_one::<UserDTO>(&state.db)
+                                .await;
+                            if find_user.is_err() {
+                                return Err(AuthError::MissingCredentials);
+                            }
+                            let status = find_user.unwrap().status;
+                            let _ = state.redis.set_cache_user_status(
+                                &user_id.to_string(),
+                                &status.clone().unwrap_or_default(),
+                                USER_STATUS_CACHE_SECONDS,
+                            );
+                            status
+                        }
+                    };
+                    let disabled = [USER_STATUS_SUSPENDED, USER_STATUS_INACTIVE];
+                    if let Some(status) = status {
+                        if disabled.contains(&status.as_str()) {
+                            let _ = state.redis.delete_session_sign_in(&claims.sub);
+                            let _ = state.redis.delete_session_permission(&claims.sub);
+                            return Err(AuthError::AccountDisabled);
+                        }
+                    }
+
                     let permissions = &state
                         .redis
                         .get_session_permission(claims.sub.clone().as_str())
@@ -223,6 +324,20 @@ where
     }
 }
 
+//used by endpoints that also serve anonymous viewers, invalid or missing credentials resolve to None
+impl<S> OptionalFromRequestParts<S> for AuthContext
+where
+    AppState: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = AuthError;
+
+    async fn from_request_parts(parts: &mut Parts, s: &S) -> Result<Option<Self>, Self::Rejection> {
+        let context = <AuthContext as FromRequestParts<S>>::from_request_parts(parts, s).await;
+        Ok(context.ok())
+    }
+}
+
 impl<S> FromRequestParts<S> for JwtClaims
 where
     AppState: FromRef<S>,
@@ -270,6 +385,9 @@ impl IntoResponse for AuthError {
                 ApiResponse::<String>::un_authorized("Token creation error")
             }
             AuthError::InvalidToken => ApiResponse::<String>::un_authorized("Invalid token"),
+            AuthError::AccountDisabled => {
+                ApiResponse::<String>::un_authorized("Account is suspended or inactive")
+            }
         };
 
         response.into_response()
@@ -5,6 +5,7 @@ use axum::http::request::Parts;
 #[derive(Debug, Clone)]
 pub struct Lang {
     pub locale_code: String,
+    pub timezone: Option<String>,
 }
 
 impl Lang {
@@ -15,6 +16,7 @@ impl Lang {
     pub fn from(locale_code: &str) -> Self {
         Lang {
             locale_code: locale_code.to_string(),
+            timezone: None,
         }
     }
 }
@@ -26,16 +28,25 @@ where
     type Rejection = AuthError;
 
     async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        //viewer timezone in IANA format e.g Asia/Jakarta
+        let timezone = parts
+            .headers
+            .get("X-Timezone")
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string());
+
         let head = parts.headers.get("Accept-Language");
         if head.is_none() {
             return Ok(Lang {
                 locale_code: "id-ID".to_string(),
+                timezone,
             });
         }
         let head = head.unwrap();
 
         Ok(Lang {
             locale_code: head.to_str().unwrap_or("id-ID").to_string(),
+            timezone,
         })
     }
 }
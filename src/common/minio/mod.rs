@@ -143,4 +143,43 @@ impl MinIO {
             Err(e) => Err(format!("Error uploading file: {}", e)),
         }
     }
+
+    pub async fn copy_file(
+        &self,
+        from_path: String,
+        to_path: String,
+        bucket_name: String,
+    ) -> Result<String, String> {
+        let credentials = Credentials::new(
+            Some(self.access_key.clone().as_str()),
+            Some(self.secret_key.clone().as_str()),
+            None,
+            None,
+            None,
+        );
+        if credentials.is_err() {
+            return Err(credentials.unwrap_err().to_string());
+        }
+        let credentials = credentials.unwrap();
+        let bucket = Bucket::new(
+            bucket_name.as_str(),
+            Region::Custom {
+                region: "asia".to_string(),
+                endpoint: self.url_server.clone(),
+            },
+            credentials,
+        );
+        if bucket.is_err() {
+            return Err(bucket.unwrap_err().to_string());
+        }
+        let bucket = bucket.unwrap().with_path_style();
+
+        let copy = bucket
+            .copy_object_internal(from_path.as_str(), to_path.as_str())
+            .await;
+        match copy {
+            Ok(_) => Ok("Successfully copy file".to_string()),
+            Err(e) => Err(format!("Error copying file: {}", e)),
+        }
+    }
 }
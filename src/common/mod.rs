@@ -2,6 +2,7 @@ pub mod api_response;
 pub mod app_state;
 pub mod bson;
 pub mod env_config;
+pub mod ical;
 pub mod jwt;
 pub mod minio;
 pub mod redis;
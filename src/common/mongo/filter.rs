@@ -79,10 +79,10 @@ pub fn is_not_in<T: Into<Bson>>(column: &str, value: T) -> FilterGroup {
     when(column, Some("$nin"), value)
 }
 pub fn lower<T: Into<Bson>>(column: &str, value: T) -> FilterGroup {
-    when(column, Some("$le"), value)
+    when(column, Some("$lt"), value)
 }
 pub fn greater<T: Into<Bson>>(column: &str, value: T) -> FilterGroup {
-    when(column, Some("$ge"), value)
+    when(column, Some("$gt"), value)
 }
 pub fn lower_than_equal<T: Into<Bson>>(column: &str, value: T) -> FilterGroup {
     when(column, Some("$lte"), value)
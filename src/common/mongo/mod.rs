@@ -13,6 +13,7 @@ use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
 use tokio_stream::StreamExt;
 
+use super::constant::GEO_POINT_TYPE;
 use super::env_config::EnvConfig;
 
 pub mod delete;
@@ -31,6 +32,7 @@ pub struct DB {
     limit: Option<Document>,
     skip: Option<Document>,
     count: Option<Document>,
+    geo_near: Option<Document>,
 }
 
 impl DB {
@@ -43,6 +45,7 @@ impl DB {
             limit: None,
             skip: None,
             count: None,
+            geo_near: None,
         }
     }
 
@@ -72,9 +75,30 @@ impl DB {
         self
     }
 
+    //result come sorted by distance, meters is written to distance_field
+    pub fn near(
+        mut self,
+        column: &str,
+        lat: f64,
+        lng: f64,
+        max_distance: f64,
+        distance_field: &str,
+    ) -> Self {
+        self.geo_near = Some(doc! {
+            "$geoNear": {
+                "near": { "type": GEO_POINT_TYPE, "coordinates": [lng, lat] },
+                "key": column,
+                "distanceField": distance_field,
+                "maxDistance": max_distance,
+                "spherical": true
+            }
+        });
+        self
+    }
+
     pub fn sort(mut self, sort: Vec<(&str, i32)>) -> Self {
         let mut sort_doc = Document::new();
-        let mut doc = self.sort.clone().unwrap_or(Document::new());
+        let mut doc = self.get_sort();
         for (col, order) in sort {
             doc.insert(col, order);
         }
@@ -83,6 +107,29 @@ impl DB {
         self
     }
 
+    //used with get_all for cursor based pagination
+    pub fn limit(mut self, limit: i64) -> Self {
+        self.limit = Some(doc! { "$limit": limit });
+        self
+    }
+
+    //need $text in the filter, best match first
+    pub fn sort_by_text_score(mut self) -> Self {
+        let mut sort_doc = Document::new();
+        let mut doc = self.get_sort();
+        doc.insert("score", doc! { "$meta": "textScore" });
+        sort_doc.insert("$sort", doc);
+        self.sort = Some(sort_doc);
+        self
+    }
+
+    fn get_sort(&self) -> Document {
+        self.sort
+            .clone()
+            .and_then(|sort| sort.get_document("$sort").ok().cloned())
+            .unwrap_or(Document::new())
+    }
+
     pub fn filter<T: Into<FilterGroup>>(mut self, filters: Vec<T>) -> Self {
         let mut filter = self.filter.clone().unwrap_or(Filter::empty());
         for group in filters {
@@ -131,6 +178,10 @@ impl DB {
     }
     pub fn populate_pipeline(self) -> (Vec<Document>, Vec<Document>) {
         let mut pipeline = Vec::new();
+        //$geoNear only allowed as the first stage
+        if let Some(geo_near) = self.geo_near {
+            pipeline.push(geo_near);
+        }
         // let mut unwinds = Vec::new();
         if let Some(filter) = self.filter {
             let mut match_doc = Document::new();
@@ -354,6 +405,19 @@ impl DB {
         })
     }
 
+    pub async fn count(self, client: &Client) -> Result<i64, String> {
+        let db = client.database(&get_db_name());
+        let collection: Collection<Document> = db.collection(&self.collection);
+
+        let count = collection.count_documents(self.populate_filter()).await;
+        if count.is_err() {
+            let message = format!("{:?}", count.err().unwrap().kind);
+            info!(target: "db::count::error","{}",message.clone());
+            return Err(message);
+        }
+        Ok(count.unwrap() as i64)
+    }
+
     pub async fn find_one<T: DeserializeOwned>(
         self,
         query: Document,
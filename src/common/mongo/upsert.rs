@@ -12,6 +12,7 @@ pub struct Upsert {
     orm: DB,
     set: Option<Document>,
     inc: Option<Document>,
+    set_on_insert: Option<Document>,
 }
 
 impl Upsert {
@@ -20,6 +21,7 @@ impl Upsert {
             orm: DB::get(from),
             set: None,
             inc: None,
+            set_on_insert: None,
         }
     }
 
@@ -47,6 +49,12 @@ impl Upsert {
         self
     }
 
+    //only written when the filter match nothing and a new document is created
+    pub fn set_on_insert(mut self, doc: Document) -> Self {
+        self.set_on_insert = Some(doc);
+        self
+    }
+
     pub fn filter<T: Into<FilterGroup>>(mut self, filters: Vec<T>) -> Self {
         self.orm = self.orm.filter(filters);
         self
@@ -94,7 +102,11 @@ impl Upsert {
             doc.insert("$inc", inc);
         }
 
-        let save = collection.replace_one(query, doc).await;
+        if let Some(set_on_insert) = self.set_on_insert {
+            doc.insert("$setOnInsert", set_on_insert);
+        }
+
+        let save = collection.update_one(query, doc).upsert(true).await;
 
         if save.is_err() {
             let message = format!("{:?}", save.unwrap_err().kind);
@@ -105,7 +117,7 @@ impl Upsert {
         let save = save.unwrap();
 
         // info!(target: "db::UPDATE::oke","Success update  data");
-        Ok(save.modified_count)
+        Ok(save.modified_count + save.upserted_id.map_or(0, |_| 1))
     }
 
     pub async fn one_with_session<T: Serialize>(
@@ -141,7 +153,15 @@ impl Upsert {
             doc.insert("$inc", inc);
         }
 
-        let save = collection.replace_one(query, doc).session(session).await;
+        if let Some(set_on_insert) = self.set_on_insert {
+            doc.insert("$setOnInsert", set_on_insert);
+        }
+
+        let save = collection
+            .update_one(query, doc)
+            .upsert(true)
+            .session(session)
+            .await;
 
         if save.is_err() {
             let message = format!("{:?}", save.unwrap_err().kind);
@@ -152,7 +172,7 @@ impl Upsert {
         let save = save.unwrap();
 
         // info!(target: "db::UPDATE::oke","Success update  data");
-        Ok(save.modified_count)
+        Ok(save.modified_count + save.upserted_id.map_or(0, |_| 1))
     }
     pub fn show_merging(self) -> (Vec<Document>, Vec<Document>) {
         self.orm.populate_pipeline()
@@ -112,6 +112,38 @@ impl MultiFileExtractor {
         }
     }
 
+    pub fn ref_id_to_object(&self) -> Option<ObjectId> {
+        create_object_id_option(&self.ref_id.clone())
+    }
+
+    pub fn validate_body(&self) -> Result<bool, ValidationErrors> {
+        let body = self.validate();
+        if body.is_err() {
+            return Err(body.unwrap_err());
+        }
+
+        if self.temp_file.is_empty() {
+            let mut errors = ValidationErrors::new();
+            let _add = errors.add("file", ValidationError::new("file cannot empty"));
+            return Err(errors);
+        }
+
+        for (_, file) in &self.temp_file {
+            let validate = file.validate_file();
+            if validate.is_err() {
+                return validate;
+            }
+        }
+        Ok(true)
+    }
+
+    pub fn files(&self) -> Vec<FileTemp> {
+        self.temp_file
+            .iter()
+            .map(|(_, value)| value.clone())
+            .collect::<Vec<FileTemp>>()
+    }
+
     pub fn remove_file(&self) -> Result<String, String> {
         self.temp_file.iter().for_each(|(_, value)| {
             let _ok = value.remove_file();
@@ -3,5 +3,9 @@ pub(crate) mod permission {
         pub mod admin {
             pub const ALL: &str = "app::admin::all";
         }
+        pub mod moderation {
+            pub const REPORT: &str = "app::moderation::report";
+            pub const USER: &str = "app::moderation::user";
+        }
     }
 }
@@ -28,6 +28,21 @@ impl RedisClient {
     pub fn create_key_sign_in_session(&self, session_id: &str) -> String {
         format!("{}:session:sign_in:{}", self.mode, session_id)
     }
+    pub fn create_key_user_session(&self, user_id: &str) -> String {
+        format!("{}:session:user:{}", self.mode, user_id)
+    }
+    pub fn create_key_email_verification_session(&self, token: &str) -> String {
+        format!("{}:session:email_verification:{}", self.mode, token)
+    }
+    pub fn create_key_change_phone_session(&self, user_id: &str) -> String {
+        format!("{}:session:change_phone:{}", self.mode, user_id)
+    }
+    pub fn create_key_user_status(&self, user_id: &str) -> String {
+        format!("{}:user_status:{}", self.mode, user_id)
+    }
+    pub fn create_key_rate_limit(&self, action: &str, id: &str) -> String {
+        format!("{}:rate_limit:{}:{}", self.mode, action, id)
+    }
     pub fn create_key_sign_up_session(&self, session_id: &str) -> String {
         format!("{}:session:sign_up:{}", self.mode, session_id)
     }
@@ -156,12 +171,145 @@ impl RedisClient {
         self.client.del(key)
     }
 
+    //SESSION USER, index of sign in session owned by a user so it can be revoked at once
+    pub fn add_session_user(&mut self, user_id: &str, session_id: &str) -> RedisResult<i64> {
+        let key = self.create_key_user_session(user_id);
+        let saved: RedisResult<i64> = self.client.sadd(key.clone(), session_id);
+        let _: RedisResult<String> = self.client.expire(key, 3600);
+        saved
+    }
+
+    pub fn revoke_session_user(&mut self, user_id: &str) -> RedisResult<String> {
+        let key = self.create_key_user_session(user_id);
+        let session_ids: Vec<String> = self.client.smembers(key.clone())?;
+        for session_id in session_ids.iter() {
+            let _ = self.delete_session_sign_in(session_id);
+            let _ = self.delete_session_permission(session_id);
+        }
+        self.client.del(key)
+    }
+
+    //update every active sign in session of a user, used when profile data in session changed
+    pub fn refresh_session_user(
+        &mut self,
+        user_id: &str,
+        items: &[(&str, String)],
+    ) -> RedisResult<String> {
+        let key = self.create_key_user_session(user_id);
+        let session_ids: Vec<String> = self.client.smembers(key)?;
+        for session_id in session_ids.iter() {
+            let key = self.create_key_sign_in_session(session_id);
+            let exist: RedisResult<i16> = self.client.exists(key.clone());
+            if exist.unwrap_or(0) < 1 {
+                continue;
+            }
+            let _: RedisResult<String> = self.client.hset_multiple(key, items);
+        }
+        Ok("OK".to_string())
+    }
+    //END SESSION USER
+
+    //USER STATUS
+    pub fn set_cache_user_status(&mut self, user_id: &str, status: &str, ttl: i64) -> RedisResult<String> {
+        let key = self.create_key_user_status(user_id);
+        self.client.set_ex(key, status, ttl as u64)
+    }
+
+    pub fn get_cache_user_status(&mut self, user_id: &str) -> RedisResult<Option<String>> {
+        let key = self.create_key_user_status(user_id);
+        self.client.get(key)
+    }
+
+    pub fn delete_cache_user_status(&mut self, user_id: &str) -> RedisResult<String> {
+        let key = self.create_key_user_status(user_id);
+        self.client.del(key)
+    }
+    //END USER STATUS
+
+    //SESSION EMAIL VERIFICATION
+    pub fn set_session_email_verification(
+        &mut self,
+        token: &str,
+        items: &[(&str, String)],
+    ) -> RedisResult<String> {
+        let key = self.create_key_email_verification_session(token);
+        let saved: RedisResult<String> = self.client.hset_multiple(key.clone(), items);
+        let _: RedisResult<String> = self.client.expire(key, 3600);
+        saved
+    }
+
+    pub fn get_session_email_verification(
+        &mut self,
+        token: &str,
+    ) -> RedisResult<HashMap<String, String>> {
+        let key = self.create_key_email_verification_session(token);
+        self.client.hgetall(key)
+    }
+
+    pub fn delete_session_email_verification(&mut self, token: &str) -> RedisResult<String> {
+        let key = self.create_key_email_verification_session(token);
+        self.client.del(key)
+    }
+    //END EMAIL VERIFICATION
+
+    //SESSION CHANGE PHONE, one pending change per user
+    pub fn set_session_change_phone(
+        &mut self,
+        user_id: &str,
+        items: &[(&str, String)],
+    ) -> RedisResult<String> {
+        let key = self.create_key_change_phone_session(user_id);
+        let saved: RedisResult<String> = self.client.hset_multiple(key.clone(), items);
+        let _: RedisResult<String> = self.client.expire(key, 3600);
+        saved
+    }
+
+    pub fn get_session_change_phone(
+        &mut self,
+        user_id: &str,
+    ) -> RedisResult<HashMap<String, String>> {
+        let key = self.create_key_change_phone_session(user_id);
+        self.client.hgetall(key)
+    }
+
+    pub fn delete_session_change_phone(&mut self, user_id: &str) -> RedisResult<String> {
+        let key = self.create_key_change_phone_session(user_id);
+        self.client.del(key)
+    }
+    //END CHANGE PHONE
+
     //DELETE SESSION
     pub fn delete_session_reset_password(&mut self, session_id: &str) -> RedisResult<String> {
         let key = self.create_key_reset_password_session(session_id);
         self.client.del(key)
     }
 
+    //RATE LIMIT, fixed window counter, return false once the limit is passed
+    pub fn hit_rate_limit(&mut self, action: &str, id: &str, limit: i64, window: i64) -> bool {
+        self.hit_rate_limit_by(action, id, 1, limit, window)
+    }
+
+    pub fn hit_rate_limit_by(
+        &mut self,
+        action: &str,
+        id: &str,
+        amount: i64,
+        limit: i64,
+        window: i64,
+    ) -> bool {
+        let key = self.create_key_rate_limit(action, id);
+        let count: RedisResult<i64> = self.client.incr(key.clone(), amount);
+        if count.is_err() {
+            return false;
+        }
+        let count = count.unwrap();
+        if count == amount {
+            let _: RedisResult<String> = self.client.expire(key, window);
+        }
+        count <= limit
+    }
+    //END RATE LIMIT
+
     //OTHER
     pub async fn exist(&mut self, key: &str) -> bool {
         let result: RedisResult<i16> = self.client.exists(key);
@@ -1,10 +1,17 @@
-use crate::common::constant::{COLLECTION_EVENTS, COLLECTION_USERS};
+use crate::common::constant::{
+    COLLECTION_EVENT_BAN, COLLECTION_EVENT_INVITATION, COLLECTION_EVENT_ROLE_INVITATION, COLLECTION_EVENT_TEMPLATE,
+    COLLECTION_EVENTS, COLLECTION_MUTUAL, COLLECTION_USER_CONTACT, COLLECTION_USER_REPORT, COLLECTION_USER_AUDIT_LOG, COLLECTION_USER_STATUS_LOG, COLLECTION_USERS,
+    GEO_POINT_TYPE,
+};
 use crate::common::mongo::get_db_name;
 use crate::common::utils::create_object_id_option;
-use bson::{Document, doc};
+use crate::feature::event::event_link::backfill_event_links;
+use bson::{Bson, Document, doc};
 use log::info;
 use mongodb::options::IndexOptions;
 use mongodb::{Client, IndexModel};
+use std::time::Duration;
+use tokio_stream::StreamExt;
 
 pub async fn init_seeder(db_client: &Client) {
     info!(target: "seeder","Spread the seed...");
@@ -63,5 +70,378 @@ pub async fn init_seeder(db_client: &Client) {
         )
         .await;
 
+    let _index_geo_event = &db_client
+        .database(&get_db_name())
+        .collection::<Document>(COLLECTION_EVENTS)
+        .create_index(
+            IndexModel::builder()
+                .keys(doc! { "venue_location.point": "2dsphere" })
+                .options(
+                    IndexOptions::builder()
+                        .name("event-index-venue-point".to_string())
+                        .unique(false)
+                        .build(),
+                )
+                .build(),
+        )
+        .await;
+
+    let _index_event_slug = &db_client
+        .database(&get_db_name())
+        .collection::<Document>(COLLECTION_EVENTS)
+        .create_index(
+            IndexModel::builder()
+                .keys(doc! { "slug": 1 })
+                .options(
+                    IndexOptions::builder()
+                        .name("event-index-slug".to_string())
+                        .unique(true)
+                        .partial_filter_expression(doc! { "slug": { "$type": "string" } })
+                        .build(),
+                )
+                .build(),
+        )
+        .await;
+
+    let _index_event_previous_slug = &db_client
+        .database(&get_db_name())
+        .collection::<Document>(COLLECTION_EVENTS)
+        .create_index(
+            IndexModel::builder()
+                .keys(doc! { "previous_slugs": 1 })
+                .options(
+                    IndexOptions::builder()
+                        .name("event-index-previous-slug".to_string())
+                        .unique(false)
+                        .build(),
+                )
+                .build(),
+        )
+        .await;
+
+    let _index_event_invitation_id = &db_client
+        .database(&get_db_name())
+        .collection::<Document>(COLLECTION_EVENTS)
+        .create_index(
+            IndexModel::builder()
+                .keys(doc! { "invitation_id": 1 })
+                .options(
+                    IndexOptions::builder()
+                        .name("event-index-invitation-id".to_string())
+                        .unique(false)
+                        .build(),
+                )
+                .build(),
+        )
+        .await;
+
+    let _index_invitation_code = &db_client
+        .database(&get_db_name())
+        .collection::<Document>(COLLECTION_EVENT_INVITATION)
+        .create_index(
+            IndexModel::builder()
+                .keys(doc! { "invitation_code": 1 })
+                .options(
+                    IndexOptions::builder()
+                        .name("invitation-index-code".to_string())
+                        .unique(false)
+                        .build(),
+                )
+                .build(),
+        )
+        .await;
+
+    let _index_invitation_phone = &db_client
+        .database(&get_db_name())
+        .collection::<Document>(COLLECTION_EVENT_INVITATION)
+        .create_index(
+            IndexModel::builder()
+                .keys(doc! { "phone_number": 1, "user_id": 1 })
+                .options(
+                    IndexOptions::builder()
+                        .name("invitation-index-phone-user".to_string())
+                        .unique(false)
+                        .sparse(true)
+                        .build(),
+                )
+                .build(),
+        )
+        .await;
+
+    let _index_user_phone_hash = &db_client
+        .database(&get_db_name())
+        .collection::<Document>(COLLECTION_USERS)
+        .create_index(
+            IndexModel::builder()
+                .keys(doc! { "phone_hash": 1 })
+                .options(
+                    IndexOptions::builder()
+                        .name("user-index-phone-hash".to_string())
+                        .unique(false)
+                        .sparse(true)
+                        .build(),
+                )
+                .build(),
+        )
+        .await;
+
+    let _index_user_contact_user = &db_client
+        .database(&get_db_name())
+        .collection::<Document>(COLLECTION_USER_CONTACT)
+        .create_index(
+            IndexModel::builder()
+                .keys(doc! { "user_id": 1, "phone_hash": 1 })
+                .options(
+                    IndexOptions::builder()
+                        .name("user-contact-index-user-hash".to_string())
+                        .unique(false)
+                        .build(),
+                )
+                .build(),
+        )
+        .await;
+
+    //uploaded hashes are dropped by mongo once expires_at passed
+    let _index_user_contact_expiry = &db_client
+        .database(&get_db_name())
+        .collection::<Document>(COLLECTION_USER_CONTACT)
+        .create_index(
+            IndexModel::builder()
+                .keys(doc! { "expires_at": 1 })
+                .options(
+                    IndexOptions::builder()
+                        .name("user-contact-index-expiry".to_string())
+                        .expire_after(Duration::from_secs(0))
+                        .build(),
+                )
+                .build(),
+        )
+        .await;
+
+    //get_list_mutual search with $text
+    let _index_mutual_display_name = &db_client
+        .database(&get_db_name())
+        .collection::<Document>(COLLECTION_MUTUAL)
+        .create_index(
+            IndexModel::builder()
+                .keys(doc! { "display_name": "text" })
+                .options(
+                    IndexOptions::builder()
+                        .name("mutual-index-display-name".to_string())
+                        .unique(false)
+                        .build(),
+                )
+                .build(),
+        )
+        .await;
+
+    //the unique pair index cannot be built while old friend request left duplicate rows
+    match dedupe_mutual_pair(db_client).await {
+        Ok(total) => info!(target: "seeder","dedupe mutual pair {}",total),
+        Err(why) => info!(target: "seeder","dedupe mutual pair failed {}",why),
+    }
+    let index_mutual_user_pair = db_client
+        .database(&get_db_name())
+        .collection::<Document>(COLLECTION_MUTUAL)
+        .create_index(
+            IndexModel::builder()
+                .keys(doc! { "user_id": 1, "mutual_id": 1 })
+                .options(
+                    IndexOptions::builder()
+                        .name("mutual-index-user-pair".to_string())
+                        .unique(true)
+                        .build(),
+                )
+                .build(),
+        )
+        .await;
+    if let Err(why) = index_mutual_user_pair {
+        info!(target: "seeder","create mutual-index-user-pair failed {:?}",why);
+    }
+
+    let _index_user_report_status = &db_client
+        .database(&get_db_name())
+        .collection::<Document>(COLLECTION_USER_REPORT)
+        .create_index(
+            IndexModel::builder()
+                .keys(doc! { "status": 1, "created_at": -1 })
+                .options(
+                    IndexOptions::builder()
+                        .name("user-report-index-status".to_string())
+                        .unique(false)
+                        .build(),
+                )
+                .build(),
+        )
+        .await;
+
+    let _index_mutual_status = &db_client
+        .database(&get_db_name())
+        .collection::<Document>(COLLECTION_MUTUAL)
+        .create_index(
+            IndexModel::builder()
+                .keys(doc! { "mutual_id": 1, "status": 1 })
+                .options(
+                    IndexOptions::builder()
+                        .name("mutual-index-mutual-status".to_string())
+                        .unique(false)
+                        .build(),
+                )
+                .build(),
+        )
+        .await;
+
+    let _index_user_status_log_user = &db_client
+        .database(&get_db_name())
+        .collection::<Document>(COLLECTION_USER_STATUS_LOG)
+        .create_index(
+            IndexModel::builder()
+                .keys(doc! { "user_id": 1, "created_at": -1 })
+                .options(
+                    IndexOptions::builder()
+                        .name("user-status-log-index-user".to_string())
+                        .unique(false)
+                        .build(),
+                )
+                .build(),
+        )
+        .await;
+
+    let _index_user_audit_log_user = &db_client
+        .database(&get_db_name())
+        .collection::<Document>(COLLECTION_USER_AUDIT_LOG)
+        .create_index(
+            IndexModel::builder()
+                .keys(doc! { "user_id": 1, "created_at": -1 })
+                .options(
+                    IndexOptions::builder()
+                        .name("user-audit-log-index-user".to_string())
+                        .unique(false)
+                        .build(),
+                )
+                .build(),
+        )
+        .await;
+
+    let _index_event_template_user = &db_client
+        .database(&get_db_name())
+        .collection::<Document>(COLLECTION_EVENT_TEMPLATE)
+        .create_index(
+            IndexModel::builder()
+                .keys(doc! { "user_id": 1, "updated_at": -1 })
+                .options(
+                    IndexOptions::builder()
+                        .name("event-template-index-user".to_string())
+                        .unique(false)
+                        .build(),
+                )
+                .build(),
+        )
+        .await;
+
+    let _index_role_invitation_user = &db_client
+        .database(&get_db_name())
+        .collection::<Document>(COLLECTION_EVENT_ROLE_INVITATION)
+        .create_index(
+            IndexModel::builder()
+                .keys(doc! { "user_id": 1, "status": 1 })
+                .options(
+                    IndexOptions::builder()
+                        .name("role-invitation-index-user-status".to_string())
+                        .unique(false)
+                        .build(),
+                )
+                .build(),
+        )
+        .await;
+
+    let _index_event_ban = &db_client
+        .database(&get_db_name())
+        .collection::<Document>(COLLECTION_EVENT_BAN)
+        .create_index(
+            IndexModel::builder()
+                .keys(doc! { "event_id": 1, "user_id": 1 })
+                .options(
+                    IndexOptions::builder()
+                        .name("event-ban-index-event-user".to_string())
+                        .unique(true)
+                        .build(),
+                )
+                .build(),
+        )
+        .await;
+
+    //events saved before nearby search only have lat/lng, the 2dsphere index skip them
+    let backfill_venue_point = db_client
+        .database(&get_db_name())
+        .collection::<Document>(COLLECTION_EVENTS)
+        .update_many(
+            doc! {
+                "venue_location.point": { "$exists": false },
+                "venue_location.lat": { "$type": "number" },
+                "venue_location.lng": { "$type": "number" },
+            },
+            vec![doc! {
+                "$set": {
+                    "venue_location.point": {
+                        "type": GEO_POINT_TYPE,
+                        "coordinates": ["$venue_location.lng", "$venue_location.lat"]
+                    }
+                }
+            }],
+        )
+        .await;
+    match backfill_venue_point {
+        Ok(result) => {
+            info!(target: "seeder","backfill venue point {}",result.modified_count)
+        }
+        Err(why) => info!(target: "seeder","backfill venue point failed {:?}",why),
+    }
+
+    match backfill_event_links(db_client).await {
+        Ok(total) => info!(target: "seeder","backfill event link {}",total),
+        Err(why) => info!(target: "seeder","backfill event link failed {}",why),
+    }
+
     info!(target: "seeder", "seeding completed application ready");
 }
+
+//keep the latest updated row of every user pair
+async fn dedupe_mutual_pair(db_client: &Client) -> Result<u64, String> {
+    let collection = db_client
+        .database(&get_db_name())
+        .collection::<Document>(COLLECTION_MUTUAL);
+    let duplicates = collection
+        .aggregate(vec![
+            doc! { "$sort": { "updated_at": -1 } },
+            doc! {
+                "$group": {
+                    "_id": { "user_id": "$user_id", "mutual_id": "$mutual_id" },
+                    "ids": { "$push": "$_id" },
+                    "count": { "$sum": 1 }
+                }
+            },
+            doc! { "$match": { "count": { "$gt": 1 } } },
+        ])
+        .await
+        .map_err(|why| format!("{:?}", why.kind))?
+        .collect::<Result<Vec<Document>, _>>()
+        .await
+        .map_err(|why| why.to_string())?;
+
+    let mut stale_ids = Vec::<Bson>::new();
+    for duplicate in duplicates {
+        if let Ok(ids) = duplicate.get_array("ids") {
+            stale_ids.extend(ids.iter().skip(1).cloned());
+        }
+    }
+    if stale_ids.is_empty() {
+        return Ok(0);
+    }
+
+    collection
+        .delete_many(doc! { "_id": { "$in": stale_ids } })
+        .await
+        .map(|result| result.deleted_count)
+        .map_err(|why| format!("{:?}", why.kind))
+}
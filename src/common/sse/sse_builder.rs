@@ -5,6 +5,7 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SseTarget {
     user_id: Vec<String>,
+    exclude_user_id: Vec<String>,
     device_id: String,
     event_name: String,
     is_broadcast: bool,
@@ -21,6 +22,7 @@ impl SseTarget {
     pub fn create() -> Self {
         SseTarget {
             user_id: vec![],
+            exclude_user_id: vec![],
             device_id: "".to_string(),
             event_name: "".to_string(),
             is_broadcast: false,
@@ -31,6 +33,7 @@ impl SseTarget {
     pub fn broadcast(event_name: String) -> Self {
         SseTarget {
             user_id: vec![],
+            exclude_user_id: vec![],
             device_id: "".to_string(),
             event_name: event_name,
             is_broadcast: true,
@@ -53,6 +56,12 @@ impl SseTarget {
         self
     }
 
+    //receiver that must not get the message, e.g. who blocked the sender
+    pub fn set_exclude_user_ids(mut self, user_ids: Vec<String>) -> Self {
+        self.exclude_user_id = user_ids;
+        self
+    }
+
     pub fn set_device_id(mut self, device_id: String) -> Self {
         self.device_id = device_id;
         self.is_to_device = true;
@@ -68,6 +77,9 @@ impl SseTarget {
     pub fn user_id(&self) -> &Vec<String> {
         &self.user_id
     }
+    pub fn exclude_user_id(&self) -> &Vec<String> {
+        &self.exclude_user_id
+    }
 
     pub fn is_broadcast(&self) -> bool {
         self.is_broadcast
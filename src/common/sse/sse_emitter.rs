@@ -128,10 +128,17 @@ impl SseBroadcaster {
     pub async fn send<T: serde::Serialize>(&self, builder: SseBuilder<T>) {
         let target = builder.get_target();
         if target.is_broadcast() {
-            let _send = self.broadcast(&target.even_name(), &builder.data).await;
+            let _send = self
+                .broadcast(&target.even_name(), target.exclude_user_id(), &builder.data)
+                .await;
         } else {
+            let users = target
+                .user_id()
+                .iter()
+                .filter(|user| !target.exclude_user_id().contains(user))
+                .collect::<Vec<&String>>();
             if target.is_to_device() {
-                for user in target.user_id() {
+                for user in users {
                     let _send = self.send_to_user_device(
                         &user,
                         target.device_id(),
@@ -141,7 +148,7 @@ impl SseBroadcaster {
                         .await;
                 }
             } else {
-                for user in target.user_id() {
+                for user in users {
                     let _send =self.send_to_user(&user, &target.even_name(), &builder.data)
                         .await;
                 }
@@ -149,12 +156,20 @@ impl SseBroadcaster {
         }
     }
 
-    async fn broadcast<T: serde::Serialize>(&self, event_name: &String, data: &T) {
+    async fn broadcast<T: serde::Serialize>(
+        &self,
+        event_name: &String,
+        exclude_user_id: &Vec<String>,
+        data: &T,
+    ) {
         let clients = self.inner.lock().unwrap().clients.clone();
         let event = Event::default().event(event_name).json_data(data).unwrap();
 
         if !clients.is_empty() {
-            for (_, users) in clients {
+            for (user_id, users) in clients {
+                if exclude_user_id.contains(&user_id) {
+                    continue;
+                }
                 for (_, client) in users {
                     let _ = client.send(event.clone()).await;
                 }
@@ -1,10 +1,23 @@
 use super::multipart_file::{MultiFileExtractor, SingleFileExtractor};
 use crate::common::api_response::ApiResponse;
+use crate::common::constant::{
+    EVENT_GUEST_ROLE_CO_HOST, EVENT_GUEST_ROLE_GUEST, EVENT_GUEST_RSVP_GOING, EVENT_GUEST_RSVP_MAYBE, EVENT_GUEST_RSVP_NOT_GOING,
+    EVENT_STATUS_ARCHIVED, EVENT_STATUS_CANCELLED, EVENT_STATUS_COMPLETED, EVENT_STATUS_DRAFT,
+    EVENT_STATUS_PUBLISHED, EVENT_VISIBILITY_PRIVATE, EVENT_VISIBILITY_PUBLIC,
+    EVENT_VISIBILITY_UNLISTED,
+    SERIES_EDIT_SCOPE_ALL, SERIES_EDIT_SCOPE_FOLLOWING, SERIES_EDIT_SCOPE_THIS,
+    SHORT_CODE_ALPHABET, SHORT_CODE_LENGTH,
+    REPORT_STATUS_DISMISSED, REPORT_STATUS_RESOLVED, REPORT_TARGET_ALBUM, REPORT_TARGET_EVENT,
+    REPORT_TARGET_USER,
+    SERIES_FREQUENCY_DAILY, SERIES_FREQUENCY_MONTHLY, SERIES_FREQUENCY_WEEKLY,
+};
 use bson::oid::ObjectId;
-use chrono::{Local, NaiveDate, TimeZone, Utc};
+use chrono::{Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
 use log::info;
 use mime::Mime;
 use rand::Rng;
+use sha2::{Digest, Sha256};
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::fmt::Debug;
@@ -136,6 +149,173 @@ pub fn validate_gender(gender: &String) -> Result<(), ValidationError> {
     )
 }
 
+pub fn validate_rsvp(rsvp: &String) -> Result<(), ValidationError> {
+    let allowed = [
+        EVENT_GUEST_RSVP_GOING,
+        EVENT_GUEST_RSVP_MAYBE,
+        EVENT_GUEST_RSVP_NOT_GOING,
+    ];
+    if allowed.contains(&rsvp.as_str()) {
+        return Ok(());
+    }
+
+    Err(
+        ValidationError::new("Invalid RSVP, valid value going, maybe or not-going").with_message(
+            Cow::from("Invalid RSVP, valid value going, maybe or not-going"),
+        ),
+    )
+}
+
+pub fn validate_event_status(status: &String) -> Result<(), ValidationError> {
+    let allowed = [
+        EVENT_STATUS_DRAFT,
+        EVENT_STATUS_PUBLISHED,
+        EVENT_STATUS_CANCELLED,
+        EVENT_STATUS_COMPLETED,
+        EVENT_STATUS_ARCHIVED,
+    ];
+    if allowed.contains(&status.as_str()) {
+        return Ok(());
+    }
+
+    Err(
+        ValidationError::new("Invalid status, valid value DRAFT, PUBLISHED, CANCELLED, COMPLETED or ARCHIVED")
+            .with_message(Cow::from(
+                "Invalid status, valid value DRAFT, PUBLISHED, CANCELLED, COMPLETED or ARCHIVED",
+            )),
+    )
+}
+
+pub fn validate_event_visibility(visibility: &String) -> Result<(), ValidationError> {
+    let allowed = [
+        EVENT_VISIBILITY_PUBLIC,
+        EVENT_VISIBILITY_UNLISTED,
+        EVENT_VISIBILITY_PRIVATE,
+    ];
+    if allowed.contains(&visibility.as_str()) {
+        return Ok(());
+    }
+
+    Err(
+        ValidationError::new("Invalid visibility, valid value PUBLIC, UNLISTED or PRIVATE")
+            .with_message(Cow::from(
+                "Invalid visibility, valid value PUBLIC, UNLISTED or PRIVATE",
+            )),
+    )
+}
+
+//local 08xx and +62 / dashed / spaced input all end up as 628xx, the format stored on users
+pub fn normalize_phone_number(phone_number: &str) -> String {
+    let mut phone_number = phone_number
+        .chars()
+        .filter(|char| !['-', '+', ' ', '(', ')'].contains(char))
+        .collect::<String>();
+    if phone_number.starts_with("08") {
+        phone_number = phone_number.replacen("08", "628", 1);
+    }
+    phone_number
+}
+
+//sign in used to replace every "08" of a local number, not only the prefix.
+//lookups by phone also try that form so accounts saved back then keep matching
+pub fn phone_number_variants(phone_number: &str) -> Vec<String> {
+    let mut variants = vec![phone_number.to_string()];
+    if let Some(rest) = phone_number.strip_prefix("628") {
+        let legacy = format!("628{}", rest.replace("08", "628"));
+        if legacy != phone_number {
+            variants.push(legacy);
+        }
+    }
+    variants
+}
+
+//contact sync only ever compare this digest, clients hash the same normalized number
+pub fn hash_phone_number(phone_number: &str) -> String {
+    let digest = Sha256::digest(normalize_phone_number(phone_number).as_bytes());
+    format!("{:x}", digest)
+}
+
+pub fn validate_invitation_role(role: &String) -> Result<(), ValidationError> {
+    let allowed = [EVENT_GUEST_ROLE_GUEST, EVENT_GUEST_ROLE_CO_HOST];
+    if allowed.contains(&role.as_str()) {
+        return Ok(());
+    }
+
+    Err(
+        ValidationError::new("Invalid role, valid value guest or co-host")
+            .with_message(Cow::from("Invalid role, valid value guest or co-host")),
+    )
+}
+
+pub fn validate_report_target(target_type: &String) -> Result<(), ValidationError> {
+    let allowed = [REPORT_TARGET_USER, REPORT_TARGET_EVENT, REPORT_TARGET_ALBUM];
+    if allowed.contains(&target_type.as_str()) {
+        return Ok(());
+    }
+
+    Err(
+        ValidationError::new("Invalid target, valid value user, event or album")
+            .with_message(Cow::from("Invalid target, valid value user, event or album")),
+    )
+}
+
+pub fn validate_report_resolution(status: &String) -> Result<(), ValidationError> {
+    let allowed = [REPORT_STATUS_RESOLVED, REPORT_STATUS_DISMISSED];
+    if allowed.contains(&status.as_str()) {
+        return Ok(());
+    }
+
+    Err(
+        ValidationError::new("Invalid status, valid value resolved or dismissed")
+            .with_message(Cow::from("Invalid status, valid value resolved or dismissed")),
+    )
+}
+
+pub fn validate_series_frequency(frequency: &String) -> Result<(), ValidationError> {
+    let allowed = [
+        SERIES_FREQUENCY_DAILY,
+        SERIES_FREQUENCY_WEEKLY,
+        SERIES_FREQUENCY_MONTHLY,
+    ];
+    if allowed.contains(&frequency.as_str()) {
+        return Ok(());
+    }
+
+    Err(
+        ValidationError::new("Invalid frequency, valid value daily, weekly or monthly")
+            .with_message(Cow::from(
+                "Invalid frequency, valid value daily, weekly or monthly",
+            )),
+    )
+}
+
+pub fn validate_series_scope(scope: &String) -> Result<(), ValidationError> {
+    let allowed = [
+        SERIES_EDIT_SCOPE_THIS,
+        SERIES_EDIT_SCOPE_FOLLOWING,
+        SERIES_EDIT_SCOPE_ALL,
+    ];
+    if allowed.contains(&scope.as_str()) {
+        return Ok(());
+    }
+
+    Err(
+        ValidationError::new("Invalid scope, valid value this, following or all")
+            .with_message(Cow::from("Invalid scope, valid value this, following or all")),
+    )
+}
+
+pub fn validate_timezone(timezone: &String) -> Result<(), ValidationError> {
+    if Tz::from_str(timezone).is_ok() {
+        return Ok(());
+    }
+
+    Err(
+        ValidationError::new("Invalid timezone, use IANA name e.g Asia/Jakarta")
+            .with_message(Cow::from("Invalid timezone, use IANA name e.g Asia/Jakarta")),
+    )
+}
+
 static COUNTER: AtomicUsize = AtomicUsize::new(0);
 
 pub fn generate_member_code(prefix: &str) -> String {
@@ -232,3 +412,53 @@ pub fn string_to_chrono_datetime(date: &str) -> Result<chrono::DateTime<Utc>, St
     }
     Ok(datetime.unwrap())
 }
+
+//accept full rfc3339 datetime or a plain %Y-%m-%d date
+pub fn parse_bson_datetime(date: &str) -> Result<bson::DateTime, String> {
+    if let Ok(datetime) = bson::DateTime::parse_rfc3339_str(date) {
+        return Ok(datetime);
+    }
+    string_to_bson_datetime(date.to_string())
+}
+
+//datetime with offset is taken as is, local datetime without offset is read in the given timezone
+pub fn parse_datetime_in_timezone(date: &str, timezone: &str) -> Result<bson::DateTime, String> {
+    if let Ok(datetime) = bson::DateTime::parse_rfc3339_str(date) {
+        return Ok(datetime);
+    }
+    let tz = Tz::from_str(timezone);
+    if let Err(why) = tz {
+        return Err(why.to_string());
+    }
+    let tz = tz.unwrap();
+
+    let naive = ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(date, format).ok());
+    if let None = naive {
+        return Err(format!("Invalid datetime {}", date));
+    }
+
+    //skipped local time (dst gap) cannot be resolved, ambiguous time take the earliest
+    let local = tz.from_local_datetime(&naive.unwrap()).earliest();
+    if let None = local {
+        return Err(format!("Datetime {} does not exist in {}", date, timezone));
+    }
+    Ok(bson::DateTime::from_chrono(local.unwrap()))
+}
+
+//url safe secret used for unauthenticated links such as calendar feed
+pub fn generate_secret_token() -> String {
+    let mut rng = rand::rng();
+    (0..32)
+        .map(|_| format!("{:02x}", rng.random::<u8>()))
+        .collect::<String>()
+}
+
+pub fn generate_short_code() -> String {
+    let mut rng = rand::rng();
+    let alphabet = SHORT_CODE_ALPHABET.chars().collect::<Vec<char>>();
+    (0..SHORT_CODE_LENGTH)
+        .map(|_| alphabet[rng.random_range(0..alphabet.len())])
+        .collect::<String>()
+}
@@ -0,0 +1,60 @@
+use crate::common::bson::*;
+use crate::dto::user_dto::UserDTO;
+use crate::entity::event_activity::EventActivity;
+use bson::DateTime;
+use bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EventActivityDTO {
+    #[serde(
+        rename = "_id",
+        serialize_with = "serialize_object_id",
+        deserialize_with = "deserialize_object_id"
+    )]
+    pub id: Option<ObjectId>,
+    #[serde(
+        rename = "event_id",
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_object_id",
+        deserialize_with = "deserialize_object_id"
+    )]
+    pub event_id: Option<ObjectId>,
+    #[serde(
+        rename = "user_id",
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_object_id",
+        deserialize_with = "deserialize_object_id"
+    )]
+    pub user_id: Option<ObjectId>,
+    pub user: Option<UserDTO>,
+    #[serde(
+        rename = "ref_id",
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_object_id",
+        deserialize_with = "deserialize_object_id"
+    )]
+    pub ref_id: Option<ObjectId>,
+    pub activity_type: String,
+    pub payload: Option<serde_json::Value>,
+    #[serde(serialize_with = "serialize_datetime")]
+    pub created_at: DateTime,
+    #[serde(serialize_with = "serialize_datetime")]
+    pub updated_at: DateTime,
+}
+
+impl Into<EventActivityDTO> for EventActivity {
+    fn into(self) -> EventActivityDTO {
+        EventActivityDTO {
+            id: self.id,
+            event_id: self.event_id,
+            user_id: self.user_id,
+            user: None,
+            ref_id: self.ref_id,
+            activity_type: self.activity_type,
+            payload: self.payload,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+}
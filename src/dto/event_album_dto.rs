@@ -0,0 +1,58 @@
+use crate::common::bson::*;
+use crate::dto::user_dto::UserDTO;
+use crate::entity::event_album::EventAlbum;
+use bson::DateTime;
+use bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EventAlbumDTO {
+    #[serde(
+        rename = "_id",
+        serialize_with = "serialize_object_id",
+        deserialize_with = "deserialize_object_id"
+    )]
+    pub id: Option<ObjectId>,
+    #[serde(
+        rename = "event_id",
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_object_id",
+        deserialize_with = "deserialize_object_id"
+    )]
+    pub event_id: Option<ObjectId>,
+    #[serde(
+        rename = "user_id",
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_object_id",
+        deserialize_with = "deserialize_object_id"
+    )]
+    pub user_id: Option<ObjectId>,
+    pub user: Option<UserDTO>,
+    pub file_name: String,
+    pub mime_type: String,
+    pub bucket: String,
+    pub path: String,
+    pub is_hidden: bool,
+    #[serde(serialize_with = "serialize_datetime")]
+    pub created_at: DateTime,
+    #[serde(serialize_with = "serialize_datetime")]
+    pub updated_at: DateTime,
+}
+
+impl Into<EventAlbumDTO> for EventAlbum {
+    fn into(self) -> EventAlbumDTO {
+        EventAlbumDTO {
+            id: self.id,
+            event_id: self.event_id,
+            user_id: self.user_id,
+            user: None,
+            file_name: self.file_name,
+            mime_type: self.mime_type,
+            bucket: self.bucket,
+            path: self.path,
+            is_hidden: self.is_hidden,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+}
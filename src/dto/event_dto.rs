@@ -8,6 +8,8 @@ use crate::{
     entity::{event_image::EventImage, theme::Theme},
 };
 
+use super::event_ranking_dto::EventRankingDTO;
+use super::event_schedule_dto::EventScheduleDTO;
 use super::user_dto::UserDTO;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -42,13 +44,43 @@ pub struct EventDTO {
     )]
     pub theme_id: Option<ObjectId>,
     pub theme: Option<Theme>,
+    #[serde(
+        rename = "series_id",
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_object_id",
+        deserialize_with = "deserialize_object_id"
+    )]
+    pub series_id: Option<ObjectId>,
     pub invitation_id: String,
+    #[serde(default)]
+    pub slug: Option<String>,
+    #[serde(default, skip_serializing)]
+    pub previous_slugs: Vec<String>,
     pub event_name: String,
     pub event_description: String,
     pub status: String,
+    #[serde(default)]
+    pub allowed_transitions: Vec<String>,
     pub datetime: Option<DateTime>,
+    #[serde(default)]
+    pub end_datetime: Option<DateTime>,
+    #[serde(default)]
+    pub timezone: Option<String>,
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub schedule: Option<EventScheduleDTO>,
     pub venue_location: Option<VenueLocationDTO>,
+    //meters from the searched point, only filled by nearby search
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub distance: Option<f64>,
+    //only filled by discovery feed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ranking: Option<EventRankingDTO>,
     pub config: EventConfigDTO,
+    #[serde(default)]
+    pub revision: i64,
     #[serde(serialize_with = "serialize_datetime")]
     pub created_at: DateTime,
     #[serde(serialize_with = "serialize_datetime")]
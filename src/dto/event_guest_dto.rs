@@ -1,6 +1,7 @@
 use bson::DateTime;
 use bson::oid::ObjectId;
 use serde::{Deserialize, Serialize};
+use crate::common::bson::serialize_option_datetime;
 use crate::dto::event_dto::EventDTO;
 use crate::dto::user_dto::UserDTO;
 use crate::entity::event_guest::EventGuest;
@@ -17,6 +18,8 @@ pub struct EventGuestDTO {
     pub event:Option<EventDTO>,
     pub role: String,
     pub rsvp:Option<String>,
+    #[serde(default, serialize_with = "serialize_option_datetime")]
+    pub checked_in_at: Option<DateTime>,
     pub created_at: DateTime,
     pub updated_at: DateTime,
 }
@@ -32,6 +35,7 @@ impl Into<EventGuestDTO> for EventGuest {
             event: None,
             role: self.role,
             rsvp: self.rsvp,
+            checked_in_at: self.checked_in_at,
             created_at: self.created_at,
             updated_at: self.updated_at,
         }
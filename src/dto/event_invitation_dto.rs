@@ -17,7 +17,22 @@ pub struct EventInvitationDTO {
     pub event:Option<EventDTO>,
     pub invitation_type: String,
     pub invitation_code:String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phone_number: Option<String>,
     pub expires_at: Option<DateTime>,
+    #[serde(default)]
+    pub max_uses: Option<i64>,
+    #[serde(default)]
+    pub default_role: Option<String>,
+    #[serde(default)]
+    pub open_count: i64,
+    #[serde(default)]
+    pub redemption_count: i64,
+    //redemption per open, filled when listing link analytics
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conversion_rate: Option<f64>,
+    #[serde(default)]
+    pub revoked_at: Option<DateTime>,
     pub created_at: DateTime,
     pub updated_at: DateTime,
 }
@@ -32,7 +47,14 @@ impl Into<EventInvitationDTO> for EventInvitation{
             event: None,
             invitation_type: self.invitation_type,
             invitation_code: self.invitation_code,
+            phone_number: self.phone_number,
             expires_at: self.expires_at,
+            max_uses: self.max_uses,
+            default_role: self.default_role,
+            open_count: self.open_count,
+            redemption_count: self.redemption_count,
+            conversion_rate: None,
+            revoked_at: self.revoked_at,
             created_at: self.created_at,
             updated_at: self.updated_at,
         }
@@ -0,0 +1,8 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EventRankingDTO {
+    pub going_count: i64,
+    pub friends_going: i64,
+    pub score: f64,
+}
@@ -0,0 +1,44 @@
+use bson::DateTime;
+use bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+
+use crate::dto::event_dto::EventDTO;
+use crate::dto::user_dto::UserDTO;
+use crate::entity::event_role_invitation::EventRoleInvitation;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EventRoleInvitationDTO {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    #[serde(rename = "event_id", skip_serializing_if = "Option::is_none")]
+    pub event_id: Option<ObjectId>,
+    pub event: Option<EventDTO>,
+    #[serde(rename = "user_id", skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<ObjectId>,
+    #[serde(rename = "invited_by", skip_serializing_if = "Option::is_none")]
+    pub invited_by: Option<ObjectId>,
+    pub inviter: Option<UserDTO>,
+    pub role: String,
+    pub status: String,
+    pub responded_at: Option<DateTime>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+impl Into<EventRoleInvitationDTO> for EventRoleInvitation {
+    fn into(self) -> EventRoleInvitationDTO {
+        EventRoleInvitationDTO {
+            id: self.id,
+            event_id: self.event_id,
+            event: None,
+            user_id: self.user_id,
+            invited_by: self.invited_by,
+            inviter: None,
+            role: self.role,
+            status: self.status,
+            responded_at: self.responded_at,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+}
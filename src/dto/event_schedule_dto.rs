@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EventScheduleDTO {
+    pub timezone: String,
+    pub start_local: Option<String>,
+    pub end_local: Option<String>,
+    pub viewer_timezone: String,
+    pub start_viewer: Option<String>,
+    pub end_viewer: Option<String>,
+    pub display: Option<String>,
+    pub duration_minutes: Option<i64>,
+}
@@ -0,0 +1,75 @@
+use bson::DateTime;
+use bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+
+use crate::common::bson::*;
+use crate::dto::event_dto::EventDTO;
+use crate::entity::event_series::{EventSeries, RecurrenceRule};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RecurrenceRuleDTO {
+    pub frequency: String,
+    pub interval: i64,
+    pub count: Option<i64>,
+    #[serde(default, serialize_with = "serialize_option_datetime")]
+    pub until: Option<DateTime>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EventSeriesDTO {
+    #[serde(
+        rename = "_id",
+        serialize_with = "serialize_object_id",
+        deserialize_with = "deserialize_object_id"
+    )]
+    pub id: Option<ObjectId>,
+    #[serde(
+        rename = "host_id",
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_object_id",
+        deserialize_with = "deserialize_object_id"
+    )]
+    pub host_id: Option<ObjectId>,
+    #[serde(
+        rename = "template_event_id",
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_object_id",
+        deserialize_with = "deserialize_object_id"
+    )]
+    pub template_event_id: Option<ObjectId>,
+    pub rule: RecurrenceRuleDTO,
+    #[serde(serialize_with = "serialize_vec_datetime")]
+    pub exceptions: Vec<DateTime>,
+    #[serde(default)]
+    pub occurrences: Vec<EventDTO>,
+    #[serde(serialize_with = "serialize_datetime")]
+    pub created_at: DateTime,
+    #[serde(serialize_with = "serialize_datetime")]
+    pub updated_at: DateTime,
+}
+
+impl Into<RecurrenceRuleDTO> for RecurrenceRule {
+    fn into(self) -> RecurrenceRuleDTO {
+        RecurrenceRuleDTO {
+            frequency: self.frequency,
+            interval: self.interval,
+            count: self.count,
+            until: self.until,
+        }
+    }
+}
+
+impl Into<EventSeriesDTO> for EventSeries {
+    fn into(self) -> EventSeriesDTO {
+        EventSeriesDTO {
+            id: self.id,
+            host_id: self.host_id,
+            template_event_id: self.template_event_id,
+            rule: self.rule.into(),
+            exceptions: self.exceptions,
+            occurrences: Vec::new(),
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use bson::DateTime;
+use bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+
+use crate::dto::{event_config_dto::EventConfigDTO, venue_location_dto::VenueLocationDTO};
+use crate::entity::event_questionnaire::EventQuestionnaire;
+use crate::entity::event_template::EventTemplate;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EventTemplateDTO {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    #[serde(rename = "user_id", skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<ObjectId>,
+    pub name: String,
+    #[serde(rename = "image_id")]
+    pub image_id: Option<ObjectId>,
+    #[serde(rename = "theme_id")]
+    pub theme_id: Option<ObjectId>,
+    pub event_name: String,
+    pub event_description: String,
+    pub timezone: Option<String>,
+    #[serde(default)]
+    pub category: Option<String>,
+    pub venue_location: Option<VenueLocationDTO>,
+    pub config: EventConfigDTO,
+    #[serde(default)]
+    pub questionnaire: Vec<EventQuestionnaire>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+impl Into<EventTemplateDTO> for EventTemplate {
+    fn into(self) -> EventTemplateDTO {
+        EventTemplateDTO {
+            id: self.id,
+            user_id: self.user_id,
+            name: self.name,
+            image_id: self.image_id,
+            theme_id: self.theme_id,
+            event_name: self.event_name,
+            event_description: self.event_description,
+            timezone: self.timezone,
+            category: self.category,
+            venue_location: self.venue_location,
+            config: self.config,
+            questionnaire: self.questionnaire,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+}
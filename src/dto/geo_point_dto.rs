@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+use crate::common::constant::GEO_POINT_TYPE;
+
+//geojson keep coordinates as [lng, lat]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GeoPointDTO {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub coordinates: Vec<f64>,
+}
+
+impl GeoPointDTO {
+    pub fn new(lat: f64, lng: f64) -> Self {
+        GeoPointDTO {
+            kind: GEO_POINT_TYPE.to_string(),
+            coordinates: vec![lng, lat],
+        }
+    }
+}
@@ -10,3 +10,14 @@ pub mod venue_location_dto;
 pub mod theme_dto;
 pub mod event_guest_dto;
 pub mod event_invitation_dto;
+pub mod event_album_dto;
+pub mod event_activity_dto;
+pub mod event_series_dto;
+pub mod event_schedule_dto;
+pub mod geo_point_dto;
+pub mod event_ranking_dto;
+pub mod event_template_dto;
+pub mod event_role_invitation_dto;
+pub mod user_contact_dto;
+pub mod user_report_dto;
+pub mod user_summary_dto;
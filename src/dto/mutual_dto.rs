@@ -14,6 +14,12 @@ pub struct MutualDTO {
     #[serde(rename = "mutual_id", skip_serializing_if = "Option::is_none")]
     pub mutual_id: Option<ObjectId>,
     pub mutual: Option<UserDTO>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
     pub created_at: DateTime,
     pub updated_at: DateTime,
 }
@@ -26,6 +32,9 @@ impl Into<MutualDTO> for Mutual {
             user: None,
             mutual_id: self.mutual_id,
             mutual: None,
+            display_name: self.display_name,
+            status: self.status,
+            source: self.source,
             created_at: self.created_at,
             updated_at: self.updated_at,
         }
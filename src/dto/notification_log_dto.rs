@@ -38,6 +38,14 @@ pub struct NotificationLogDTO {
     )]
     pub user_id: Option<ObjectId>,
     pub user: Option<UserDTO>,
+    #[serde(
+        rename = "sender_id",
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_object_id",
+        deserialize_with = "deserialize_object_id"
+    )]
+    pub sender_id: Option<ObjectId>,
     pub is_read: bool,
     #[serde(serialize_with = "serialize_datetime")]
     pub created_at: DateTime,
@@ -54,6 +62,7 @@ impl Into<NotificationLogDTO> for NotificationLog {
             ref_id: self.ref_id,
             user_id: self.user_id,
             user: None,
+            sender_id: self.sender_id,
             is_read: self.is_read,
             created_at: self.created_at,
             updated_at: self.updated_at,
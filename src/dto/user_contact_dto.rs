@@ -0,0 +1,36 @@
+use bson::DateTime;
+use bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+
+use crate::dto::user_summary_dto::UserSummaryDTO;
+use crate::entity::user_contact::UserContact;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UserContactDTO {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    #[serde(rename = "user_id", skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<ObjectId>,
+    pub phone_hash: String,
+    #[serde(rename = "matched_user_id", skip_serializing_if = "Option::is_none")]
+    pub matched_user_id: Option<ObjectId>,
+    pub matched_user: Option<UserSummaryDTO>,
+    pub expires_at: DateTime,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+impl Into<UserContactDTO> for UserContact {
+    fn into(self) -> UserContactDTO {
+        UserContactDTO {
+            id: self.id,
+            user_id: self.user_id,
+            phone_hash: self.phone_hash,
+            matched_user_id: self.matched_user_id,
+            matched_user: None,
+            expires_at: self.expires_at,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+}
@@ -27,6 +27,8 @@ pub struct UserDTO {
     #[serde(serialize_with = "serialize_option_datetime")]
     pub last_logged_in: Option<DateTime>,
     pub status: Option<String>,
+    #[serde(default)]
+    pub status_reason: Option<String>,
     #[serde(serialize_with = "serialize_datetime")]
     pub created_at: DateTime,
     #[serde(serialize_with = "serialize_datetime")]
@@ -52,6 +54,7 @@ impl Into<UserDTO> for User {
                 .map_or_else(|| None, |value| Some(value.into())),
             last_logged_in: self.last_logged_in,
             status: self.status,
+            status_reason: self.status_reason,
             created_at: self.created_at,
             updated_at: self.updated_at,
             confirmation_at: self.confirmation_at,
@@ -5,12 +5,21 @@ use crate::entity::user_metadata::UserMetaData;
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct UserMetaDataDTO {
     pub providers: Option<Vec<String>>,
+    #[serde(default)]
+    pub bio: Option<String>,
+    #[serde(default)]
+    pub gender: Option<String>,
+    #[serde(default)]
+    pub date_of_birth: Option<String>,
 }
 
 impl Into<UserMetaDataDTO> for UserMetaData {
     fn into(self) -> UserMetaDataDTO {
         UserMetaDataDTO {
             providers: self.providers,
+            bio: self.bio,
+            gender: self.gender,
+            date_of_birth: self.date_of_birth,
         }
     }
 }
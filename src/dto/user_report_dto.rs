@@ -0,0 +1,52 @@
+use bson::DateTime;
+use bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+
+use crate::dto::user_dto::UserDTO;
+use crate::entity::user_report::UserReport;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UserReportDTO {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    #[serde(rename = "reporter_id", skip_serializing_if = "Option::is_none")]
+    pub reporter_id: Option<ObjectId>,
+    pub reporter: Option<UserDTO>,
+    pub target_type: String,
+    #[serde(rename = "target_id", skip_serializing_if = "Option::is_none")]
+    pub target_id: Option<ObjectId>,
+    #[serde(rename = "reported_user_id", skip_serializing_if = "Option::is_none")]
+    pub reported_user_id: Option<ObjectId>,
+    pub reported_user: Option<UserDTO>,
+    pub reason: String,
+    pub detail: Option<String>,
+    pub status: String,
+    pub resolution_note: Option<String>,
+    #[serde(rename = "resolved_by", skip_serializing_if = "Option::is_none")]
+    pub resolved_by: Option<ObjectId>,
+    pub resolved_at: Option<DateTime>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+impl Into<UserReportDTO> for UserReport {
+    fn into(self) -> UserReportDTO {
+        UserReportDTO {
+            id: self.id,
+            reporter_id: self.reporter_id,
+            reporter: None,
+            target_type: self.target_type,
+            target_id: self.target_id,
+            reported_user_id: self.reported_user_id,
+            reported_user: None,
+            reason: self.reason,
+            detail: self.detail,
+            status: self.status,
+            resolution_note: self.resolution_note,
+            resolved_by: self.resolved_by,
+            resolved_at: self.resolved_at,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+
+use crate::common::bson::*;
+
+use super::profile_picture_dto::ProfilePictureDTO;
+use super::user_dto::UserDTO;
+
+//public face of a user, safe to return to someone who only know the phone hash
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UserSummaryDTO {
+    #[serde(
+        rename = "_id",
+        serialize_with = "serialize_object_id",
+        deserialize_with = "deserialize_object_id"
+    )]
+    pub id: Option<ObjectId>,
+    pub display_name: String,
+    pub profile_picture: Option<ProfilePictureDTO>,
+}
+
+impl Into<UserSummaryDTO> for UserDTO {
+    fn into(self) -> UserSummaryDTO {
+        UserSummaryDTO {
+            id: self.id,
+            display_name: self.display_name,
+            profile_picture: self.profile_picture,
+        }
+    }
+}
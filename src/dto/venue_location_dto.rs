@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::dto::geo_point_dto::GeoPointDTO;
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct VenueLocationDTO {
     pub map_id:Option<String>,
@@ -7,6 +9,8 @@ pub struct VenueLocationDTO {
     pub venue_name:Option<String>,
     pub venue_address:Option<String>,
     pub venue_detail:Option<String>,
-    pub lat:Option<i64>,
-    pub lng:Option<i64>
-}
\ No newline at end of file
+    pub lat:Option<f64>,
+    pub lng:Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub point:Option<GeoPointDTO>
+}
@@ -14,13 +14,27 @@ pub struct Event {
     pub image_id: Option<ObjectId>,
     #[serde(rename = "theme_id")]
     pub theme_id: Option<ObjectId>,
+    #[serde(rename = "series_id", skip_serializing_if = "Option::is_none")]
+    pub series_id: Option<ObjectId>,
     pub invitation_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slug: Option<String>,
+    //kept so shared links still resolve after rename
+    #[serde(default)]
+    pub previous_slugs: Vec<String>,
     pub event_name: String,
     pub event_description: String,
     pub datetime: Option<DateTime>,
+    pub end_datetime: Option<DateTime>,
+    pub timezone: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
     pub venue_location: Option<VenueLocationDTO>,
     pub status:String,
     pub config: EventConfigDTO,
+    //bumped on schedule or venue edits, used as calendar SEQUENCE
+    #[serde(default)]
+    pub revision: i64,
     pub created_at: DateTime,
     pub updated_at: DateTime,
 }
@@ -0,0 +1,40 @@
+use bson::DateTime;
+use bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EventActivity {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    #[serde(rename = "event_id", skip_serializing_if = "Option::is_none")]
+    pub event_id: Option<ObjectId>,
+    #[serde(rename = "user_id", skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<ObjectId>,
+    #[serde(rename = "ref_id", skip_serializing_if = "Option::is_none")]
+    pub ref_id: Option<ObjectId>,
+    pub activity_type: String,
+    pub payload: Option<serde_json::Value>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+impl EventActivity {
+    pub fn create(
+        event_id: Option<ObjectId>,
+        user_id: Option<ObjectId>,
+        ref_id: Option<ObjectId>,
+        activity_type: &str,
+        payload: Option<serde_json::Value>,
+    ) -> Self {
+        EventActivity {
+            id: Some(ObjectId::new()),
+            event_id,
+            user_id,
+            ref_id,
+            activity_type: activity_type.to_string(),
+            payload,
+            created_at: DateTime::now(),
+            updated_at: DateTime::now(),
+        }
+    }
+}
@@ -0,0 +1,20 @@
+use bson::DateTime;
+use bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EventAlbum {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    #[serde(rename = "event_id", skip_serializing_if = "Option::is_none")]
+    pub event_id: Option<ObjectId>,
+    #[serde(rename = "user_id", skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<ObjectId>,
+    pub file_name: String,
+    pub mime_type: String,
+    pub bucket: String,
+    pub path: String,
+    pub is_hidden: bool,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
@@ -0,0 +1,19 @@
+use bson::DateTime;
+use bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+
+//banned user cannot join again through invitation link or event code
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EventBan {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    #[serde(rename = "event_id", skip_serializing_if = "Option::is_none")]
+    pub event_id: Option<ObjectId>,
+    #[serde(rename = "user_id", skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<ObjectId>,
+    #[serde(rename = "banned_by", skip_serializing_if = "Option::is_none")]
+    pub banned_by: Option<ObjectId>,
+    pub reason: Option<String>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
@@ -12,6 +12,7 @@ pub struct EventGuest {
     pub event_id: Option<ObjectId>,
     pub role: String,
     pub rsvp:Option<String>,
+    pub checked_in_at: Option<DateTime>,
     pub created_at: DateTime,
     pub updated_at: DateTime,
 }
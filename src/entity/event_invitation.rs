@@ -12,7 +12,22 @@ pub struct EventInvitation {
     pub event_id: Option<ObjectId>,
     pub invitation_type: String,
     pub invitation_code:String,
+    //normalized 628xx, set for invitation to someone without an account
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phone_number: Option<String>,
     pub expires_at: Option<DateTime>,
+    //public link only, none means unlimited
+    #[serde(default)]
+    pub max_uses: Option<i64>,
+    //role given to whoever join through the link
+    #[serde(default)]
+    pub default_role: Option<String>,
+    #[serde(default)]
+    pub open_count: i64,
+    #[serde(default)]
+    pub redemption_count: i64,
+    #[serde(default)]
+    pub revoked_at: Option<DateTime>,
     pub created_at: DateTime,
     pub updated_at: DateTime,
 }
@@ -0,0 +1,21 @@
+use bson::DateTime;
+use bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+
+//pending promotion to co-host or host, the role only change after the target accept
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EventRoleInvitation {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    #[serde(rename = "event_id", skip_serializing_if = "Option::is_none")]
+    pub event_id: Option<ObjectId>,
+    #[serde(rename = "user_id", skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<ObjectId>,
+    #[serde(rename = "invited_by", skip_serializing_if = "Option::is_none")]
+    pub invited_by: Option<ObjectId>,
+    pub role: String,
+    pub status: String,
+    pub responded_at: Option<DateTime>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
@@ -0,0 +1,87 @@
+use bson::DateTime;
+use bson::oid::ObjectId;
+use chrono::{Days, Months, TimeZone};
+use chrono_tz::Tz;
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+
+use crate::common::constant::{
+    DEFAULT_EVENT_TIMEZONE, SERIES_FREQUENCY_DAILY, SERIES_FREQUENCY_MONTHLY, SERIES_FREQUENCY_WEEKLY,
+    SERIES_MAX_OCCURRENCE,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RecurrenceRule {
+    pub frequency: String,
+    pub interval: i64,
+    pub count: Option<i64>,
+    pub until: Option<DateTime>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EventSeries {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    #[serde(rename = "host_id", skip_serializing_if = "Option::is_none")]
+    pub host_id: Option<ObjectId>,
+    #[serde(rename = "template_event_id", skip_serializing_if = "Option::is_none")]
+    pub template_event_id: Option<ObjectId>,
+    pub rule: RecurrenceRule,
+    pub exceptions: Vec<DateTime>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+impl RecurrenceRule {
+    //the first item is always the start, exceptions are matched by local date and still consume the count.
+    //steps are added on the local wall clock so a weekly 19:00 meetup stays 19:00 across dst changes
+    pub fn occurrences(
+        &self,
+        start: DateTime,
+        exceptions: &Vec<DateTime>,
+        timezone: Option<&String>,
+    ) -> Vec<DateTime> {
+        let tz = timezone
+            .and_then(|timezone| Tz::from_str(timezone).ok())
+            .unwrap_or(Tz::from_str(DEFAULT_EVENT_TIMEZONE).unwrap());
+        let start = start.to_chrono().with_timezone(&tz).naive_local();
+        let max = self
+            .count
+            .unwrap_or(SERIES_MAX_OCCURRENCE)
+            .min(SERIES_MAX_OCCURRENCE);
+        let excluded = exceptions
+            .iter()
+            .map(|date| date.to_chrono().with_timezone(&tz).date_naive())
+            .collect::<Vec<_>>();
+
+        let mut result = Vec::new();
+        for index in 0..max {
+            let step = (index * self.interval.max(1)) as u64;
+            let next = match self.frequency.as_str() {
+                SERIES_FREQUENCY_DAILY => start.checked_add_days(Days::new(step)),
+                SERIES_FREQUENCY_WEEKLY => start.checked_add_days(Days::new(step * 7)),
+                SERIES_FREQUENCY_MONTHLY => start.checked_add_months(Months::new(step as u32)),
+                _ => None,
+            };
+            if let None = next {
+                break;
+            }
+            let next = next.unwrap();
+            let instant = tz.from_local_datetime(&next).earliest();
+            if let None = instant {
+                continue;
+            }
+            let instant = instant.unwrap();
+            if let Some(until) = self.until {
+                if instant > until.to_chrono() {
+                    break;
+                }
+            }
+            if index > 0 && excluded.contains(&next.date()) {
+                continue;
+            }
+            result.push(DateTime::from_chrono(instant));
+        }
+        result
+    }
+}
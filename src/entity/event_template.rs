@@ -0,0 +1,31 @@
+use bson::DateTime;
+use bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+
+use crate::dto::{event_config_dto::EventConfigDTO, venue_location_dto::VenueLocationDTO};
+use crate::entity::event_questionnaire::EventQuestionnaire;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EventTemplate {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    #[serde(rename = "user_id", skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<ObjectId>,
+    pub name: String,
+    #[serde(rename = "image_id")]
+    pub image_id: Option<ObjectId>,
+    #[serde(rename = "theme_id")]
+    pub theme_id: Option<ObjectId>,
+    pub event_name: String,
+    pub event_description: String,
+    pub timezone: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    pub venue_location: Option<VenueLocationDTO>,
+    pub config: EventConfigDTO,
+    //copied to every event created from this template, event_id is empty here
+    #[serde(default)]
+    pub questionnaire: Vec<EventQuestionnaire>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
@@ -11,3 +11,14 @@ pub mod theme;
 pub mod user;
 pub mod user_metadata;
 pub mod event_invitation;
+pub mod event_album;
+pub mod event_activity;
+pub mod event_series;
+pub mod calendar_feed;
+pub mod event_template;
+pub mod event_role_invitation;
+pub mod event_ban;
+pub mod user_contact;
+pub mod user_report;
+pub mod user_status_log;
+pub mod user_audit_log;
@@ -10,6 +10,13 @@ pub struct Mutual {
     pub user_id: Option<ObjectId>,
     #[serde(rename = "mutual_id", skip_serializing_if = "Option::is_none")]
     pub mutual_id: Option<ObjectId>,
+    //display name of the other side, copied so mutual search can use text index
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
     pub created_at: DateTime,
     pub updated_at: DateTime,
 }
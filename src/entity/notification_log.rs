@@ -11,6 +11,9 @@ pub struct NotificationLog {
     pub ref_id: Option<ObjectId>,
     #[serde(rename = "user_id", skip_serializing_if = "Option::is_none")]
     pub user_id: Option<ObjectId>,
+    //user who caused the notification, used to hide it once blocked
+    #[serde(rename = "sender_id", default, skip_serializing_if = "Option::is_none")]
+    pub sender_id: Option<ObjectId>,
     pub is_read: bool,
     pub created_at: DateTime,
     pub updated_at: DateTime,
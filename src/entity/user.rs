@@ -12,12 +12,16 @@ pub struct User {
     pub display_name: String,
     pub email: String,
     pub phone_number: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phone_hash: Option<String>,
     pub password: Option<String>,
     pub app_meta_data: Option<serde_json::Value>,
     pub user_meta_data: Option<UserMetaData>,
     pub profile_picture: Option<ProfilePictureDTO>,
     pub last_logged_in: Option<DateTime>,
     pub status: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status_reason: Option<String>,
     pub created_at: DateTime,
     pub updated_at: DateTime,
     pub confirmation_at: Option<DateTime>,
@@ -0,0 +1,16 @@
+use bson::DateTime;
+use bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+
+//sensitive account change made by the user itself, kept for support and dispute
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UserAuditLog {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    #[serde(rename = "user_id", skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<ObjectId>,
+    pub action: String,
+    pub previous_value: Option<String>,
+    pub new_value: Option<String>,
+    pub created_at: DateTime,
+}
@@ -0,0 +1,18 @@
+use bson::DateTime;
+use bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+
+//one uploaded address book entry, only the hash is kept and it expire after retention period
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UserContact {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    #[serde(rename = "user_id", skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<ObjectId>,
+    pub phone_hash: String,
+    #[serde(rename = "matched_user_id", skip_serializing_if = "Option::is_none")]
+    pub matched_user_id: Option<ObjectId>,
+    pub expires_at: DateTime,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
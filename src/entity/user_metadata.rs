@@ -2,5 +2,11 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct UserMetaData {
-    pub providers: Option<Vec<String>>
+    pub providers: Option<Vec<String>>,
+    #[serde(default)]
+    pub bio: Option<String>,
+    #[serde(default)]
+    pub gender: Option<String>,
+    #[serde(default)]
+    pub date_of_birth: Option<String>,
 }
@@ -0,0 +1,27 @@
+use bson::DateTime;
+use bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+
+//report filed by a user against another user or their content, reviewed by moderator
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UserReport {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    #[serde(rename = "reporter_id", skip_serializing_if = "Option::is_none")]
+    pub reporter_id: Option<ObjectId>,
+    pub target_type: String,
+    #[serde(rename = "target_id", skip_serializing_if = "Option::is_none")]
+    pub target_id: Option<ObjectId>,
+    //owner of the reported content, the one that get suspended
+    #[serde(rename = "reported_user_id", skip_serializing_if = "Option::is_none")]
+    pub reported_user_id: Option<ObjectId>,
+    pub reason: String,
+    pub detail: Option<String>,
+    pub status: String,
+    pub resolution_note: Option<String>,
+    #[serde(rename = "resolved_by", skip_serializing_if = "Option::is_none")]
+    pub resolved_by: Option<ObjectId>,
+    pub resolved_at: Option<DateTime>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
@@ -0,0 +1,18 @@
+use bson::DateTime;
+use bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+
+//history of account status changes made by moderator, latest entry hold the active reason
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UserStatusLog {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    #[serde(rename = "user_id", skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<ObjectId>,
+    pub previous_status: Option<String>,
+    pub status: String,
+    pub reason: Option<String>,
+    #[serde(rename = "changed_by", skip_serializing_if = "Option::is_none")]
+    pub changed_by: Option<ObjectId>,
+    pub created_at: DateTime,
+}
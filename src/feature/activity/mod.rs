@@ -0,0 +1,111 @@
+use crate::common::api_response::{ApiResponse, PaginationRequest, PagingResponse};
+use crate::common::app_state::AppState;
+use crate::common::constant::{
+    COLLECTION_EVENT_ACTIVITY, COLLECTION_EVENT_GUEST, COLLECTION_EVENTS, COLLECTION_USERS,
+};
+use crate::common::jwt::AuthContext;
+use crate::common::lang::Lang;
+use crate::common::mongo::DB;
+use crate::common::mongo::filter::is;
+use crate::common::mongo::lookup::one;
+use crate::common::utils::create_object_id_option;
+use crate::dto::event_activity_dto::EventActivityDTO;
+use crate::dto::event_dto::EventDTO;
+use crate::dto::event_guest_dto::EventGuestDTO;
+use crate::feature::event::event_policy::is_event_host;
+use crate::i18n;
+use axum::extract::{Path, Query, State};
+use log::info;
+
+pub async fn get_event_timeline(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Path(event_id): Path<String>,
+    Query(query): Query<PaginationRequest>,
+) -> ApiResponse<PagingResponse<EventActivityDTO>> {
+    info!(target:"get_event_timeline","Starting...");
+    let i18n = i18n!("activity", lang);
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"get_event_timeline","user id not found");
+        return ApiResponse::failed(&i18n.translate("get_event_timeline.user_id_not_found"));
+    }
+    let current_user_id = auth_context.get_user_id().unwrap();
+
+    let create_event_id = create_object_id_option(&event_id);
+    if let None = create_event_id {
+        info!(target:"get_event_timeline","event id invalid");
+        return ApiResponse::failed(&i18n.translate("get_event_timeline.event_id_invalid"));
+    }
+    let create_event_id = create_event_id.unwrap();
+
+    let find_event = DB::get(COLLECTION_EVENTS)
+        .filter(vec![is("_id", create_event_id)])
+        .get_one::<EventDTO>(&state.db)
+        .await;
+    if let Err(why) = find_event {
+        info!(target:"get_event_timeline","cannot find events {}",why);
+        return ApiResponse::not_found(&i18n.translate("get_event_timeline.event_not_found"));
+    }
+    let event = find_event.unwrap();
+
+    let is_host = is_event_host(&state.db, &event, current_user_id).await;
+    if !is_host {
+        let find_guest = DB::get(COLLECTION_EVENT_GUEST)
+            .filter(vec![
+                is("event_id", create_event_id),
+                is("user_id", current_user_id),
+            ])
+            .get_one::<EventGuestDTO>(&state.db)
+            .await;
+        if let Err(why) = find_guest {
+            info!(target:"get_event_timeline","user is not a participant {}",why);
+            return ApiResponse::access_denied(
+                &i18n.translate("get_event_timeline.not_participant"),
+            );
+        }
+
+        if !event.config.show_timestamp_activity {
+            info!(target:"get_event_timeline","activity timeline hidden by host");
+            return ApiResponse::access_denied(
+                &i18n.translate("get_event_timeline.activity_hidden"),
+            );
+        }
+    }
+
+    let mut data = DB::get(COLLECTION_EVENT_ACTIVITY).filter(vec![is("event_id", create_event_id)]);
+
+    if let Some(activity_type) = query.filter.clone() {
+        data = data.filter(vec![is("activity_type", activity_type)]);
+    }
+
+    let find_activity = data
+        .lookup(&[one(COLLECTION_USERS, "user_id", "_id", "user")])
+        .sort(vec![("created_at", -1)])
+        .get_per_page::<EventActivityDTO>(
+            query.page.unwrap_or(0),
+            query.size.unwrap_or(50),
+            &state.db,
+        )
+        .await;
+
+    if let Err(why) = find_activity {
+        info!(target:"get_event_timeline","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("get_event_timeline.failed"));
+    }
+    let mut find_activity = find_activity.unwrap();
+
+    //guest identity only visible to host when show_guest_name is off
+    if !is_host && !event.config.show_guest_name {
+        for activity in find_activity.items.iter_mut() {
+            if activity.user_id != event.host_id {
+                activity.user_id = None;
+                activity.user = None;
+            }
+        }
+    }
+
+    info!(target:"get_event_timeline","finish");
+    ApiResponse::ok(find_activity, &i18n.translate("get_event_timeline.ok"))
+}
@@ -0,0 +1,7 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Validate)]
+pub struct UpdateAlbumVisibilityRequest {
+    pub is_hidden: bool,
+}
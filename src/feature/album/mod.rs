@@ -0,0 +1,438 @@
+use crate::common::api_response::{ApiResponse, PaginationRequest, PagingResponse};
+use crate::common::app_state::AppState;
+use crate::common::constant::{
+    BUCKET_EVENT, COLLECTION_EVENT_ALBUM, COLLECTION_EVENT_GUEST, COLLECTION_EVENT_IMAGES,
+    COLLECTION_EVENT_THEMES, COLLECTION_EVENTS, COLLECTION_USERS, PATH_EVENT_ALBUM,
+    SSE_EVENT_UPDATE_EVENT_ALBUM,
+};
+use crate::common::jwt::AuthContext;
+use crate::common::lang::Lang;
+use crate::common::middleware::Json;
+use crate::common::minio::MinIO;
+use crate::common::mongo::DB;
+use crate::common::mongo::filter::is;
+use crate::common::mongo::lookup::one;
+use crate::common::multipart_file::MultiFileExtractor;
+use crate::common::sse::sse_builder::{SseBuilder, SseTarget};
+use crate::common::utils::create_object_id_option;
+use crate::dto::event_album_dto::EventAlbumDTO;
+use crate::dto::event_dto::EventDTO;
+use crate::dto::event_guest_dto::EventGuestDTO;
+use crate::entity::event_album::EventAlbum;
+use crate::feature::album::album_model::UpdateAlbumVisibilityRequest;
+use crate::feature::event::event_model::EventAccessRequest;
+use crate::feature::event::event_policy::{EventViewerRole, check_event_access, is_event_host};
+use crate::feature::mutual::get_blocked_user_ids;
+use crate::i18n;
+use axum::extract::{Path, Query, State};
+use bson::oid::ObjectId;
+use bson::{DateTime, doc};
+use log::info;
+use validator::Validate;
+
+pub mod album_model;
+
+pub async fn upload_album_photo(
+    mut state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    body: MultiFileExtractor,
+) -> ApiResponse<Vec<EventAlbumDTO>> {
+    info!(target:"upload_album_photo","Starting...");
+    let i18n = i18n!("album", lang);
+    if let Err(err) = body.validate_body() {
+        info!(target:"upload_album_photo","{:?}", err);
+        let _remove = body.remove_file();
+        return ApiResponse::error_validation(
+            err,
+            &i18n.translate("upload_album_photo.validation_error"),
+        );
+    }
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"upload_album_photo","user id not found");
+        let _remove = body.remove_file();
+        return ApiResponse::failed(&i18n.translate("upload_album_photo.user_id_not_found"));
+    }
+    let current_user_id = auth_context.get_user_id().unwrap();
+
+    let event_id = body.ref_id_to_object();
+    if let None = event_id {
+        info!(target:"upload_album_photo","event id invalid");
+        let _remove = body.remove_file();
+        return ApiResponse::failed(&i18n.translate("upload_album_photo.event_id_invalid"));
+    }
+    let event_id = event_id.unwrap();
+
+    let find_event = DB::get(COLLECTION_EVENTS)
+        .filter(vec![is("_id", event_id)])
+        .get_one::<EventDTO>(&state.db)
+        .await;
+    if let Err(why) = find_event {
+        info!(target:"upload_album_photo","cannot find events {}",why);
+        let _remove = body.remove_file();
+        return ApiResponse::not_found(&i18n.translate("upload_album_photo.event_not_found"));
+    }
+    let event = find_event.unwrap();
+
+    if !event.config.allow_participant_album {
+        info!(target:"upload_album_photo","participant album disabled for this event");
+        let _remove = body.remove_file();
+        return ApiResponse::access_denied(&i18n.translate("upload_album_photo.album_disabled"));
+    }
+
+    let is_participant = event.host_id == Some(current_user_id)
+        || DB::get(COLLECTION_EVENT_GUEST)
+            .filter(vec![is("event_id", event_id), is("user_id", current_user_id)])
+            .get_one::<EventGuestDTO>(&state.db)
+            .await
+            .is_ok();
+    if !is_participant {
+        info!(target:"upload_album_photo","user is not a participant of the event");
+        let _remove = body.remove_file();
+        return ApiResponse::access_denied(&i18n.translate("upload_album_photo.not_participant"));
+    }
+
+    let storage = MinIO::new();
+    let path = format!("{}/{}", event_id, PATH_EVENT_ALBUM);
+    let mut photos: Vec<EventAlbum> = Vec::new();
+    for file in body.files() {
+        let upload = storage
+            .upload_file(
+                file.temp_path.clone(),
+                BUCKET_EVENT.to_string(),
+                format!("{}/{}", path, file.filename),
+            )
+            .await;
+        if let Err(why) = upload {
+            info!(target:"upload_album_photo","{:?}", why);
+            let _remove = body.remove_file();
+            for photo in photos {
+                let _delete = storage
+                    .delete_file(
+                        format!("{}/{}", photo.path, photo.file_name),
+                        photo.bucket,
+                    )
+                    .await;
+            }
+            return ApiResponse::failed(&i18n.translate("upload_album_photo.failed_upload"));
+        }
+
+        photos.push(EventAlbum {
+            id: Some(ObjectId::new()),
+            event_id: Some(event_id),
+            user_id: Some(current_user_id),
+            file_name: file.filename.clone(),
+            mime_type: file.mime_type.clone(),
+            bucket: BUCKET_EVENT.to_string(),
+            path: path.clone(),
+            is_hidden: false,
+            created_at: DateTime::now(),
+            updated_at: DateTime::now(),
+        });
+    }
+    let _remove = body.remove_file();
+
+    let save_photos = DB::insert(COLLECTION_EVENT_ALBUM)
+        .many(photos.clone(), &state.db)
+        .await;
+    if let Err(why) = save_photos {
+        info!(target:"upload_album_photo","{:?}", why);
+        for photo in photos {
+            let _delete = storage
+                .delete_file(
+                    format!("{}/{}", photo.path, photo.file_name),
+                    photo.bucket,
+                )
+                .await;
+        }
+        return ApiResponse::failed(&i18n.translate("upload_album_photo.failed_save"));
+    }
+
+    let photos = photos
+        .into_iter()
+        .map(|photo| photo.into())
+        .collect::<Vec<EventAlbumDTO>>();
+
+    info!(target:"upload_album_photo","sending sse");
+    let find_subscriber = state
+        .redis
+        .get_list_subscriber(event_id.to_string())
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect::<Vec<String>>();
+
+    let blocked_ids = get_blocked_user_ids(&state.db, current_user_id)
+        .await
+        .into_iter()
+        .map(|id| id.to_string())
+        .collect::<Vec<String>>();
+
+    let sse = SseBuilder::new(
+        SseTarget::create()
+            .set_event_name(SSE_EVENT_UPDATE_EVENT_ALBUM.to_string())
+            .set_user_ids(find_subscriber)
+            .set_exclude_user_ids(blocked_ids),
+        photos.clone(),
+    );
+    state.sse.send(sse).await;
+    info!(target:"upload_album_photo","finish");
+    ApiResponse::ok(photos, &i18n.translate("upload_album_photo.ok"))
+}
+
+pub async fn get_event_album(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Path(event_id): Path<String>,
+    Query(query): Query<PaginationRequest>,
+    Query(access): Query<EventAccessRequest>,
+) -> ApiResponse<PagingResponse<EventAlbumDTO>> {
+    info!(target:"get_event_album","Starting...");
+    let i18n = i18n!("album", lang);
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"get_event_album","user id not found");
+        return ApiResponse::failed(&i18n.translate("get_event_album.user_id_not_found"));
+    }
+    let current_user_id = auth_context.get_user_id().unwrap();
+
+    let create_event_id = create_object_id_option(&event_id);
+    if let None = create_event_id {
+        info!(target:"get_event_album","event id invalid");
+        return ApiResponse::failed(&i18n.translate("get_event_album.event_id_invalid"));
+    }
+    let create_event_id = create_event_id.unwrap();
+
+    let find_event = DB::get(COLLECTION_EVENTS)
+        .filter(vec![is("_id", create_event_id)])
+        .get_one::<EventDTO>(&state.db)
+        .await;
+    if let Err(why) = find_event {
+        info!(target:"get_event_album","cannot find events {}",why);
+        return ApiResponse::not_found(&i18n.translate("get_event_album.event_not_found"));
+    }
+    let event = find_event.unwrap();
+
+    let viewer_role = check_event_access(&state.db, &event, Some(current_user_id), &access).await;
+    if let Err(why) = viewer_role {
+        info!(target:"get_event_album","access denied {}",why);
+        return ApiResponse::access_denied(&i18n.translate(&format!("get_event_album.{}", why)));
+    }
+
+    let mut data = DB::get(COLLECTION_EVENT_ALBUM).filter(vec![is("event_id", create_event_id)]);
+
+    //hidden photo only visible to host
+    if viewer_role.unwrap() != EventViewerRole::Host {
+        data = data.filter(vec![is("is_hidden", false)]);
+    }
+
+    let find_album = data
+        .lookup(&[one(COLLECTION_USERS, "user_id", "_id", "user")])
+        .sort(vec![("created_at", -1)])
+        .get_per_page::<EventAlbumDTO>(
+            query.page.unwrap_or(0),
+            query.size.unwrap_or(50),
+            &state.db,
+        )
+        .await;
+
+    if let Err(why) = find_album {
+        info!(target:"get_event_album","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("get_event_album.failed"));
+    }
+
+    ApiResponse::ok(find_album.unwrap(), &i18n.translate("get_event_album.ok"))
+}
+
+pub async fn update_album_photo_visibility(
+    mut state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Path(album_id): Path<String>,
+    Json(body): Json<UpdateAlbumVisibilityRequest>,
+) -> ApiResponse<EventAlbumDTO> {
+    info!(target:"update_album_photo_visibility","Starting...");
+    let i18n = i18n!("album", lang);
+    if let Err(err) = body.validate() {
+        info!(target:"update_album_photo_visibility","{:?}", err);
+        return ApiResponse::error_validation(
+            err,
+            &i18n.translate("update_album_photo_visibility.validation_error"),
+        );
+    }
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"update_album_photo_visibility","user id not found");
+        return ApiResponse::failed(
+            &i18n.translate("update_album_photo_visibility.user_id_not_found"),
+        );
+    }
+    let current_user_id = auth_context.get_user_id().unwrap();
+
+    let create_album_id = create_object_id_option(&album_id);
+    if let None = create_album_id {
+        info!(target:"update_album_photo_visibility","album id invalid");
+        return ApiResponse::failed(
+            &i18n.translate("update_album_photo_visibility.album_id_invalid"),
+        );
+    }
+    let create_album_id = create_album_id.unwrap();
+
+    let find_photo = DB::get(COLLECTION_EVENT_ALBUM)
+        .filter(vec![is("_id", create_album_id)])
+        .lookup(&[one(COLLECTION_USERS, "user_id", "_id", "user")])
+        .get_one::<EventAlbumDTO>(&state.db)
+        .await;
+    if let Err(why) = find_photo {
+        info!(target:"update_album_photo_visibility","{:?}",why);
+        return ApiResponse::not_found(
+            &i18n.translate("update_album_photo_visibility.photo_not_found"),
+        );
+    }
+    let mut photo = find_photo.unwrap();
+
+    let find_event = DB::get(COLLECTION_EVENTS)
+        .filter(vec![is("_id", photo.event_id)])
+        .lookup(&[
+            one(COLLECTION_USERS, "host_id", "_id", "host"),
+            one(COLLECTION_EVENT_IMAGES, "image_id", "_id", "image"),
+            one(COLLECTION_EVENT_THEMES, "theme_id", "_id", "theme"),
+        ])
+        .get_one::<EventDTO>(&state.db)
+        .await;
+    if let Err(why) = find_event {
+        info!(target:"update_album_photo_visibility","cannot find events {}",why);
+        return ApiResponse::not_found(
+            &i18n.translate("update_album_photo_visibility.event_not_found"),
+        );
+    }
+    let event = find_event.unwrap();
+
+    if !is_event_host(&state.db, &event, current_user_id).await {
+        info!(target:"update_album_photo_visibility","only host can moderate album");
+        return ApiResponse::access_denied(
+            &i18n.translate("update_album_photo_visibility.not_host"),
+        );
+    }
+
+    let update_photo = DB::update(COLLECTION_EVENT_ALBUM)
+        .filter(vec![is("_id", create_album_id)])
+        .set(doc! {
+            "is_hidden":body.is_hidden,
+            "updated_at":DateTime::now()
+        })
+        .execute(&state.db)
+        .await;
+    if let Err(why) = update_photo {
+        info!(target:"update_album_photo_visibility","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("update_album_photo_visibility.failed"));
+    }
+    photo.is_hidden = body.is_hidden;
+
+    info!(target:"update_album_photo_visibility","sending sse");
+    let find_subscriber = state
+        .redis
+        .get_list_subscriber(event.id.unwrap().to_string())
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect::<Vec<String>>();
+
+    let sse = SseBuilder::new(
+        SseTarget::create()
+            .set_event_name(SSE_EVENT_UPDATE_EVENT_ALBUM.to_string())
+            .set_user_ids(find_subscriber),
+        vec![photo.clone()],
+    );
+    state.sse.send(sse).await;
+    info!(target:"update_album_photo_visibility","finish");
+    ApiResponse::ok(photo, &i18n.translate("update_album_photo_visibility.ok"))
+}
+
+pub async fn delete_album_photo(
+    mut state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Path(album_id): Path<String>,
+) -> ApiResponse<EventAlbumDTO> {
+    info!(target:"delete_album_photo","Starting...");
+    let i18n = i18n!("album", lang);
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"delete_album_photo","user id not found");
+        return ApiResponse::failed(&i18n.translate("delete_album_photo.user_id_not_found"));
+    }
+    let current_user_id = auth_context.get_user_id().unwrap();
+
+    let create_album_id = create_object_id_option(&album_id);
+    if let None = create_album_id {
+        info!(target:"delete_album_photo","album id invalid");
+        return ApiResponse::failed(&i18n.translate("delete_album_photo.album_id_invalid"));
+    }
+    let create_album_id = create_album_id.unwrap();
+
+    let find_photo = DB::get(COLLECTION_EVENT_ALBUM)
+        .filter(vec![is("_id", create_album_id)])
+        .lookup(&[one(COLLECTION_USERS, "user_id", "_id", "user")])
+        .get_one::<EventAlbumDTO>(&state.db)
+        .await;
+    if let Err(why) = find_photo {
+        info!(target:"delete_album_photo","{:?}",why);
+        return ApiResponse::not_found(&i18n.translate("delete_album_photo.photo_not_found"));
+    }
+    let photo = find_photo.unwrap();
+
+    let find_event = DB::get(COLLECTION_EVENTS)
+        .filter(vec![is("_id", photo.event_id)])
+        .get_one::<EventDTO>(&state.db)
+        .await;
+    if let Err(why) = find_event {
+        info!(target:"delete_album_photo","cannot find events {}",why);
+        return ApiResponse::not_found(&i18n.translate("delete_album_photo.event_not_found"));
+    }
+    let event = find_event.unwrap();
+
+    //uploader can remove their own photo, host can remove any photo
+    let is_uploader = photo.user_id == Some(current_user_id);
+    if !is_uploader && !is_event_host(&state.db, &event, current_user_id).await {
+        info!(target:"delete_album_photo","user is not allowed to delete this photo");
+        return ApiResponse::access_denied(&i18n.translate("delete_album_photo.not_allowed"));
+    }
+
+    let delete_photo = DB::delete(COLLECTION_EVENT_ALBUM)
+        .filter(vec![is("_id", create_album_id)])
+        .one(&state.db)
+        .await;
+    if let Err(why) = delete_photo {
+        info!(target:"delete_album_photo","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("delete_album_photo.failed"));
+    }
+
+    let delete_file = MinIO::new()
+        .delete_file(
+            format!("{}/{}", photo.path, photo.file_name),
+            photo.bucket.clone(),
+        )
+        .await;
+    if let Err(why) = delete_file {
+        info!(target:"delete_album_photo","{:?}",why);
+    }
+
+    info!(target:"delete_album_photo","sending sse");
+    let find_subscriber = state
+        .redis
+        .get_list_subscriber(event.id.unwrap().to_string())
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect::<Vec<String>>();
+
+    let sse = SseBuilder::new(
+        SseTarget::create()
+            .set_event_name(SSE_EVENT_UPDATE_EVENT_ALBUM.to_string())
+            .set_user_ids(find_subscriber),
+        vec![photo.clone()],
+    );
+    state.sse.send(sse).await;
+    info!(target:"delete_album_photo","finish");
+    ApiResponse::ok(photo, &i18n.translate("delete_album_photo.ok"))
+}
@@ -15,6 +15,21 @@ pub struct VerifyOTPRequest {
     pub otp: String,
 }
 
+#[derive(Serialize, Deserialize, Validate, Debug, Clone)]
+pub struct ChangePhoneRequest {
+    #[validate(length(min = 4))]
+    pub phone_number: String,
+}
+
+//otp sent to the current number prove the account owner, the other prove the new number
+#[derive(Serialize, Deserialize, Validate, Debug, Clone)]
+pub struct VerifyChangePhoneRequest {
+    #[validate(length(min = 4))]
+    pub current_otp: String,
+    #[validate(length(min = 4))]
+    pub new_otp: String,
+}
+
 #[derive(Serialize, Deserialize, Validate, Debug, Clone)]
 pub struct SignInEmailRequest {
     #[validate(email)]
@@ -5,27 +5,36 @@ use crate::{
         api_response::ApiResponse,
         app_state::AppState,
         constant::{
-            COLLECTION_USERS, PROVIDER_OTP, REDIS_KEY_OTP, REDIS_KEY_OTP_AT, REDIS_KEY_OTP_ATTEMPT,
-            REDIS_KEY_OTP_PHONE_NUMBER, REDIS_KEY_OTP_TYPE, REDIS_KEY_USER_ID,
+            CHANGE_PHONE_RATE_LIMIT, CHANGE_PHONE_RATE_WINDOW_SECONDS, COLLECTION_EVENT_INVITATION, COLLECTION_USER_AUDIT_LOG, COLLECTION_USERS, DEFAULT_ID_NON_MEMBER, PROVIDER_OTP, REDIS_KEY_OTP, REDIS_KEY_OTP_AT, REDIS_KEY_OTP_ATTEMPT,
+            REDIS_KEY_OTP_NEW_PHONE, REDIS_KEY_OTP_PHONE_NUMBER, REDIS_KEY_OTP_TYPE, REDIS_KEY_USER_DISPLAY_NAME, REDIS_KEY_USER_EMAIL, REDIS_KEY_USER_ID,
             REDIS_KEY_USER_PHONE_NUMBER, REDIS_KEY_USER_TOKEN, REDIS_SESSION_OTP_SIGN_IN,
             REDIS_SESSION_OTP_SIGN_UP, REDIS_SESSION_SIGN_IN, USER_STATUS_ACTIVE,
-            USER_STATUS_WAITING_ACTIVATION,
+            USER_AUDIT_PHONE_CHANGED, USER_STATUS_WAITING_ACTIVATION,
         },
         i18n,
-        jwt::{JwtClaims, JwtUtil},
+        jwt::{AuthContext, JwtClaims, JwtUtil},
         lang::Lang,
         middleware::Json,
-        mongo::{DB, filter::is},
-        utils::{generate_otp, get_i64_with_default, get_naive_date_time, get_string_with_default},
+        mongo::{
+            DB,
+            filter::{is, is_in, not_equal},
+        },
+        utils::{
+            create_object_id_option, generate_otp, get_i64_with_default, get_naive_date_time, get_string_with_default,
+            hash_phone_number, normalize_phone_number, phone_number_variants,
+        },
         wa,
     },
     dto::user_dto::UserDTO,
-    entity::{user::User, user_metadata::UserMetaData},
+    entity::{user::User, user_audit_log::UserAuditLog, user_metadata::UserMetaData},
     i18n,
 };
-use auth_model::{AuthOTPRequest, AuthOTPResponse, VerifyOTPRequest, VerifyOTPResponse};
+use auth_model::{
+    AuthOTPRequest, AuthOTPResponse, ChangePhoneRequest, VerifyChangePhoneRequest,
+    VerifyOTPRequest, VerifyOTPResponse,
+};
 use axum::extract::State;
-use bson::{DateTime, doc, oid::ObjectId};
+use bson::{Bson, DateTime, doc, oid::ObjectId};
 use chrono::Local;
 use log::info;
 use validator::Validate;
@@ -45,21 +54,13 @@ pub async fn auth_otp(
         return ApiResponse::error_validation(err, &i18n.translate("auth_otp.validation_error"));
     }
 
-    if body.phone_number.starts_with("08") {
-        body.phone_number = body.phone_number.replace("08", "628");
-    }
-    if body.phone_number.contains("-") {
-        body.phone_number = body.phone_number.replace("-", "");
-    }
-    if body.phone_number.contains("+") {
-        body.phone_number = body.phone_number.replace("+", "");
-    }
-    if body.phone_number.contains(" ") {
-        body.phone_number = body.phone_number.replace(" ", "");
-    }
+    body.phone_number = normalize_phone_number(&body.phone_number);
 
     let find_duplicate_phone_number = DB::get(COLLECTION_USERS)
-        .filter(vec![is("phone_number", body.phone_number.clone())])
+        .filter(vec![is_in(
+            "phone_number",
+            phone_number_variants(&body.phone_number),
+        )])
         .get_one::<UserDTO>(&state.db)
         .await;
 
@@ -83,14 +84,19 @@ pub async fn sign_up_otp(
         display_name: String::new(),
         email: String::new(),
         phone_number: body.phone_number.clone(),
+        phone_hash: Some(hash_phone_number(&body.phone_number)),
         password: None,
         app_meta_data: None,
         user_meta_data: Some(UserMetaData {
             providers: Some(vec![PROVIDER_OTP.to_string()]),
+            bio: None,
+            gender: None,
+            date_of_birth: None,
         }),
         profile_picture: None,
         last_logged_in: None,
         status: Some(USER_STATUS_WAITING_ACTIVATION.to_string()),
+        status_reason: None,
         created_at: DateTime::now(),
         updated_at: DateTime::now(),
         confirmation_at: None,
@@ -114,6 +120,24 @@ pub async fn sign_up_otp(
         let _ = session.abort_transaction().await;
         return ApiResponse::un_authorized(&i18n.translate("sign_up_otp.failed_create_user"));
     }
+
+    //invitation sent to this number before the account existed
+    let link_invitation = DB::update(COLLECTION_EVENT_INVITATION)
+        .filter(vec![
+            is("phone_number", body.phone_number.clone()),
+            is("user_id", create_object_id_option(DEFAULT_ID_NON_MEMBER)),
+        ])
+        .set(doc! {
+            "user_id":create_new_user_id,
+            "updated_at":DateTime::now()
+        })
+        .execute_many_with_session(&state.db, &mut session)
+        .await;
+    if let Err(why) = link_invitation {
+        info!(target:"sign_up_otp","{:?}",why);
+        let _ = session.abort_transaction().await;
+        return ApiResponse::un_authorized(&i18n.translate("sign_up_otp.failed_create_user"));
+    }
     let session_id = ObjectId::new().to_string();
 
     let create_session_token =
@@ -195,7 +219,8 @@ pub async fn sign_in_otp(
 
     let save_user = DB::update(COLLECTION_USERS)
         .set(doc! {
-            "last_logged_id":DateTime::now()
+            "last_logged_id":DateTime::now(),
+            "phone_hash":hash_phone_number(&body.phone_number)
         })
         .filter(vec![is("_id", user.id)])
         .execute_with_session(&state.db, &mut session)
@@ -302,6 +327,21 @@ pub async fn verify_otp(
     }
     let user = find_user.unwrap();
 
+    //the account can be suspended between requesting and verifying the otp
+    let allowed = [USER_STATUS_ACTIVE, USER_STATUS_WAITING_ACTIVATION];
+    let is_allowed = allowed.into_iter().any(|value| {
+        value.to_string()
+            == user
+                .status
+                .clone()
+                .unwrap_or(USER_STATUS_WAITING_ACTIVATION.to_string())
+    });
+    if !is_allowed {
+        info!(target:"verify_otp","user status not allowed {:?}",user.status);
+        let _remove_session = state.redis.delete_session_otp(&claims.sub);
+        return ApiResponse::un_authorized(&i18n.translate("verify_otp.status_not_allowed"));
+    }
+
     if session_attempt >= 3 {
         info!(target:"verify_otp","max attempt reached attempt is: {}",session_attempt);
         return ApiResponse::un_authorized(&i18n.translate("verify_otp.max_attempt"));
@@ -349,8 +389,19 @@ pub async fn verify_otp(
     let mut session = session.unwrap();
     let _ = session.start_transaction().await;
 
+    //status is checked again on write, a suspension can land after the read above
     let update_user = DB::update(COLLECTION_USERS)
-        .filter(vec![is("_id", session_user_object_id)])
+        .filter(vec![
+            is("_id", session_user_object_id),
+            is_in(
+                "status",
+                vec![
+                    Bson::from(USER_STATUS_ACTIVE),
+                    Bson::from(USER_STATUS_WAITING_ACTIVATION),
+                    Bson::Null,
+                ],
+            ),
+        ])
         .set(doc! {
             "last_logged_in":DateTime::now(),
             "status":USER_STATUS_ACTIVE
@@ -363,6 +414,12 @@ pub async fn verify_otp(
         let _ = session.abort_transaction().await;
         return ApiResponse::un_authorized(&i18n.translate("verify_otp.user_not_updated"));
     }
+    if update_user.unwrap() == 0 {
+        info!(target:"verify_otp","user status changed before sign in");
+        let _ = session.abort_transaction().await;
+        let _remove_session = state.redis.delete_session_otp(&claims.sub);
+        return ApiResponse::un_authorized(&i18n.translate("verify_otp.status_not_allowed"));
+    }
 
     let _remove_session = state.redis.delete_session_otp(&claims.sub);
 
@@ -378,6 +435,8 @@ pub async fn verify_otp(
             (REDIS_KEY_USER_ID, user.id.clone().unwrap().to_string()),
             (REDIS_KEY_USER_TOKEN, create_token.clone().unwrap()),
             (REDIS_KEY_USER_PHONE_NUMBER, user.phone_number.clone()),
+            (REDIS_KEY_USER_DISPLAY_NAME, user.display_name.clone()),
+            (REDIS_KEY_USER_EMAIL, user.email.clone()),
         ],
     );
 
@@ -386,6 +445,9 @@ pub async fn verify_otp(
         let _ = session.abort_transaction().await;
         return ApiResponse::un_authorized(&i18n.translate("verify_otp.user_not_updated"));
     }
+    let _save_user_session = state
+        .redis
+        .add_session_user(&user.id.unwrap().to_string(), &create_session_sign_in);
     let _ = session.commit_transaction().await;
     ApiResponse::ok(
         VerifyOTPResponse {
@@ -493,3 +555,297 @@ pub async fn resend_otp(
     info!(target:"resend_otp","success");
     ApiResponse::ok("OK".to_string(), &i18n.translate("resend_otp.success"))
 }
+
+pub async fn request_change_phone(
+    mut state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Json(mut body): Json<ChangePhoneRequest>,
+) -> ApiResponse<String> {
+    info!(target:"request_change_phone","starting...");
+    let i18n = i18n!("auth", lang);
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"request_change_phone","session not found");
+        return ApiResponse::access_denied(&i18n.translate("request_change_phone.user_id_not_found"));
+    }
+    let current_user_id = auth_context.get_user_id().unwrap();
+
+    let validate = body.validate();
+    if let Err(err) = validate {
+        info!(target:"request_change_phone","{:?}",err);
+        return ApiResponse::error_validation(
+            err,
+            &i18n.translate("request_change_phone.validation_error"),
+        );
+    }
+    body.phone_number = normalize_phone_number(&body.phone_number);
+
+    //every request send two otp, keep it from being used to spam both numbers
+    if !state.redis.hit_rate_limit(
+        "change_phone",
+        &current_user_id.to_string(),
+        CHANGE_PHONE_RATE_LIMIT,
+        CHANGE_PHONE_RATE_WINDOW_SECONDS,
+    ) {
+        info!(target:"request_change_phone","rate limit reached");
+        return ApiResponse::failed(&i18n.translate("request_change_phone.rate_limited"));
+    }
+
+    let find_user = DB::get(COLLECTION_USERS)
+        .filter(vec![is("_id", current_user_id)])
+        .get_one::<UserDTO>(&state.db)
+        .await;
+    if let Err(why) = find_user {
+        info!(target:"request_change_phone","user not exist {:?}",why);
+        return ApiResponse::not_found(&i18n.translate("request_change_phone.user_not_exist"));
+    }
+    let user = find_user.unwrap();
+    if phone_number_variants(&body.phone_number).contains(&user.phone_number) {
+        info!(target:"request_change_phone","same phone number");
+        return ApiResponse::failed(&i18n.translate("request_change_phone.same_phone_number"));
+    }
+
+    let find_duplicate_phone_number = DB::get(COLLECTION_USERS)
+        .filter(vec![is_in(
+            "phone_number",
+            phone_number_variants(&body.phone_number),
+        )])
+        .count(&state.db)
+        .await
+        .unwrap_or(0);
+    if find_duplicate_phone_number > 0 {
+        info!(target:"request_change_phone","phone number already used");
+        return ApiResponse::failed(&i18n.translate("request_change_phone.phone_number_in_use"));
+    }
+
+    let create_current_otp = generate_otp();
+    let create_new_otp = generate_otp();
+    let created_at = chrono::Local::now().naive_local().and_utc().timestamp();
+
+    let send_current_otp =
+        wa::send_otp(user.phone_number.clone(), create_current_otp.clone()).await;
+    if let Err(why) = send_current_otp {
+        info!(target:"request_change_phone","failed to send otp to current number {:?}",why);
+        return ApiResponse::failed(&i18n.translate("request_change_phone.otp_not_sent"));
+    }
+    let send_new_otp = wa::send_otp(body.phone_number.clone(), create_new_otp.clone()).await;
+    if let Err(why) = send_new_otp {
+        info!(target:"request_change_phone","failed to send otp to new number {:?}",why);
+        return ApiResponse::failed(&i18n.translate("request_change_phone.otp_not_sent"));
+    }
+
+    //requesting again replace the pending change, failed attempt carry over
+    //so a new otp does not reset the attempt limit
+    let session_attempt = state
+        .redis
+        .get_session_change_phone(&current_user_id.to_string())
+        .map_or_else(
+            |_| 0,
+            |session| get_i64_with_default(session.get(REDIS_KEY_OTP_ATTEMPT)),
+        );
+    let _remove_session = state
+        .redis
+        .delete_session_change_phone(&current_user_id.to_string());
+    let create_otp_session = state.redis.set_session_change_phone(
+        &current_user_id.to_string(),
+        &[
+            (REDIS_KEY_OTP, create_current_otp),
+            (REDIS_KEY_OTP_NEW_PHONE, create_new_otp),
+            (REDIS_KEY_OTP_PHONE_NUMBER, body.phone_number.clone()),
+            (REDIS_KEY_OTP_AT, created_at.to_string()),
+            (REDIS_KEY_OTP_ATTEMPT, session_attempt.to_string()),
+        ],
+    );
+    if let Err(why) = create_otp_session {
+        info!(target:"request_change_phone","failed to save session {:?}",why);
+        return ApiResponse::failed(&i18n.translate("request_change_phone.session_not_saved"));
+    }
+
+    info!(target:"request_change_phone","finish");
+    ApiResponse::ok(
+        "".to_string(),
+        &i18n.translate("request_change_phone.ok"),
+    )
+}
+
+pub async fn verify_change_phone(
+    mut state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Json(body): Json<VerifyChangePhoneRequest>,
+) -> ApiResponse<UserDTO> {
+    info!(target:"verify_change_phone","starting...");
+    let i18n = i18n!("auth", lang);
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"verify_change_phone","session not found");
+        return ApiResponse::access_denied(&i18n.translate("verify_change_phone.user_id_not_found"));
+    }
+    let current_user_id = auth_context.get_user_id().unwrap();
+
+    let validate = body.validate();
+    if let Err(err) = validate {
+        info!(target:"verify_change_phone","{:?}",err);
+        return ApiResponse::error_validation(
+            err,
+            &i18n.translate("verify_change_phone.validation_error"),
+        );
+    }
+
+    let get_session = state
+        .redis
+        .get_session_change_phone(&current_user_id.to_string());
+    if let Err(why) = get_session {
+        info!(target:"verify_change_phone","session invalid {:?}",why);
+        return ApiResponse::un_authorized(&i18n.translate("verify_change_phone.failed"));
+    }
+    let session = get_session.unwrap();
+    if session.is_empty() {
+        info!(target:"verify_change_phone","no pending change");
+        return ApiResponse::un_authorized(&i18n.translate("verify_change_phone.expired"));
+    }
+    let session_current_otp = get_string_with_default(session.get(REDIS_KEY_OTP));
+    let session_new_otp = get_string_with_default(session.get(REDIS_KEY_OTP_NEW_PHONE));
+    let session_phone_number = get_string_with_default(session.get(REDIS_KEY_OTP_PHONE_NUMBER));
+    let session_otp_at = get_naive_date_time(session.get(REDIS_KEY_OTP_AT));
+    let mut session_attempt = get_i64_with_default(session.get(REDIS_KEY_OTP_ATTEMPT));
+
+    if session_attempt >= 3 {
+        info!(target:"verify_change_phone","max attempt reached attempt is: {}",session_attempt);
+        let _remove_session = state
+            .redis
+            .delete_session_change_phone(&current_user_id.to_string());
+        return ApiResponse::un_authorized(&i18n.translate("verify_change_phone.max_attempt"));
+    }
+
+    let current_time = Local::now().naive_local().and_utc();
+    let duration = current_time - session_otp_at;
+    if duration.num_minutes() >= 2 {
+        info!(target:"verify_change_phone","otp expired {:?} minutes",duration.num_minutes());
+        let _remove_session = state
+            .redis
+            .delete_session_change_phone(&current_user_id.to_string());
+        return ApiResponse::un_authorized(&i18n.translate("verify_change_phone.expired"));
+    }
+
+    if session_current_otp != body.current_otp || session_new_otp != body.new_otp {
+        info!(target:"verify_change_phone","otp did not match");
+        session_attempt += 1;
+        let _update_attempt = state.redis.set_session_change_phone(
+            &current_user_id.to_string(),
+            &[(REDIS_KEY_OTP_ATTEMPT, session_attempt.to_string())],
+        );
+        return ApiResponse::un_authorized(&i18n.translate("verify_change_phone.invalid_otp"));
+    }
+
+    let find_user = DB::get(COLLECTION_USERS)
+        .filter(vec![is("_id", current_user_id)])
+        .get_one::<UserDTO>(&state.db)
+        .await;
+    if let Err(why) = find_user {
+        info!(target:"verify_change_phone","user not exist {:?}",why);
+        return ApiResponse::not_found(&i18n.translate("verify_change_phone.user_not_exist"));
+    }
+    let user = find_user.unwrap();
+
+    //the number can be registered by someone else while the otp is pending
+    let find_duplicate_phone_number = DB::get(COLLECTION_USERS)
+        .filter(vec![
+            is("phone_number", session_phone_number.clone()),
+            not_equal("_id", current_user_id),
+        ])
+        .count(&state.db)
+        .await
+        .unwrap_or(0);
+    if find_duplicate_phone_number > 0 {
+        info!(target:"verify_change_phone","phone number already used");
+        let _remove_session = state
+            .redis
+            .delete_session_change_phone(&current_user_id.to_string());
+        return ApiResponse::failed(&i18n.translate("verify_change_phone.phone_number_in_use"));
+    }
+
+    let session = state.db.start_session().await;
+    if let Err(err) = session {
+        info!(target:"verify_change_phone","{:?}",err);
+        return ApiResponse::failed(&i18n.translate("verify_change_phone.failed"));
+    }
+    let mut session = session.unwrap();
+    let _ = session.start_transaction().await;
+
+    let update_user = DB::update(COLLECTION_USERS)
+        .filter(vec![is("_id", current_user_id)])
+        .set(doc! {
+            "phone_number":session_phone_number.clone(),
+            "phone_hash":hash_phone_number(&session_phone_number),
+            "updated_at":DateTime::now()
+        })
+        .execute_with_session(&state.db, &mut session)
+        .await;
+    if let Err(why) = update_user {
+        //unique index on phone number is the last guard
+        info!(target:"verify_change_phone","failed to update user {}",why);
+        let _ = session.abort_transaction().await;
+        return ApiResponse::failed(&i18n.translate("verify_change_phone.failed"));
+    }
+
+    //invitation sent to the new number before it belong to this account
+    let link_invitation = DB::update(COLLECTION_EVENT_INVITATION)
+        .filter(vec![
+            is("phone_number", session_phone_number.clone()),
+            is("user_id", create_object_id_option(DEFAULT_ID_NON_MEMBER)),
+        ])
+        .set(doc! {
+            "user_id":current_user_id,
+            "updated_at":DateTime::now()
+        })
+        .execute_many_with_session(&state.db, &mut session)
+        .await;
+    if let Err(why) = link_invitation {
+        info!(target:"verify_change_phone","{:?}",why);
+        let _ = session.abort_transaction().await;
+        return ApiResponse::failed(&i18n.translate("verify_change_phone.failed"));
+    }
+
+    let audit = UserAuditLog {
+        id: Some(ObjectId::new()),
+        user_id: Some(current_user_id),
+        action: USER_AUDIT_PHONE_CHANGED.to_string(),
+        previous_value: Some(user.phone_number.clone()),
+        new_value: Some(session_phone_number.clone()),
+        created_at: DateTime::now(),
+    };
+    let save_audit = DB::insert(COLLECTION_USER_AUDIT_LOG)
+        .one_with_session(audit, &state.db, &mut session)
+        .await;
+    if let Err(why) = save_audit {
+        info!(target:"verify_change_phone","{:?}",why);
+        let _ = session.abort_transaction().await;
+        return ApiResponse::failed(&i18n.translate("verify_change_phone.failed"));
+    }
+    let _ = session.commit_transaction().await;
+
+    //every device has to sign in again with the new number
+    let _remove_session = state
+        .redis
+        .delete_session_change_phone(&current_user_id.to_string());
+    let _revoke = state
+        .redis
+        .revoke_session_user(&current_user_id.to_string());
+
+    let find_user = DB::get(COLLECTION_USERS)
+        .filter(vec![is("_id", current_user_id)])
+        .get_one::<UserDTO>(&state.db)
+        .await;
+    if let Err(why) = find_user {
+        info!(target:"verify_change_phone","user not exist {:?}",why);
+        return ApiResponse::not_found(&i18n.translate("verify_change_phone.user_not_exist"));
+    }
+
+    info!(target:"verify_change_phone","finish");
+    ApiResponse::ok(
+        find_user.unwrap(),
+        &i18n.translate("verify_change_phone.ok"),
+    )
+}
@@ -0,0 +1,7 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CalendarFeedResponse {
+    pub token: String,
+    pub url: String,
+}
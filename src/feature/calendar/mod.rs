@@ -0,0 +1,279 @@
+use crate::common::api_response::ApiResponse;
+use crate::common::app_state::AppState;
+use crate::common::constant::{
+    COLLECTION_CALENDAR_FEED, COLLECTION_EVENTS, COLLECTION_USERS, EVENT_STATUS_DRAFT,
+};
+use crate::common::env_config::EnvConfig;
+use crate::common::ical::ICalendar;
+use crate::common::jwt::AuthContext;
+use crate::common::lang::Lang;
+use crate::common::mongo::DB;
+use crate::common::mongo::filter::{is, is_in, not_equal, or};
+use crate::common::mongo::lookup::one;
+use crate::common::utils::{create_object_id_option, generate_secret_token};
+use crate::dto::event_dto::EventDTO;
+use crate::entity::calendar_feed::CalendarFeed;
+use crate::feature::calendar::calendar_model::CalendarFeedResponse;
+use crate::feature::event::event_model::EventAccessRequest;
+use crate::feature::event::event_policy::{check_event_access, get_participating_event_ids};
+use crate::i18n;
+use axum::extract::{Path, Query, State};
+use axum::http::header;
+use axum::response::{IntoResponse, Response};
+use bson::oid::ObjectId;
+use bson::{DateTime, doc};
+use log::info;
+use mongodb::Client;
+
+pub mod calendar_model;
+
+const ICS_CONTENT_TYPE: &str = "text/calendar; charset=utf-8";
+
+pub async fn export_event_ics(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Path(event_id): Path<String>,
+    Query(access): Query<EventAccessRequest>,
+) -> Response {
+    info!(target:"export_event_ics","Starting...");
+    let i18n = i18n!("calendar", lang);
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"export_event_ics","user id not found");
+        return ApiResponse::<String>::failed(&i18n.translate("export_event_ics.user_id_not_found"))
+            .into_response();
+    }
+    let current_user_id = auth_context.get_user_id().unwrap();
+
+    let create_event_id = create_object_id_option(&event_id);
+    if let None = create_event_id {
+        info!(target:"export_event_ics","event id invalid");
+        return ApiResponse::<String>::failed(&i18n.translate("export_event_ics.event_id_invalid"))
+            .into_response();
+    }
+    let create_event_id = create_event_id.unwrap();
+
+    let find_event = DB::get(COLLECTION_EVENTS)
+        .filter(vec![is("_id", create_event_id)])
+        .lookup(&[one(COLLECTION_USERS, "host_id", "_id", "host")])
+        .get_one::<EventDTO>(&state.db)
+        .await;
+    if let Err(why) = find_event {
+        info!(target:"export_event_ics","cannot find event {}",why);
+        return ApiResponse::<String>::not_found(&i18n.translate("export_event_ics.event_not_found"))
+            .into_response();
+    }
+    let event = find_event.unwrap();
+
+    let viewer_role = check_event_access(&state.db, &event, Some(current_user_id), &access).await;
+    if let Err(why) = viewer_role {
+        info!(target:"export_event_ics","access denied {}",why);
+        return ApiResponse::<String>::access_denied(
+            &i18n.translate(&format!("export_event_ics.{}", why)),
+        )
+        .into_response();
+    }
+
+    if let None = event.datetime {
+        info!(target:"export_event_ics","event has no schedule");
+        return ApiResponse::<String>::failed(&i18n.translate("export_event_ics.no_schedule"))
+            .into_response();
+    }
+
+    let calendar = ICalendar::new(&event.event_name)
+        .set_timezone(event.timezone.clone())
+        .add_event(&event)
+        .build();
+
+    info!(target:"export_event_ics","finish");
+    (
+        [
+            (header::CONTENT_TYPE, ICS_CONTENT_TYPE.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}.ics\"", event_id),
+            ),
+        ],
+        calendar,
+    )
+        .into_response()
+}
+
+pub async fn get_calendar_feed_url(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+) -> ApiResponse<CalendarFeedResponse> {
+    info!(target:"get_calendar_feed_url","Starting...");
+    let i18n = i18n!("calendar", lang);
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"get_calendar_feed_url","user id not found");
+        return ApiResponse::failed(&i18n.translate("get_calendar_feed_url.user_id_not_found"));
+    }
+    let current_user_id = auth_context.get_user_id().unwrap();
+
+    let find_feed = DB::get(COLLECTION_CALENDAR_FEED)
+        .filter(vec![is("user_id", current_user_id)])
+        .get_one::<CalendarFeed>(&state.db)
+        .await;
+    if let Ok(feed) = find_feed {
+        info!(target:"get_calendar_feed_url","finish");
+        return ApiResponse::ok(
+            to_feed_response(feed.token),
+            &i18n.translate("get_calendar_feed_url.ok"),
+        );
+    }
+
+    let feed = CalendarFeed {
+        id: Some(ObjectId::new()),
+        user_id: Some(current_user_id),
+        token: generate_secret_token(),
+        created_at: DateTime::now(),
+        updated_at: DateTime::now(),
+    };
+    let save_feed = DB::insert(COLLECTION_CALENDAR_FEED)
+        .one(feed.clone(), &state.db)
+        .await;
+    if let Err(why) = save_feed {
+        info!(target:"get_calendar_feed_url","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("get_calendar_feed_url.failed"));
+    }
+
+    info!(target:"get_calendar_feed_url","finish");
+    ApiResponse::ok(
+        to_feed_response(feed.token),
+        &i18n.translate("get_calendar_feed_url.ok"),
+    )
+}
+
+//old url stop working right away, used when the link leaked
+pub async fn reset_calendar_feed_url(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+) -> ApiResponse<CalendarFeedResponse> {
+    info!(target:"reset_calendar_feed_url","Starting...");
+    let i18n = i18n!("calendar", lang);
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"reset_calendar_feed_url","user id not found");
+        return ApiResponse::failed(&i18n.translate("reset_calendar_feed_url.user_id_not_found"));
+    }
+    let current_user_id = auth_context.get_user_id().unwrap();
+    let token = generate_secret_token();
+
+    let update_feed = DB::update(COLLECTION_CALENDAR_FEED)
+        .filter(vec![is("user_id", current_user_id)])
+        .set(doc! {
+            "token": token.clone(),
+            "updated_at": DateTime::now()
+        })
+        .execute(&state.db)
+        .await;
+    if let Err(why) = update_feed {
+        info!(target:"reset_calendar_feed_url","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("reset_calendar_feed_url.failed"));
+    }
+
+    if update_feed.unwrap() == 0 {
+        let save_feed = DB::insert(COLLECTION_CALENDAR_FEED)
+            .one(
+                CalendarFeed {
+                    id: Some(ObjectId::new()),
+                    user_id: Some(current_user_id),
+                    token: token.clone(),
+                    created_at: DateTime::now(),
+                    updated_at: DateTime::now(),
+                },
+                &state.db,
+            )
+            .await;
+        if let Err(why) = save_feed {
+            info!(target:"reset_calendar_feed_url","{:?}",why);
+            return ApiResponse::failed(&i18n.translate("reset_calendar_feed_url.failed"));
+        }
+    }
+
+    info!(target:"reset_calendar_feed_url","finish");
+    ApiResponse::ok(
+        to_feed_response(token),
+        &i18n.translate("reset_calendar_feed_url.ok"),
+    )
+}
+
+//public, the token in the url is the credential so calendar apps can subscribe
+pub async fn get_calendar_feed(
+    state: State<AppState>,
+    lang: Lang,
+    Path(token): Path<String>,
+) -> Response {
+    info!(target:"get_calendar_feed","Starting...");
+    let i18n = i18n!("calendar", lang);
+    let token = token.trim_end_matches(".ics").to_string();
+
+    let find_feed = DB::get(COLLECTION_CALENDAR_FEED)
+        .filter(vec![is("token", token)])
+        .get_one::<CalendarFeed>(&state.db)
+        .await;
+    if let Err(why) = find_feed {
+        info!(target:"get_calendar_feed","feed not found {}",why);
+        return ApiResponse::<String>::not_found(&i18n.translate("get_calendar_feed.not_found"))
+            .into_response();
+    }
+    let feed = find_feed.unwrap();
+    if let None = feed.user_id {
+        info!(target:"get_calendar_feed","feed has no owner");
+        return ApiResponse::<String>::not_found(&i18n.translate("get_calendar_feed.not_found"))
+            .into_response();
+    }
+    let user_id = feed.user_id.unwrap();
+
+    let find_event = get_calendar_events(&state.db, user_id).await;
+    if let Err(why) = find_event {
+        info!(target:"get_calendar_feed","{:?}",why);
+        return ApiResponse::<String>::failed(&i18n.translate("get_calendar_feed.failed"))
+            .into_response();
+    }
+
+    let calendar = find_event
+        .unwrap()
+        .iter()
+        .fold(
+            ICalendar::new(&i18n.translate("get_calendar_feed.calendar_name")),
+            |calendar, event| calendar.add_event(event),
+        )
+        .build();
+
+    info!(target:"get_calendar_feed","finish");
+    ([(header::CONTENT_TYPE, ICS_CONTENT_TYPE)], calendar).into_response()
+}
+
+fn to_feed_response(token: String) -> CalendarFeedResponse {
+    let env = EnvConfig::init();
+    CalendarFeedResponse {
+        url: format!(
+            "{}/calendar/feed/{}.ics",
+            env.base_url.trim_end_matches('/'),
+            token
+        ),
+        token,
+    }
+}
+
+//hosted, joined and invited events. draft is not shared yet, cancelled stay in the feed
+//so subscribed calendar remove it instead of keeping a stale entry
+async fn get_calendar_events(db: &Client, user_id: ObjectId) -> Result<Vec<EventDTO>, String> {
+    let event_ids = get_participating_event_ids(db, user_id).await;
+
+    DB::get(COLLECTION_EVENTS)
+        .filter(vec![
+            or(&[is("host_id", user_id), is_in("_id", event_ids)]),
+            not_equal("status", EVENT_STATUS_DRAFT),
+        ])
+        .lookup(&[one(COLLECTION_USERS, "host_id", "_id", "host")])
+        .sort(vec![("datetime", 1)])
+        .get_all::<EventDTO>(db)
+        .await
+}
@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CheckInQrRequest {
+    pub format: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Validate)]
+pub struct ScanCheckInRequest {
+    #[validate(length(min = 1))]
+    pub token: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CheckInTokenResponse {
+    pub event_id: String,
+    pub guest_id: String,
+    pub token: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CheckInCountResponse {
+    pub event_id: String,
+    pub checked_in: i64,
+    pub confirmed: i64,
+}
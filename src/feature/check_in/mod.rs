@@ -0,0 +1,331 @@
+use crate::common::api_response::ApiResponse;
+use crate::common::app_state::AppState;
+use crate::common::constant::{
+    CHECK_IN_TOKEN_GRACE_SECONDS, CHECK_IN_TOKEN_UNSCHEDULED_SECONDS, COLLECTION_EVENT_GUEST,
+    COLLECTION_EVENTS, COLLECTION_USERS, EVENT_GUEST_RSVP_GOING, SSE_EVENT_UPDATE_EVENT_CHECK_IN,
+};
+use crate::common::jwt::{AuthContext, JwtUtil};
+use crate::common::lang::Lang;
+use crate::common::middleware::Json;
+use crate::common::mongo::DB;
+use crate::common::mongo::filter::{is, not_equal};
+use crate::common::mongo::lookup::one;
+use crate::common::sse::sse_builder::{SseBuilder, SseTarget};
+use crate::common::utils::create_object_id_option;
+use crate::dto::event_dto::EventDTO;
+use crate::dto::event_guest_dto::EventGuestDTO;
+use crate::feature::check_in::check_in_model::{
+    CheckInCountResponse, CheckInQrRequest, CheckInTokenResponse, ScanCheckInRequest,
+};
+use crate::feature::event::event_policy::{get_event_host_ids, is_event_host};
+use crate::i18n;
+use axum::extract::{Path, Query, State};
+use axum::http::header;
+use axum::response::{IntoResponse, Response};
+use bson::oid::ObjectId;
+use bson::{Bson, DateTime, doc};
+use image::{ImageFormat, Luma};
+use log::info;
+use mongodb::Client;
+use qrcode::QrCode;
+use qrcode::render::svg;
+use std::io::Cursor;
+use validator::Validate;
+
+pub mod check_in_model;
+
+pub async fn get_check_in_token(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Path(event_id): Path<String>,
+) -> ApiResponse<CheckInTokenResponse> {
+    info!(target:"get_check_in_token","Starting...");
+    let i18n = i18n!("check_in", lang);
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"get_check_in_token","user id not found");
+        return ApiResponse::failed(&i18n.translate("get_check_in_token.user_id_not_found"));
+    }
+    let current_user_id = auth_context.get_user_id().unwrap();
+
+    let create_event_id = create_object_id_option(&event_id);
+    if let None = create_event_id {
+        info!(target:"get_check_in_token","event id invalid");
+        return ApiResponse::failed(&i18n.translate("get_check_in_token.event_id_invalid"));
+    }
+    let create_event_id = create_event_id.unwrap();
+
+    let find_guest = find_confirmed_guest(&state.db, create_event_id, current_user_id).await;
+    if let Err(why) = find_guest {
+        info!(target:"get_check_in_token","guest not confirmed {}",why);
+        return ApiResponse::access_denied(&i18n.translate("get_check_in_token.not_confirmed"));
+    }
+    let guest = find_guest.unwrap();
+
+    let find_expiry = find_check_in_expiry(&state.db, create_event_id).await;
+    if let Err(why) = find_expiry {
+        info!(target:"get_check_in_token","cannot find event {}",why);
+        return ApiResponse::not_found(&i18n.translate("get_check_in_token.event_not_found"));
+    }
+
+    let token = JwtUtil::encode_check_in(
+        guest.id.unwrap().to_string(),
+        event_id.clone(),
+        find_expiry.unwrap(),
+    );
+    if let None = token {
+        info!(target:"get_check_in_token","failed create token");
+        return ApiResponse::failed(&i18n.translate("get_check_in_token.failed"));
+    }
+
+    info!(target:"get_check_in_token","finish");
+    ApiResponse::ok(
+        CheckInTokenResponse {
+            event_id,
+            guest_id: guest.id.unwrap().to_string(),
+            token: token.unwrap(),
+        },
+        &i18n.translate("get_check_in_token.ok"),
+    )
+}
+
+pub async fn get_check_in_qr(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Path(event_id): Path<String>,
+    Query(query): Query<CheckInQrRequest>,
+) -> Response {
+    info!(target:"get_check_in_qr","Starting...");
+    let i18n = i18n!("check_in", lang);
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"get_check_in_qr","user id not found");
+        return ApiResponse::<String>::failed(&i18n.translate("get_check_in_qr.user_id_not_found"))
+            .into_response();
+    }
+    let current_user_id = auth_context.get_user_id().unwrap();
+
+    let create_event_id = create_object_id_option(&event_id);
+    if let None = create_event_id {
+        info!(target:"get_check_in_qr","event id invalid");
+        return ApiResponse::<String>::failed(&i18n.translate("get_check_in_qr.event_id_invalid"))
+            .into_response();
+    }
+    let create_event_id = create_event_id.unwrap();
+
+    let find_guest = find_confirmed_guest(&state.db, create_event_id, current_user_id).await;
+    if let Err(why) = find_guest {
+        info!(target:"get_check_in_qr","guest not confirmed {}",why);
+        return ApiResponse::<String>::access_denied(
+            &i18n.translate("get_check_in_qr.not_confirmed"),
+        )
+        .into_response();
+    }
+    let guest = find_guest.unwrap();
+
+    let find_expiry = find_check_in_expiry(&state.db, create_event_id).await;
+    if let Err(why) = find_expiry {
+        info!(target:"get_check_in_qr","cannot find event {}",why);
+        return ApiResponse::<String>::not_found(
+            &i18n.translate("get_check_in_qr.event_not_found"),
+        )
+        .into_response();
+    }
+
+    let token = JwtUtil::encode_check_in(
+        guest.id.unwrap().to_string(),
+        event_id,
+        find_expiry.unwrap(),
+    );
+    if let None = token {
+        info!(target:"get_check_in_qr","failed create token");
+        return ApiResponse::<String>::failed(&i18n.translate("get_check_in_qr.failed"))
+            .into_response();
+    }
+
+    let code = QrCode::new(token.unwrap().as_bytes());
+    if let Err(why) = code {
+        info!(target:"get_check_in_qr","{:?}",why);
+        return ApiResponse::<String>::failed(&i18n.translate("get_check_in_qr.failed"))
+            .into_response();
+    }
+    let code = code.unwrap();
+
+    if query.format.unwrap_or("png".to_string()) == "svg" {
+        let image = code
+            .render::<svg::Color>()
+            .min_dimensions(256, 256)
+            .build();
+        info!(target:"get_check_in_qr","finish");
+        return ([(header::CONTENT_TYPE, "image/svg+xml")], image).into_response();
+    }
+
+    let image = code.render::<Luma<u8>>().min_dimensions(256, 256).build();
+    let mut bytes: Vec<u8> = Vec::new();
+    if let Err(why) = image.write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png) {
+        info!(target:"get_check_in_qr","{:?}",why);
+        return ApiResponse::<String>::failed(&i18n.translate("get_check_in_qr.failed"))
+            .into_response();
+    }
+
+    info!(target:"get_check_in_qr","finish");
+    ([(header::CONTENT_TYPE, "image/png")], bytes).into_response()
+}
+
+pub async fn scan_check_in(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Json(body): Json<ScanCheckInRequest>,
+) -> ApiResponse<EventGuestDTO> {
+    info!(target:"scan_check_in","Starting...");
+    let i18n = i18n!("check_in", lang);
+    if let Err(err) = body.validate() {
+        info!(target:"scan_check_in","{:?}", err);
+        return ApiResponse::error_validation(
+            err,
+            &i18n.translate("scan_check_in.validation_error"),
+        );
+    }
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"scan_check_in","user id not found");
+        return ApiResponse::failed(&i18n.translate("scan_check_in.user_id_not_found"));
+    }
+    let current_user_id = auth_context.get_user_id().unwrap();
+
+    let claims = JwtUtil::decode_check_in(body.token.clone());
+    if let Err(why) = claims {
+        info!(target:"scan_check_in","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("scan_check_in.token_invalid"));
+    }
+    let claims = claims.unwrap();
+
+    let create_event_id = create_object_id_option(&claims.event_id);
+    let create_guest_id = create_object_id_option(&claims.sub);
+    if create_event_id.is_none() || create_guest_id.is_none() {
+        info!(target:"scan_check_in","token contains invalid id");
+        return ApiResponse::failed(&i18n.translate("scan_check_in.token_invalid"));
+    }
+    let create_event_id = create_event_id.unwrap();
+    let create_guest_id = create_guest_id.unwrap();
+
+    let find_event = DB::get(COLLECTION_EVENTS)
+        .filter(vec![is("_id", create_event_id)])
+        .get_one::<EventDTO>(&state.db)
+        .await;
+    if let Err(why) = find_event {
+        info!(target:"scan_check_in","cannot find events {}",why);
+        return ApiResponse::not_found(&i18n.translate("scan_check_in.event_not_found"));
+    }
+    let event = find_event.unwrap();
+
+    if !is_event_host(&state.db, &event, current_user_id).await {
+        info!(target:"scan_check_in","only host can check in guest");
+        return ApiResponse::access_denied(&i18n.translate("scan_check_in.not_host"));
+    }
+
+    let find_guest = DB::get(COLLECTION_EVENT_GUEST)
+        .filter(vec![
+            is("_id", create_guest_id),
+            is("event_id", create_event_id),
+            is("rsvp", EVENT_GUEST_RSVP_GOING),
+        ])
+        .lookup(&[one(COLLECTION_USERS, "user_id", "_id", "user")])
+        .get_one::<EventGuestDTO>(&state.db)
+        .await;
+    if let Err(why) = find_guest {
+        info!(target:"scan_check_in","guest not confirmed {}",why);
+        return ApiResponse::not_found(&i18n.translate("scan_check_in.guest_not_found"));
+    }
+    let mut guest = find_guest.unwrap();
+
+    if guest.checked_in_at.is_some() {
+        info!(target:"scan_check_in","guest already checked in");
+        return ApiResponse::failed(&i18n.translate("scan_check_in.already_checked_in"));
+    }
+
+    //filter on checked_in_at so two scan at the same time cannot both succeed
+    let checked_in_at = DateTime::now();
+    let update = DB::update(COLLECTION_EVENT_GUEST)
+        .filter(vec![is("_id", create_guest_id), is("checked_in_at", Bson::Null)])
+        .set(doc! {
+            "checked_in_at":checked_in_at,
+            "updated_at":checked_in_at
+        })
+        .execute(&state.db)
+        .await;
+    if let Err(why) = update {
+        info!(target:"scan_check_in","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("scan_check_in.failed"));
+    }
+    if update.unwrap() == 0 {
+        info!(target:"scan_check_in","guest already checked in");
+        return ApiResponse::failed(&i18n.translate("scan_check_in.already_checked_in"));
+    }
+    guest.checked_in_at = Some(checked_in_at);
+    guest.updated_at = checked_in_at;
+
+    let checked_in = DB::get(COLLECTION_EVENT_GUEST)
+        .filter(vec![
+            is("event_id", create_event_id),
+            not_equal("checked_in_at", Bson::Null),
+        ])
+        .count(&state.db)
+        .await
+        .unwrap_or(0);
+    let confirmed = DB::get(COLLECTION_EVENT_GUEST)
+        .filter(vec![
+            is("event_id", create_event_id),
+            is("rsvp", EVENT_GUEST_RSVP_GOING),
+        ])
+        .count(&state.db)
+        .await
+        .unwrap_or(0);
+
+    let host_ids = get_event_host_ids(&state.db, &event).await;
+    let sse = SseBuilder::new(
+        SseTarget::create()
+            .set_event_name(SSE_EVENT_UPDATE_EVENT_CHECK_IN.to_string())
+            .set_user_ids(host_ids),
+        CheckInCountResponse {
+            event_id: create_event_id.to_string(),
+            checked_in,
+            confirmed,
+        },
+    );
+    state.sse.send(sse).await;
+
+    info!(target:"scan_check_in","finish");
+    ApiResponse::ok(guest, &i18n.translate("scan_check_in.ok"))
+}
+
+//the qr is shown at the door, it is useless once the event is over
+async fn find_check_in_expiry(db: &Client, event_id: ObjectId) -> Result<i64, String> {
+    let event = DB::get(COLLECTION_EVENTS)
+        .filter(vec![is("_id", event_id)])
+        .get_one::<EventDTO>(db)
+        .await?;
+    let expiry = match event.end_datetime.or(event.datetime) {
+        Some(end) => end.timestamp_millis() / 1000 + CHECK_IN_TOKEN_GRACE_SECONDS,
+        None => DateTime::now().timestamp_millis() / 1000 + CHECK_IN_TOKEN_UNSCHEDULED_SECONDS,
+    };
+    Ok(expiry)
+}
+
+async fn find_confirmed_guest(
+    db: &Client,
+    event_id: ObjectId,
+    user_id: ObjectId,
+) -> Result<EventGuestDTO, String> {
+    DB::get(COLLECTION_EVENT_GUEST)
+        .filter(vec![
+            is("event_id", event_id),
+            is("user_id", user_id),
+            is("rsvp", EVENT_GUEST_RSVP_GOING),
+        ])
+        .get_one::<EventGuestDTO>(db)
+        .await
+}
@@ -0,0 +1,37 @@
+use crate::common::constant::{CONTACT_INVITE_MAX_SIZE, CONTACT_SYNC_MAX_SIZE};
+use crate::dto::user_summary_dto::UserSummaryDTO;
+use bson::DateTime;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+//hashes are hex sha256 of the number normalized to 628xx
+#[derive(Serialize, Deserialize, Debug, Clone, Validate)]
+pub struct SyncContactRequest {
+    #[validate(length(min = 1, max = CONTACT_SYNC_MAX_SIZE))]
+    pub hashes: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ContactMatchResponse {
+    pub phone_hash: String,
+    pub user: UserSummaryDTO,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SyncContactResponse {
+    pub synced: usize,
+    pub matches: Vec<ContactMatchResponse>,
+    pub expires_at: DateTime,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Validate)]
+pub struct InviteContactRequest {
+    pub event_id: String,
+    pub expired_at: String,
+    #[serde(default)]
+    #[validate(length(max = CONTACT_INVITE_MAX_SIZE))]
+    pub user_ids: Vec<String>,
+    #[serde(default)]
+    #[validate(length(max = CONTACT_INVITE_MAX_SIZE))]
+    pub phone_numbers: Vec<String>,
+}
@@ -0,0 +1,380 @@
+use crate::common::api_response::ApiResponse;
+use crate::common::app_state::AppState;
+use crate::common::constant::{
+    COLLECTION_MUTUAL, COLLECTION_USER_CONTACT, COLLECTION_USERS, CONTACT_SYNC_RATE_LIMIT,
+    CONTACT_SYNC_RATE_WINDOW_SECONDS, MUTUAL_SOURCE_CONTACT, MUTUAL_STATUS_SUGGESTED,
+};
+use crate::common::env_config::EnvConfig;
+use crate::common::jwt::AuthContext;
+use crate::common::lang::Lang;
+use crate::common::middleware::Json;
+use crate::common::mongo::DB;
+use crate::common::mongo::filter::{greater, is, is_in, is_not_in, not_equal};
+use crate::common::mongo::lookup::one;
+use crate::common::utils::{create_object_id_option, hash_phone_number};
+use crate::dto::mutual_dto::MutualDTO;
+use crate::dto::user_contact_dto::UserContactDTO;
+use crate::dto::user_dto::UserDTO;
+use crate::entity::mutual::Mutual;
+use crate::entity::user_contact::UserContact;
+use crate::feature::contact::contact_model::{
+    ContactMatchResponse, InviteContactRequest, SyncContactRequest, SyncContactResponse,
+};
+use crate::feature::invitation::invitation_model::{
+    InvitationRequest, PhoneInvitationRequest, SendInvitationRequest,
+};
+use crate::feature::invitation::send_invitation;
+use crate::feature::mutual::get_blocked_user_ids;
+use crate::i18n;
+use axum::extract::State;
+use bson::oid::ObjectId;
+use bson::{DateTime, doc};
+use log::info;
+use validator::Validate;
+
+pub mod contact_model;
+
+//every sync replace the previous upload, so removed contacts are forgotten right away
+pub async fn sync_contacts(
+    mut state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Json(body): Json<SyncContactRequest>,
+) -> ApiResponse<SyncContactResponse> {
+    info!(target:"sync_contacts","Starting...");
+    let i18n = i18n!("contact", lang);
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"sync_contacts","user id not found");
+        return ApiResponse::access_denied(&i18n.translate("sync_contacts.user_id_not_found"));
+    }
+    let current_user_id = auth_context.get_user_id().unwrap();
+
+    let validate = body.validate();
+    if let Err(err) = validate {
+        info!(target:"sync_contacts","validation error {:?}",err);
+        return ApiResponse::error_validation(err, &i18n.translate("sync_contacts.validation_error"));
+    }
+
+    //the number space is small enough to enumerate, keep the lookup slow
+    if !state.redis.hit_rate_limit(
+        "contact_sync",
+        &current_user_id.to_string(),
+        CONTACT_SYNC_RATE_LIMIT,
+        CONTACT_SYNC_RATE_WINDOW_SECONDS,
+    ) {
+        info!(target:"sync_contacts","rate limit reached");
+        return ApiResponse::failed(&i18n.translate("sync_contacts.rate_limited"));
+    }
+
+    let mut hashes = body
+        .hashes
+        .into_iter()
+        .map(|hash| hash.trim().to_lowercase())
+        .filter(|hash| hash.len() == 64 && hash.chars().all(|char| char.is_ascii_hexdigit()))
+        .collect::<Vec<String>>();
+    hashes.sort();
+    hashes.dedup();
+
+    //blocked in either direction is not revealed as a match
+    let blocked_ids = get_blocked_user_ids(&state.db, current_user_id).await;
+    let find_matches = DB::get(COLLECTION_USERS)
+        .filter(vec![
+            is_in("phone_hash", hashes.clone()),
+            not_equal("_id", current_user_id),
+            is_not_in("_id", blocked_ids.clone()),
+        ])
+        .get_all::<UserDTO>(&state.db)
+        .await
+        .unwrap_or(Vec::new());
+    let matched_ids = find_matches
+        .iter()
+        .filter_map(|user| user.id)
+        .collect::<Vec<ObjectId>>();
+
+    //hash of each matched user, uploaded hashes are not linked to anything else
+    let matches = find_matches
+        .into_iter()
+        .filter_map(|user| {
+            let phone_hash = hash_phone_number(&user.phone_number);
+            if hashes.contains(&phone_hash) {
+                Some(ContactMatchResponse {
+                    phone_hash,
+                    user: user.into(),
+                })
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<ContactMatchResponse>>();
+
+    let retention_millis = EnvConfig::init().contact_retention_days * 24 * 60 * 60 * 1000;
+    let expires_at = DateTime::from_millis(DateTime::now().timestamp_millis() + retention_millis);
+    let contacts = hashes
+        .iter()
+        .map(|phone_hash| UserContact {
+            id: Some(ObjectId::new()),
+            user_id: Some(current_user_id),
+            phone_hash: phone_hash.clone(),
+            matched_user_id: matches
+                .iter()
+                .find(|matched| &matched.phone_hash == phone_hash)
+                .and_then(|matched| matched.user.id),
+            expires_at,
+            created_at: DateTime::now(),
+            updated_at: DateTime::now(),
+        })
+        .collect::<Vec<UserContact>>();
+
+    //keep friends and earlier suggestions as they are
+    let known_mutual_ids = DB::get(COLLECTION_MUTUAL)
+        .filter(vec![
+            is("user_id", current_user_id),
+            is_in("mutual_id", matched_ids.clone()),
+        ])
+        .get_all::<MutualDTO>(&state.db)
+        .await
+        .unwrap_or(Vec::new())
+        .into_iter()
+        .filter_map(|mutual| mutual.mutual_id)
+        .collect::<Vec<ObjectId>>();
+    let suggestions = matches
+        .iter()
+        .filter_map(|matched| matched.user.id.map(|id| (id, matched.user.display_name.clone())))
+        .filter(|(id, _)| !known_mutual_ids.contains(id))
+        .map(|(id, display_name)| Mutual {
+            id: Some(ObjectId::new()),
+            user_id: Some(current_user_id),
+            mutual_id: Some(id),
+            display_name: Some(display_name),
+            status: Some(MUTUAL_STATUS_SUGGESTED.to_string()),
+            source: Some(MUTUAL_SOURCE_CONTACT.to_string()),
+            created_at: DateTime::now(),
+            updated_at: DateTime::now(),
+        })
+        .collect::<Vec<Mutual>>();
+
+    let session = state.db.start_session().await;
+    if let Err(why) = session {
+        info!(target:"sync_contacts","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("sync_contacts.failed"));
+    }
+    let mut session = session.unwrap();
+    let _ = session.start_transaction().await;
+
+    let delete_previous = DB::delete(COLLECTION_USER_CONTACT)
+        .filter(vec![is("user_id", current_user_id)])
+        .many_with_session(&state.db, &mut session)
+        .await;
+    if let Err(why) = delete_previous {
+        info!(target:"sync_contacts","{:?}",why);
+        let _ = session.abort_transaction().await;
+        return ApiResponse::failed(&i18n.translate("sync_contacts.failed"));
+    }
+
+    if !contacts.is_empty() {
+        let save_contacts = DB::insert(COLLECTION_USER_CONTACT)
+            .many_with_session(contacts.clone(), &state.db, &mut session)
+            .await;
+        if let Err(why) = save_contacts {
+            info!(target:"sync_contacts","{:?}",why);
+            let _ = session.abort_transaction().await;
+            return ApiResponse::failed(&i18n.translate("sync_contacts.failed"));
+        }
+    }
+
+    if !suggestions.is_empty() {
+        let save_suggestions = DB::insert(COLLECTION_MUTUAL)
+            .many_with_session(suggestions, &state.db, &mut session)
+            .await;
+        if let Err(why) = save_suggestions {
+            info!(target:"sync_contacts","{:?}",why);
+            let _ = session.abort_transaction().await;
+            return ApiResponse::failed(&i18n.translate("sync_contacts.failed"));
+        }
+    }
+    let _ = session.commit_transaction().await;
+
+    info!(target:"sync_contacts","finish");
+    ApiResponse::ok(
+        SyncContactResponse {
+            synced: contacts.len(),
+            matches,
+            expires_at,
+        },
+        &i18n.translate("sync_contacts.ok"),
+    )
+}
+
+pub async fn get_contact_matches(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+) -> ApiResponse<Vec<UserContactDTO>> {
+    info!(target:"get_contact_matches","Starting...");
+    let i18n = i18n!("contact", lang);
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"get_contact_matches","user id not found");
+        return ApiResponse::access_denied(&i18n.translate("get_contact_matches.user_id_not_found"));
+    }
+
+    let current_user_id = auth_context.get_user_id().unwrap();
+    let blocked_ids = get_blocked_user_ids(&state.db, current_user_id).await;
+
+    //ttl monitor is not instant, hide what already passed retention
+    let find_contacts = DB::get(COLLECTION_USER_CONTACT)
+        .filter(vec![
+            is("user_id", current_user_id),
+            greater("expires_at", DateTime::now()),
+            doc! {"matched_user_id":{"$exists":true}}.into(),
+            is_not_in("matched_user_id", blocked_ids),
+        ])
+        .lookup(&[one(COLLECTION_USERS, "matched_user_id", "_id", "matched_user")])
+        .sort(vec![("matched_user.display_name", 1)])
+        .get_all::<UserContactDTO>(&state.db)
+        .await;
+    if let Err(why) = find_contacts {
+        info!(target:"get_contact_matches","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("get_contact_matches.failed"));
+    }
+
+    info!(target:"get_contact_matches","finish");
+    ApiResponse::ok(find_contacts.unwrap(), &i18n.translate("get_contact_matches.ok"))
+}
+
+pub async fn delete_contacts(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+) -> ApiResponse<u64> {
+    info!(target:"delete_contacts","Starting...");
+    let i18n = i18n!("contact", lang);
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"delete_contacts","user id not found");
+        return ApiResponse::access_denied(&i18n.translate("delete_contacts.user_id_not_found"));
+    }
+
+    let delete_contacts = DB::delete(COLLECTION_USER_CONTACT)
+        .filter(vec![is("user_id", auth_context.get_user_id().unwrap())])
+        .many(&state.db)
+        .await;
+    if let Err(why) = delete_contacts {
+        info!(target:"delete_contacts","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("delete_contacts.failed"));
+    }
+
+    info!(target:"delete_contacts","finish");
+    ApiResponse::ok(delete_contacts.unwrap(), &i18n.translate("delete_contacts.ok"))
+}
+
+pub async fn get_contact_suggestions(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+) -> ApiResponse<Vec<MutualDTO>> {
+    info!(target:"get_contact_suggestions","Starting...");
+    let i18n = i18n!("contact", lang);
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"get_contact_suggestions","user id not found");
+        return ApiResponse::access_denied(&i18n.translate("get_contact_suggestions.user_id_not_found"));
+    }
+
+    let find_suggestions = DB::get(COLLECTION_MUTUAL)
+        .filter(vec![
+            is("user_id", auth_context.get_user_id().unwrap()),
+            is("status", MUTUAL_STATUS_SUGGESTED),
+            is("source", MUTUAL_SOURCE_CONTACT),
+        ])
+        .lookup(&[one(COLLECTION_USERS, "mutual_id", "_id", "mutual")])
+        .sort(vec![("created_at", -1)])
+        .get_all::<MutualDTO>(&state.db)
+        .await;
+    if let Err(why) = find_suggestions {
+        info!(target:"get_contact_suggestions","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("get_contact_suggestions.failed"));
+    }
+
+    info!(target:"get_contact_suggestions","finish");
+    ApiResponse::ok(find_suggestions.unwrap(), &i18n.translate("get_contact_suggestions.ok"))
+}
+
+//only people from the caller's own synced address book can be invited in bulk
+pub async fn invite_contacts(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Json(body): Json<InviteContactRequest>,
+) -> ApiResponse<String> {
+    info!(target:"invite_contacts","Starting...");
+    let i18n = i18n!("contact", lang);
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"invite_contacts","user id not found");
+        return ApiResponse::access_denied(&i18n.translate("invite_contacts.user_id_not_found"));
+    }
+    let current_user_id = auth_context.get_user_id().unwrap();
+
+    let validate = body.validate();
+    if let Err(err) = validate {
+        info!(target:"invite_contacts","validation error {:?}",err);
+        return ApiResponse::error_validation(err, &i18n.translate("invite_contacts.validation_error"));
+    }
+
+    let contacts = DB::get(COLLECTION_USER_CONTACT)
+        .filter(vec![
+            is("user_id", current_user_id),
+            greater("expires_at", DateTime::now()),
+        ])
+        .get_all::<UserContactDTO>(&state.db)
+        .await
+        .unwrap_or(Vec::new());
+
+    let users = body
+        .user_ids
+        .into_iter()
+        .filter(|user_id| {
+            let create_user_id = create_object_id_option(user_id);
+            create_user_id.is_some()
+                && contacts
+                    .iter()
+                    .any(|contact| contact.matched_user_id == create_user_id)
+        })
+        .map(|user_id| InvitationRequest {
+            expired_at: body.expired_at.clone(),
+            user_id,
+        })
+        .collect::<Vec<InvitationRequest>>();
+    let phone_numbers = body
+        .phone_numbers
+        .into_iter()
+        .filter(|phone_number| {
+            let phone_hash = hash_phone_number(phone_number);
+            contacts.iter().any(|contact| contact.phone_hash == phone_hash)
+        })
+        .map(|phone_number| PhoneInvitationRequest {
+            expired_at: body.expired_at.clone(),
+            phone_number,
+        })
+        .collect::<Vec<PhoneInvitationRequest>>();
+
+    if users.is_empty() && phone_numbers.is_empty() {
+        info!(target:"invite_contacts","nothing left after matching synced contacts");
+        return ApiResponse::failed(&i18n.translate("invite_contacts.not_in_contacts"));
+    }
+
+    info!(target:"invite_contacts","finish");
+    send_invitation(
+        state,
+        lang,
+        auth_context,
+        Json(SendInvitationRequest {
+            event_id: body.event_id,
+            users,
+            phone_numbers,
+        }),
+    )
+    .await
+}
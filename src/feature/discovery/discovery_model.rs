@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::dto::event_dto::EventDTO;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Validate)]
+pub struct DiscoveryFeedRequest {
+    pub cursor: Option<String>,
+    #[validate(range(min = 1, max = 50))]
+    pub size: Option<i64>,
+    //comma separated
+    pub category: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DiscoveryFeedResponse {
+    pub items: Vec<EventDTO>,
+    pub next_cursor: Option<String>,
+}
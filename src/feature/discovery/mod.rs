@@ -0,0 +1,267 @@
+use crate::common::api_response::ApiResponse;
+use crate::common::app_state::AppState;
+use crate::common::constant::{
+    COLLECTION_EVENT_GUEST, COLLECTION_EVENT_IMAGES, COLLECTION_EVENT_THEMES, COLLECTION_EVENTS,
+    COLLECTION_MUTUAL, COLLECTION_USERS, DISCOVERY_DEFAULT_SIZE, DISCOVERY_MAX_SIZE,
+    DISCOVERY_WEIGHT_DATE, DISCOVERY_WEIGHT_FRIEND, DISCOVERY_WEIGHT_POPULARITY,
+    EVENT_GUEST_RSVP_GOING, EVENT_STATUS_PUBLISHED, EVENT_VISIBILITY_PUBLIC,
+};
+use crate::common::jwt::AuthContext;
+use crate::common::lang::Lang;
+use crate::common::mongo::DB;
+use crate::common::mongo::filter::{greater_than_equal, is, is_in, or};
+use crate::common::mongo::lookup::{one, raw};
+use crate::common::utils::create_object_id_option;
+use crate::dto::event_dto::EventDTO;
+use crate::dto::mutual_dto::MutualDTO;
+use crate::dto::venue_location_dto::VenueLocationDTO;
+use crate::feature::discovery::discovery_model::{DiscoveryFeedRequest, DiscoveryFeedResponse};
+use crate::feature::event::present_event;
+use crate::feature::mutual::is_friend;
+use crate::i18n;
+use axum::extract::{Query, State};
+use bson::oid::ObjectId;
+use bson::{DateTime, doc};
+use log::info;
+use mongodb::Client;
+use validator::Validate;
+
+pub mod discovery_model;
+
+const WEEK_IN_MILLIS: i64 = 7 * 24 * 60 * 60 * 1000;
+
+//cursor keep the ranking time so score stay stable while paging
+struct DiscoveryCursor {
+    now: DateTime,
+    score: f64,
+    id: ObjectId,
+}
+
+impl DiscoveryCursor {
+    fn parse(cursor: &str) -> Option<DiscoveryCursor> {
+        let split = cursor.split("_").collect::<Vec<&str>>();
+        if split.len() != 3 {
+            return None;
+        }
+        let now = split[0].parse::<i64>().ok()?;
+        let score = split[1].parse::<f64>().ok()?;
+        let id = create_object_id_option(split[2])?;
+        Some(DiscoveryCursor {
+            now: DateTime::from_millis(now),
+            score,
+            id,
+        })
+    }
+
+    fn to_string(&self) -> String {
+        format!(
+            "{}_{}_{}",
+            self.now.timestamp_millis(),
+            self.score,
+            self.id.to_hex()
+        )
+    }
+}
+
+pub async fn get_discovery_feed(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: Option<AuthContext>,
+    Query(query): Query<DiscoveryFeedRequest>,
+) -> ApiResponse<DiscoveryFeedResponse> {
+    info!(target:"get_discovery_feed","Starting...");
+    let i18n = i18n!("discovery", lang);
+    if let Err(err) = query.validate() {
+        info!(target:"get_discovery_feed","{:?}", err);
+        return ApiResponse::error_validation(
+            err,
+            &i18n.translate("get_discovery_feed.validation_error"),
+        );
+    }
+    let current_user_id = auth_context.and_then(|auth_context| auth_context.get_user_id());
+
+    let cursor = match query.cursor.clone() {
+        None => None,
+        Some(cursor) => {
+            let parse = DiscoveryCursor::parse(&cursor);
+            if let None = parse {
+                info!(target:"get_discovery_feed","cursor invalid");
+                return ApiResponse::failed(&i18n.translate("get_discovery_feed.cursor_invalid"));
+            }
+            parse
+        }
+    };
+    let now = cursor
+        .as_ref()
+        .map(|cursor| cursor.now)
+        .unwrap_or(DateTime::now());
+    let size = query
+        .size
+        .unwrap_or(DISCOVERY_DEFAULT_SIZE)
+        .min(DISCOVERY_MAX_SIZE);
+
+    let friend_ids = match current_user_id {
+        None => Vec::new(),
+        Some(user_id) => get_friend_ids(&state.db, user_id).await,
+    };
+
+    let mut data = DB::get(COLLECTION_EVENTS).filter(vec![
+        is("status", EVENT_STATUS_PUBLISHED),
+        is("config.visibility", EVENT_VISIBILITY_PUBLIC),
+    ]);
+
+    let categories = query
+        .category
+        .clone()
+        .unwrap_or(String::new())
+        .split(",")
+        .map(|category| category.trim().to_lowercase())
+        .filter(|category| !category.is_empty())
+        .collect::<Vec<String>>();
+    if !categories.is_empty() {
+        data = data.filter(vec![is_in("category", categories)]);
+    }
+
+    //upcoming or still running
+    data = data.filter(vec![or(&[
+        greater_than_equal("datetime", now),
+        greater_than_equal("end_datetime", now),
+    ])]);
+
+    let mut ranking = vec![
+        raw(doc! {
+            "$lookup": {
+                "from": COLLECTION_EVENT_GUEST,
+                "let": { "event_id": "$_id" },
+                "pipeline": [
+                    { "$match": {
+                        "$expr": { "$eq": ["$event_id", "$$event_id"] },
+                        "rsvp": EVENT_GUEST_RSVP_GOING
+                    }},
+                    { "$project": { "user_id": 1 } }
+                ],
+                "as": "going"
+            }
+        }),
+        raw(doc! {
+            "$addFields": {
+                "ranking.going_count": { "$size": "$going" },
+                "ranking.friends_going": {
+                    "$size": { "$setIntersection": ["$going.user_id", friend_ids] }
+                }
+            }
+        }),
+        //closer date, more rsvp and more friends going rank higher
+        raw(doc! {
+            "$addFields": {
+                "ranking.score": { "$add": [
+                    { "$divide": [
+                        DISCOVERY_WEIGHT_DATE,
+                        { "$add": [1, { "$divide": [
+                            { "$max": [0, { "$subtract": [{ "$ifNull": ["$datetime", now] }, now] }] },
+                            WEEK_IN_MILLIS
+                        ]}]}
+                    ]},
+                    { "$multiply": [
+                        DISCOVERY_WEIGHT_POPULARITY,
+                        { "$ln": { "$add": [1, "$ranking.going_count"] } }
+                    ]},
+                    { "$multiply": [DISCOVERY_WEIGHT_FRIEND, "$ranking.friends_going"] }
+                ]}
+            }
+        }),
+        raw(doc! { "$unset": "going" }),
+    ];
+    if let Some(cursor) = cursor {
+        ranking.push(raw(doc! {
+            "$match": { "$or": [
+                { "ranking.score": { "$lt": cursor.score } },
+                { "ranking.score": cursor.score, "_id": { "$lt": cursor.id } }
+            ]}
+        }));
+    }
+
+    //take one more to know if there is a next page
+    let find_events = data
+        .lookup(&ranking)
+        .lookup(&[
+            one(COLLECTION_USERS, "host_id", "_id", "host"),
+            one(COLLECTION_EVENT_IMAGES, "image_id", "_id", "image"),
+            one(COLLECTION_EVENT_THEMES, "theme_id", "_id", "theme"),
+        ])
+        .sort(vec![("ranking.score", -1), ("_id", -1)])
+        .limit(size + 1)
+        .get_all::<EventDTO>(&state.db)
+        .await;
+    if let Err(why) = find_events {
+        info!(target:"get_discovery_feed","cannot find events {}",why);
+        return ApiResponse::failed(&i18n.translate("get_discovery_feed.failed"));
+    }
+    let mut events = find_events.unwrap();
+
+    let mut next_cursor = None;
+    if events.len() as i64 > size {
+        events.truncate(size as usize);
+        next_cursor = events.last().and_then(|event| {
+            Some(
+                DiscoveryCursor {
+                    now,
+                    score: event.ranking.clone()?.score,
+                    id: event.id?,
+                }
+                .to_string(),
+            )
+        });
+    }
+
+    let items = events
+        .into_iter()
+        .map(|mut event| {
+            event.config.event_password = None;
+            let event = present_event(event, &lang);
+            match current_user_id {
+                None => reduce_for_anonymous(event),
+                Some(_) => event,
+            }
+        })
+        .collect::<Vec<EventDTO>>();
+
+    info!(target:"get_discovery_feed","finish");
+    ApiResponse::ok(
+        DiscoveryFeedResponse { items, next_cursor },
+        &i18n.translate("get_discovery_feed.ok"),
+    )
+}
+
+async fn get_friend_ids(db: &Client, user_id: ObjectId) -> Vec<ObjectId> {
+    DB::get(COLLECTION_MUTUAL)
+        .filter(vec![
+            is("user_id", user_id),
+            is_friend(),
+        ])
+        .get_all::<MutualDTO>(db)
+        .await
+        .unwrap_or(Vec::new())
+        .into_iter()
+        .filter_map(|mutual| mutual.mutual_id)
+        .collect::<Vec<ObjectId>>()
+}
+
+//anonymous only get what is needed to render a card
+fn reduce_for_anonymous(mut event: EventDTO) -> EventDTO {
+    event.host_id = None;
+    event.host = None;
+    event.series_id = None;
+    event.allowed_transitions = Vec::new();
+    event.venue_location = event.venue_location.map(|venue| VenueLocationDTO {
+        map_id: None,
+        map_details: None,
+        venue_name: venue.venue_name,
+        venue_address: None,
+        venue_detail: None,
+        lat: None,
+        lng: None,
+        point: None,
+    });
+    event
+}
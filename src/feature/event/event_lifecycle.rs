@@ -0,0 +1,161 @@
+use crate::common::constant::{
+    COLLECTION_EVENT_INVITATION, COLLECTION_NOTIFICATION, COLLECTION_NOTIFICATION_LOG,
+    EVENT_STATUS_ARCHIVED, EVENT_STATUS_CANCELLED, EVENT_STATUS_COMPLETED, EVENT_STATUS_DRAFT,
+    EVENT_STATUS_PUBLISHED, NOTIFICATION_TYPE_EVENT_COMPLETED,
+};
+use crate::common::mongo::DB;
+use crate::common::mongo::filter::{greater_than_equal, is, or};
+use crate::dto::event_dto::EventDTO;
+use crate::entity::notification::Notification;
+use crate::entity::notification_log::NotificationLog;
+use crate::feature::mutual::suggest_co_attendees;
+use bson::oid::ObjectId;
+use bson::{Bson, DateTime, doc};
+use mongodb::{Client, ClientSession};
+
+//DRAFT -> PUBLISHED -> (CANCELLED | COMPLETED), finished or abandoned event can be ARCHIVED
+pub fn allowed_transitions(status: &str) -> Vec<String> {
+    let transitions = match status {
+        EVENT_STATUS_DRAFT => vec![EVENT_STATUS_PUBLISHED, EVENT_STATUS_ARCHIVED],
+        EVENT_STATUS_PUBLISHED => vec![EVENT_STATUS_CANCELLED, EVENT_STATUS_COMPLETED],
+        EVENT_STATUS_CANCELLED => vec![EVENT_STATUS_ARCHIVED],
+        EVENT_STATUS_COMPLETED => vec![EVENT_STATUS_ARCHIVED],
+        _ => vec![],
+    };
+    transitions
+        .into_iter()
+        .map(|status| status.to_string())
+        .collect::<Vec<String>>()
+}
+
+pub fn can_transition(from: &str, to: &str) -> bool {
+    allowed_transitions(from).contains(&to.to_string())
+}
+
+//returns the i18n key of the failed guard
+pub fn check_transition_guard(event: &EventDTO, to: &str) -> Result<(), String> {
+    match to {
+        EVENT_STATUS_PUBLISHED => {
+            if event.datetime.is_none() {
+                return Err("datetime_required".to_string());
+            }
+            let has_venue = event.venue_location.as_ref().map_or(false, |venue| {
+                venue.venue_name.is_some() || venue.venue_address.is_some()
+            });
+            if !has_venue {
+                return Err("venue_required".to_string());
+            }
+            Ok(())
+        }
+        EVENT_STATUS_COMPLETED => {
+            if event.datetime.map_or(true, |datetime| datetime > DateTime::now()) {
+                return Err("event_not_started".to_string());
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+pub fn with_lifecycle(mut event: EventDTO) -> EventDTO {
+    event.allowed_transitions = allowed_transitions(&event.status);
+    event
+}
+
+pub async fn notify_event_guests(
+    db: &Client,
+    session: &mut ClientSession,
+    event: &EventDTO,
+    kind: &str,
+    title: String,
+    user_ids: Vec<ObjectId>,
+) -> Result<(), String> {
+    if user_ids.is_empty() {
+        return Ok(());
+    }
+    let notification = Notification {
+        id: Some(ObjectId::new()),
+        ref_id: event.id,
+        kind: kind.to_string(),
+        title,
+        body: None,
+        notification_type: "".to_string(),
+        created_at: DateTime::now(),
+        updated_at: DateTime::now(),
+    };
+    let logs = user_ids
+        .into_iter()
+        .map(|user_id| NotificationLog {
+            id: Some(ObjectId::new()),
+            notification_id: notification.id,
+            ref_id: event.id,
+            user_id: Some(user_id),
+            sender_id: None,
+            is_read: false,
+            created_at: DateTime::now(),
+            updated_at: DateTime::now(),
+        })
+        .collect::<Vec<NotificationLog>>();
+
+    let save_notification = DB::insert(COLLECTION_NOTIFICATION)
+        .one_with_session(notification, db, session)
+        .await;
+    if let Err(why) = save_notification {
+        return Err(why);
+    }
+
+    let save_log = DB::insert(COLLECTION_NOTIFICATION_LOG)
+        .many_with_session(logs, db, session)
+        .await;
+    if let Err(why) = save_log {
+        return Err(why);
+    }
+    Ok(())
+}
+
+//after an event is completed invitation that still open get closed, attendees are notified
+//and suggested to each other
+pub async fn run_post_event_flows(
+    db: &Client,
+    session: &mut ClientSession,
+    event: &EventDTO,
+    title: String,
+    attendee_ids: Vec<ObjectId>,
+) -> Result<(), String> {
+    let close_invitation = DB::update(COLLECTION_EVENT_INVITATION)
+        .filter(vec![
+            is("event_id", event.id),
+            or(&[
+                is("expires_at", Bson::Null),
+                greater_than_equal("expires_at", DateTime::now()),
+            ]),
+        ])
+        .set(doc! {
+            "expires_at":DateTime::now(),
+            "updated_at":DateTime::now()
+        })
+        .execute_many_with_session(db, session)
+        .await;
+    if let Err(why) = close_invitation {
+        return Err(why);
+    }
+
+    let mut co_attendee_ids = attendee_ids.clone();
+    if let Some(host_id) = event.host_id {
+        co_attendee_ids.push(host_id);
+    }
+    let suggest_mutual = suggest_co_attendees(db, session, co_attendee_ids).await;
+    if let Err(why) = suggest_mutual {
+        return Err(why);
+    }
+
+    notify_event_guests(
+        db,
+        session,
+        event,
+        NOTIFICATION_TYPE_EVENT_COMPLETED,
+        title,
+        attendee_ids,
+    )
+    .await
+}
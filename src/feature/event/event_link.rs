@@ -0,0 +1,92 @@
+use crate::common::constant::{
+    COLLECTION_EVENT_INVITATION, COLLECTION_EVENTS, EVENT_SLUG_FALLBACK, EVENT_SLUG_MAX_LENGTH,
+};
+use crate::common::mongo::DB;
+use crate::common::mongo::filter::{is, or};
+use crate::common::utils::generate_short_code;
+use crate::dto::event_dto::EventDTO;
+use bson::{Bson, doc};
+use mongodb::Client;
+use slugify::slugify;
+
+//slug from event name, numbered when taken. reserved hold slugs of a batch not saved yet
+pub async fn generate_event_slug(db: &Client, event_name: &str, reserved: &Vec<String>) -> String {
+    let mut base = slugify!(event_name, max_length = EVENT_SLUG_MAX_LENGTH);
+    if base.is_empty() {
+        base = EVENT_SLUG_FALLBACK.to_string();
+    }
+
+    let mut slug = base.clone();
+    let mut counter = 1;
+    while reserved.contains(&slug) || is_slug_taken(db, &slug).await {
+        counter += 1;
+        slug = format!("{}-{}", base, counter);
+    }
+    slug
+}
+
+//old slug stay reserved, they keep redirecting to the renamed event
+pub async fn is_slug_taken(db: &Client, slug: &String) -> bool {
+    DB::get(COLLECTION_EVENTS)
+        .filter(vec![or(&[
+            is("slug", slug.clone()),
+            is("previous_slugs", slug.clone()),
+        ])])
+        .count(db)
+        .await
+        .map_or(false, |count| count > 0)
+}
+
+//short code shared by event and invitation link, unique across both
+pub async fn generate_invitation_code(db: &Client, reserved: &Vec<String>) -> String {
+    loop {
+        let code = generate_short_code();
+        if reserved.contains(&code) {
+            continue;
+        }
+        let used_by_event = DB::get(COLLECTION_EVENTS)
+            .filter(vec![is("invitation_id", code.clone())])
+            .count(db)
+            .await
+            .map_or(false, |count| count > 0);
+        let used_by_invitation = DB::get(COLLECTION_EVENT_INVITATION)
+            .filter(vec![is("invitation_code", code.clone())])
+            .count(db)
+            .await
+            .map_or(false, |count| count > 0);
+        if !used_by_event && !used_by_invitation {
+            return code;
+        }
+    }
+}
+
+//events created before slug and short code existed have no slug and an empty invitation_id
+pub async fn backfill_event_links(db: &Client) -> Result<u64, String> {
+    let events = DB::get(COLLECTION_EVENTS)
+        .filter(vec![or(&[is("slug", Bson::Null), is("invitation_id", "")])])
+        .get_all::<EventDTO>(db)
+        .await?;
+
+    let mut total = 0;
+    for event in events {
+        let mut values = doc! {};
+        if event.slug.is_none() {
+            values.insert(
+                "slug",
+                generate_event_slug(db, &event.event_name, &Vec::new()).await,
+            );
+        }
+        if event.invitation_id.is_empty() {
+            values.insert(
+                "invitation_id",
+                generate_invitation_code(db, &Vec::new()).await,
+            );
+        }
+        total += DB::update(COLLECTION_EVENTS)
+            .filter(vec![is("_id", event.id)])
+            .set(values)
+            .execute(db)
+            .await?;
+    }
+    Ok(total)
+}
@@ -3,9 +3,15 @@ use bson::oid::ObjectId;
 use serde::{Deserialize, Serialize};
 use validator::Validate;
 
+use crate::common::utils::{
+    validate_event_status, validate_event_visibility, validate_rsvp, validate_timezone,
+};
+
 #[derive(Serialize, Deserialize, Validate)]
 pub struct CreateNewEventRequest {
     pub title: String,
+    //start from a saved template instead of the default config
+    pub template_id: Option<String>,
 }
 
 
@@ -14,8 +20,12 @@ pub struct UpdateEventRequest {
     pub theme_id: Option<ObjectId>,
     pub event_name: Option<String>,
     pub event_description: Option<String>,
-    pub status: Option<String>,
     pub datetime: Option<String>,
+    pub end_datetime: Option<String>,
+    #[validate(custom(function = "validate_timezone"))]
+    pub timezone: Option<String>,
+    #[validate(length(min = 1, max = 50))]
+    pub category: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Validate)]
@@ -32,6 +42,7 @@ pub struct UpdateEventConfigRequest {
     pub show_guest_count: Option<bool>,
     pub event_password: Option<String>,
     pub allow_participant_album: Option<bool>,
+    #[validate(custom(function = "validate_event_visibility"))]
     pub visibility: Option<String>,
 }
 
@@ -42,8 +53,23 @@ pub struct UpdateEventLocationRequest {
     pub venue_name:Option<String>,
     pub venue_address:Option<String>,
     pub venue_detail:Option<String>,
-    pub lat:Option<i64>,
-    pub lng:Option<i64>
+    #[validate(range(min = -90.0, max = 90.0))]
+    pub lat:Option<f64>,
+    #[validate(range(min = -180.0, max = 180.0))]
+    pub lng:Option<f64>
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Validate)]
+pub struct GetNearbyEventRequest {
+    #[validate(range(min = -90.0, max = 90.0))]
+    pub lat: f64,
+    #[validate(range(min = -180.0, max = 180.0))]
+    pub lng: f64,
+    //meters
+    #[validate(range(min = 1.0, max = 50000.0))]
+    pub radius: Option<f64>,
+    pub page: Option<i64>,
+    pub size: Option<i64>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone,Validate)]
@@ -51,4 +77,47 @@ pub struct UpdateEventHostRequest {
     pub event_id:String,
     pub user_id:String,
     pub role:String,
-}
\ No newline at end of file
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Validate)]
+pub struct UpdateEventRsvpRequest {
+    pub event_id: String,
+    #[validate(custom(function = "validate_rsvp"))]
+    pub rsvp: String,
+    //join through a shared invitation link or event code
+    pub invitation_code: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GetEventGuestRequest {
+    pub page: Option<i64>,
+    pub size: Option<i64>,
+    pub rsvp: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Validate)]
+pub struct UpdateEventStatusRequest {
+    #[validate(length(min = 1))]
+    pub status: String,
+}
+
+//how the viewer reached the event, unlisted and private events check this
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct EventAccessRequest {
+    pub invitation_code: Option<String>,
+    pub slug: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Validate)]
+pub struct SearchEventRequest {
+    pub q: Option<String>,
+    //rfc3339 or yyyy-mm-dd read in viewer timezone
+    pub from: Option<String>,
+    pub to: Option<String>,
+    #[validate(custom(function = "validate_event_status"))]
+    pub status: Option<String>,
+    #[validate(custom(function = "validate_event_visibility"))]
+    pub visibility: Option<String>,
+    pub page: Option<i64>,
+    pub size: Option<i64>,
+}
@@ -0,0 +1,279 @@
+use crate::common::api_response::PagingResponse;
+use crate::common::constant::{
+    COLLECTION_EVENT_BAN, COLLECTION_EVENT_GUEST, COLLECTION_EVENT_INVITATION, EVENT_GUEST_ROLE_CO_HOST,
+    EVENT_GUEST_ROLE_GUEST, EVENT_GUEST_ROLE_HOST, EVENT_STATUS_DRAFT, EVENT_VISIBILITY_PRIVATE,
+    EVENT_VISIBILITY_PUBLIC, EVENT_VISIBILITY_UNLISTED, INVITATION_TYPE_PUBLIC,
+};
+use crate::common::mongo::DB;
+use crate::common::mongo::filter::{FilterGroup, greater, is, is_in, or};
+use crate::dto::event_dto::EventDTO;
+use crate::dto::event_guest_dto::EventGuestDTO;
+use crate::dto::event_invitation_dto::EventInvitationDTO;
+use crate::feature::event::event_model::EventAccessRequest;
+use bson::{Bson, DateTime, doc};
+use bson::oid::ObjectId;
+use mongodb::Client;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventViewerRole {
+    Host,
+    Guest,
+    Anonymous,
+}
+
+pub async fn is_event_host(db: &Client, event: &EventDTO, user_id: ObjectId) -> bool {
+    if event.host_id == Some(user_id) {
+        return true;
+    }
+    DB::get(COLLECTION_EVENT_GUEST)
+        .filter(vec![
+            is("event_id", event.id),
+            is("user_id", user_id),
+            is_in("role", vec![EVENT_GUEST_ROLE_HOST, EVENT_GUEST_ROLE_CO_HOST]),
+        ])
+        .get_one::<EventGuestDTO>(db)
+        .await
+        .is_ok()
+}
+
+//user ids of host and co-host, used to target host only sse
+pub async fn get_event_host_ids(db: &Client, event: &EventDTO) -> Vec<String> {
+    let mut host_ids = DB::get(COLLECTION_EVENT_GUEST)
+        .filter(vec![
+            is("event_id", event.id),
+            is_in("role", vec![EVENT_GUEST_ROLE_HOST, EVENT_GUEST_ROLE_CO_HOST]),
+        ])
+        .get_all::<EventGuestDTO>(db)
+        .await
+        .unwrap_or(Vec::new())
+        .into_iter()
+        .filter_map(|guest| guest.user_id.map(|id| id.to_string()))
+        .collect::<Vec<String>>();
+
+    if let Some(host_id) = event.host_id {
+        if !host_ids.contains(&host_id.to_string()) {
+            host_ids.push(host_id.to_string());
+        }
+    }
+    host_ids
+}
+
+//events the user joined as guest / co-host or received an invitation for
+pub async fn get_participating_event_ids(db: &Client, user_id: ObjectId) -> Vec<ObjectId> {
+    let mut event_ids = DB::get(COLLECTION_EVENT_GUEST)
+        .filter(vec![is("user_id", user_id)])
+        .get_all::<EventGuestDTO>(db)
+        .await
+        .unwrap_or(Vec::new())
+        .into_iter()
+        .filter_map(|guest| guest.event_id)
+        .collect::<Vec<ObjectId>>();
+
+    event_ids.extend(
+        DB::get(COLLECTION_EVENT_INVITATION)
+            .filter(vec![is("user_id", user_id)])
+            .get_all::<EventInvitationDTO>(db)
+            .await
+            .unwrap_or(Vec::new())
+            .into_iter()
+            .filter_map(|invitation| invitation.event_id),
+    );
+    event_ids
+}
+
+//filter for event listing: public non draft events, plus everything the user host or take part in.
+//wrapped in $and so it does not collide with other $or in the same query
+pub async fn visible_event_filter(db: &Client, user_id: Option<ObjectId>) -> FilterGroup {
+    let mut visible = vec![doc! {
+        "config.visibility": EVENT_VISIBILITY_PUBLIC,
+        "status": { "$ne": EVENT_STATUS_DRAFT },
+    }];
+    if let Some(user_id) = user_id {
+        visible.push(doc! { "host_id": user_id });
+        visible.push(doc! { "_id": { "$in": get_participating_event_ids(db, user_id).await } });
+    }
+    doc! { "$and": [{ "$or": visible }] }.into()
+}
+
+//signed in users that are not part of the event are treated as anonymous
+pub async fn get_viewer_role(
+    db: &Client,
+    event: &EventDTO,
+    user_id: Option<ObjectId>,
+) -> EventViewerRole {
+    if let None = user_id {
+        return EventViewerRole::Anonymous;
+    }
+    let user_id = user_id.unwrap();
+
+    if is_event_host(db, event, user_id).await {
+        return EventViewerRole::Host;
+    }
+
+    let find_guest = DB::get(COLLECTION_EVENT_GUEST)
+        .filter(vec![is("event_id", event.id), is("user_id", user_id)])
+        .get_one::<EventGuestDTO>(db)
+        .await;
+    if find_guest.is_ok() {
+        return EventViewerRole::Guest;
+    }
+    EventViewerRole::Anonymous
+}
+
+//single access rule for every event read.
+//host and guest always pass, draft stay hidden from everyone else,
+//public is open, unlisted need an invitation or a valid invitation link code,
+//private need an invitation addressed to the user.
+//error is the i18n key suffix
+pub async fn check_event_access(
+    db: &Client,
+    event: &EventDTO,
+    user_id: Option<ObjectId>,
+    access: &EventAccessRequest,
+) -> Result<EventViewerRole, String> {
+    let role = get_viewer_role(db, event, user_id).await;
+    if role != EventViewerRole::Anonymous {
+        return Ok(role);
+    }
+
+    if event.status == EVENT_STATUS_DRAFT {
+        return Err("event_not_found".to_string());
+    }
+
+    if event.config.visibility == EVENT_VISIBILITY_PUBLIC {
+        return Ok(role);
+    }
+
+    if let Some(user_id) = user_id {
+        let find_invitation = DB::get(COLLECTION_EVENT_INVITATION)
+            .filter(vec![is("event_id", event.id), is("user_id", user_id)])
+            .get_one::<EventInvitationDTO>(db)
+            .await;
+        if find_invitation.is_ok() {
+            return Ok(role);
+        }
+    }
+
+    if event.config.visibility == EVENT_VISIBILITY_UNLISTED {
+        if access.slug.is_some() && access.slug == event.slug {
+            return Ok(role);
+        }
+        if let Some(code) = access.invitation_code.clone() {
+            if is_valid_invitation_code(db, event, &code).await {
+                return Ok(role);
+            }
+        }
+    }
+    Err("access_denied".to_string())
+}
+
+pub async fn is_banned(db: &Client, event_id: Option<ObjectId>, user_id: ObjectId) -> bool {
+    DB::get(COLLECTION_EVENT_BAN)
+        .filter(vec![is("event_id", event_id), is("user_id", user_id)])
+        .count(db)
+        .await
+        .unwrap_or(0)
+        > 0
+}
+
+//event short code or an invitation link that is not expired
+pub async fn is_valid_invitation_code(db: &Client, event: &EventDTO, code: &String) -> bool {
+    if !event.invitation_id.is_empty() && &event.invitation_id == code {
+        return true;
+    }
+    DB::get(COLLECTION_EVENT_INVITATION)
+        .filter(vec![
+            is("event_id", event.id),
+            is("invitation_code", code.clone()),
+            is("revoked_at", Bson::Null),
+            or(&[
+                is("expires_at", Bson::Null),
+                greater("expires_at", DateTime::now()),
+            ]),
+        ])
+        .get_one::<EventInvitationDTO>(db)
+        .await
+        .is_ok()
+}
+
+//self join only go through a public invitation link that still has room,
+//the event short code and personal invitations only grant read access.
+//private event need an invitation addressed to the user
+pub async fn is_valid_join_link(db: &Client, event: &EventDTO, code: &String) -> bool {
+    if event.status == EVENT_STATUS_DRAFT || event.config.visibility == EVENT_VISIBILITY_PRIVATE {
+        return false;
+    }
+    DB::get(COLLECTION_EVENT_INVITATION)
+        .filter(vec![
+            is("event_id", event.id),
+            is("invitation_code", code.clone()),
+            is("invitation_type", INVITATION_TYPE_PUBLIC),
+            is("revoked_at", Bson::Null),
+            //filter doc are merged by key, both or group must share one $and
+            doc! { "$and": [
+                { "$or": [
+                    { "expires_at": Bson::Null },
+                    { "expires_at": { "$gt": DateTime::now() } },
+                ] },
+                { "$or": [
+                    { "max_uses": Bson::Null },
+                    { "$expr": { "$lt": ["$redemption_count", "$max_uses"] } },
+                ] },
+            ] }
+            .into(),
+        ])
+        .get_one::<EventInvitationDTO>(db)
+        .await
+        .is_ok()
+}
+
+pub fn is_password_protected(event: &EventDTO) -> bool {
+    event
+        .config
+        .event_password
+        .as_ref()
+        .is_some_and(|password| !password.is_empty())
+}
+
+//filter for listing that anyone can call, password protected events stay behind their link
+pub fn unprotected_event_filter() -> FilterGroup {
+    is_in("config.event_password", vec![Bson::Null, Bson::from("")])
+}
+
+//listing entry of a password protected event the caller is not part of, only the card is left
+pub fn mask_protected_event(mut event: EventDTO) -> EventDTO {
+    event.event_description = String::new();
+    event.venue_location = None;
+    event.host = None;
+    event.allowed_transitions = Vec::new();
+    event
+}
+
+//apply show_guest_name & show_guest_count to a guest list depending on who is looking at it
+pub fn shape_guest_list(
+    role: &EventViewerRole,
+    event: &EventDTO,
+    mut guests: PagingResponse<EventGuestDTO>,
+) -> PagingResponse<EventGuestDTO> {
+    match role {
+        EventViewerRole::Host => guests,
+        EventViewerRole::Guest => {
+            for guest in guests.items.iter_mut() {
+                guest.event = None;
+                if !event.config.show_guest_name && guest.role == EVENT_GUEST_ROLE_GUEST {
+                    guest.user_id = None;
+                    guest.user = None;
+                }
+            }
+            guests
+        }
+        EventViewerRole::Anonymous => {
+            guests.items.clear();
+            if !event.config.show_guest_count {
+                guests.total_items = 0;
+                guests.total_pages = 0;
+            }
+            guests
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use crate::common::constant::{DEFAULT_EVENT_TIMEZONE, EVENT_FILTER_PAST, EVENT_FILTER_UPCOMING};
+use crate::common::lang::Lang;
+use crate::common::mongo::filter::{FilterGroup, greater_than_equal, lower, or};
+use crate::dto::event_dto::EventDTO;
+use crate::dto::event_schedule_dto::EventScheduleDTO;
+use bson::{Bson, DateTime, doc};
+use chrono::Locale;
+use chrono_tz::Tz;
+use std::str::FromStr;
+
+fn to_timezone(timezone: Option<&String>) -> Tz {
+    timezone
+        .and_then(|timezone| Tz::from_str(timezone).ok())
+        .unwrap_or(Tz::from_str(DEFAULT_EVENT_TIMEZONE).unwrap())
+}
+
+fn format_in(datetime: Option<DateTime>, tz: &Tz) -> Option<String> {
+    datetime.map(|datetime| datetime.to_chrono().with_timezone(tz).to_rfc3339())
+}
+
+//show start & end in the event timezone and in the viewer timezone (X-Timezone), viewer without timezone see the event timezone
+pub fn with_schedule(mut event: EventDTO, lang: &Lang) -> EventDTO {
+    let event_tz = to_timezone(event.timezone.as_ref());
+    let viewer_tz = lang
+        .timezone
+        .as_ref()
+        .and_then(|timezone| Tz::from_str(timezone).ok())
+        .unwrap_or(event_tz);
+    let locale = Locale::from_str(&lang.get().replace("-", "_")).unwrap_or(Locale::en_US);
+
+    let duration_minutes = match (event.datetime, event.end_datetime) {
+        (Some(start), Some(end)) => {
+            Some((end.timestamp_millis() - start.timestamp_millis()) / 60_000)
+        }
+        _ => None,
+    };
+
+    event.schedule = Some(EventScheduleDTO {
+        timezone: event_tz.name().to_string(),
+        start_local: format_in(event.datetime, &event_tz),
+        end_local: format_in(event.end_datetime, &event_tz),
+        viewer_timezone: viewer_tz.name().to_string(),
+        start_viewer: format_in(event.datetime, &viewer_tz),
+        end_viewer: format_in(event.end_datetime, &viewer_tz),
+        display: event.datetime.map(|datetime| {
+            datetime
+                .to_chrono()
+                .with_timezone(&viewer_tz)
+                .format_localized("%A, %e %B %Y %H:%M %Z", locale)
+                .to_string()
+        }),
+        duration_minutes,
+    });
+    event
+}
+
+//datetime are stored as utc instant so comparing with now is correct for every timezone,
+//event without end is considered running until its start
+pub fn schedule_filter(filter: &str) -> Option<(FilterGroup, i32)> {
+    let now = DateTime::now();
+    match filter {
+        EVENT_FILTER_UPCOMING => Some((
+            or(&[
+                greater_than_equal("end_datetime", now),
+                doc! {
+                    "end_datetime": Bson::Null,
+                    "datetime": { "$gte": now }
+                }
+                .into(),
+            ]),
+            1,
+        )),
+        EVENT_FILTER_PAST => Some((
+            or(&[
+                lower("end_datetime", now),
+                doc! {
+                    "end_datetime": Bson::Null,
+                    "datetime": { "$lt": now }
+                }
+                .into(),
+            ]),
+            -1,
+        )),
+        _ => None,
+    }
+}
@@ -1,23 +1,47 @@
 use crate::common::constant::{
-    BUCKET_EVENT, BUCKET_THREAD, COLLECTION_EVENT_GUEST, EVENT_GUEST_ROLE_CO_HOST,
-    EVENT_GUEST_ROLE_HOST, EVENT_STATUS_DRAFT, SSE_EVENT_UPDATE_EVENT_CONFIG,
-    SSE_EVENT_UPDATE_EVENT_DATA, SSE_EVENT_UPDATE_EVENT_HOST, SSE_EVENT_UPDATE_EVENT_IMAGE,
-    SSE_EVENT_UPDATE_EVENT_VENUE,
+    BUCKET_EVENT, BUCKET_THREAD, COLLECTION_EVENT_ACTIVITY, DEFAULT_EVENT_TIMEZONE, COLLECTION_EVENT_GUEST,
+    COLLECTION_EVENT_INVITATION, COLLECTION_EVENT_QUESTIONNAIRE, EVENT_ACTIVITY_GUEST_ADDED, EVENT_ACTIVITY_IMAGE_UPDATED,
+    EVENT_ACTIVITY_RSVP, EVENT_ACTIVITY_VENUE_CHANGED, EVENT_GUEST_ROLE_CO_HOST,
+    EVENT_ACTIVITY_STATUS_CHANGED, EVENT_GUEST_ROLE_GUEST, EVENT_GUEST_ROLE_HOST,
+    EVENT_GUEST_RSVP_GOING, EVENT_STATUS_CANCELLED, EVENT_STATUS_COMPLETED, EVENT_STATUS_DRAFT,
+    EVENT_STATUS_PUBLISHED, EVENT_VISIBILITY_PUBLIC, NEARBY_DEFAULT_RADIUS, NEARBY_MAX_RADIUS,
+    NOTIFICATION_TYPE_EVENT_CANCELLED, SSE_EVENT_UPDATE_EVENT_STATUS,
+    SSE_EVENT_UPDATE_EVENT_CONFIG, SSE_EVENT_UPDATE_EVENT_DATA, SSE_EVENT_UPDATE_EVENT_HOST,
+    SSE_EVENT_UPDATE_EVENT_IMAGE, SSE_EVENT_UPDATE_EVENT_RSVP, SSE_EVENT_UPDATE_EVENT_VENUE,
 };
 use crate::common::minio::MinIO;
 use crate::common::mongo::filter::{equal, is_in};
 use crate::common::multipart_file::SingleFileExtractor;
 use crate::common::sse::sse_builder::{SseBuilder, SseTarget};
-use crate::common::utils::create_object_id_option;
+use crate::common::utils::{create_object_id_option, parse_datetime_in_timezone};
 use crate::dto::event_guest_dto::EventGuestDTO;
+use crate::dto::geo_point_dto::GeoPointDTO;
+use crate::dto::event_invitation_dto::EventInvitationDTO;
 use crate::dto::mutual_dto::MutualDTO;
 use crate::dto::venue_location_dto::VenueLocationDTO;
+use crate::entity::event_activity::EventActivity;
 use crate::entity::event_guest::EventGuest;
 use crate::entity::event_image::EventImage;
 use crate::feature::event::event_model::{
     UpdateEventConfigRequest, UpdateEventHostRequest, UpdateEventLocationRequest,
-    UpdateEventRequest,
+    UpdateEventRequest, UpdateEventRsvpRequest, GetEventGuestRequest, UpdateEventStatusRequest,
+    GetNearbyEventRequest, SearchEventRequest, EventAccessRequest,
 };
+use crate::feature::mutual::get_blocked_user_ids;
+use crate::feature::event::event_lifecycle::{
+    can_transition, check_transition_guard, notify_event_guests, run_post_event_flows,
+    with_lifecycle,
+};
+use crate::feature::event::event_link::{generate_event_slug, generate_invitation_code};
+use crate::feature::event::event_schedule::{schedule_filter, with_schedule};
+use crate::feature::invitation::{record_invitation_open, redeem_invitation_link};
+use crate::feature::template::{copy_questionnaire, find_user_template};
+use crate::feature::event::event_policy::{
+    check_event_access, get_participating_event_ids, is_banned, is_event_host, is_password_protected,
+    is_valid_join_link, mask_protected_event, shape_guest_list, unprotected_event_filter,
+    visible_event_filter,
+};
+use std::collections::HashMap;
 use crate::{
     common::{
         api_response::{ApiResponse, PaginationRequest, PagingResponse},
@@ -36,14 +60,21 @@ use crate::{
     i18n,
 };
 use axum::extract::{Path, Query, State};
-use bson::{DateTime, doc, oid::ObjectId};
+use axum::response::{IntoResponse, Redirect, Response};
+use crate::common::mongo::filter::FilterGroup;
+use mongodb::Client;
+use bson::{DateTime, Document, doc, oid::ObjectId};
 use event_model::CreateNewEventRequest;
 use log::info;
-use serde_json::{Error, from_value};
+use serde_json::{Error, from_value, json};
 use validator::Validate;
 use crate::common::mongo::lookup::raw;
 
+pub mod event_lifecycle;
+pub mod event_link;
 pub mod event_model;
+pub mod event_policy;
+pub mod event_schedule;
 
 pub async fn get_hosted_events(
     state: State<AppState>,
@@ -66,6 +97,10 @@ pub async fn get_hosted_events(
         data = data.text(q);
     }
 
+    if let Some((schedule, order)) = query.filter.clone().and_then(|filter| schedule_filter(&filter)) {
+        data = data.filter(vec![schedule]).sort(vec![("datetime", order)]);
+    }
+
     if let Some((col, order)) = query.clone().get_sorted() {
         data = data.sort(vec![(&col, order)]);
     }
@@ -84,8 +119,15 @@ pub async fn get_hosted_events(
         return ApiResponse::failed(&i18n.translate("get_hosted_event.user_id_not_exist"));
     }
 
+    let mut find_hosted_events = find_hosted_events.unwrap();
+    find_hosted_events.items = find_hosted_events
+        .items
+        .into_iter()
+        .map(|event| present_event(event, &lang))
+        .collect::<Vec<EventDTO>>();
+
     ApiResponse::ok(
-        find_hosted_events.unwrap(),
+        find_hosted_events,
         &i18n.translate("get_hosted_events.ok"),
     )
 }
@@ -112,6 +154,10 @@ pub async fn get_invited_event(
         data = data.text(q);
     }
 
+    if let Some((schedule, order)) = query.filter.clone().and_then(|filter| schedule_filter(&filter)) {
+        data = data.filter(vec![schedule]).sort(vec![("datetime", order)]);
+    }
+
     if let Some((col, order)) = query.clone().get_sorted() {
         data = data.sort(vec![(&col, order)]);
     }
@@ -134,12 +180,166 @@ pub async fn get_invited_event(
         return ApiResponse::failed(&i18n.translate("get_invited_event.user_id_not_exist"));
     }
 
+    let mut find_hosted_events = find_hosted_events.unwrap();
+    find_hosted_events.items = find_hosted_events
+        .items
+        .into_iter()
+        .map(|event| present_event(event, &lang))
+        .collect::<Vec<EventDTO>>();
+
     ApiResponse::ok(
-        find_hosted_events.unwrap(),
+        find_hosted_events,
         &i18n.translate("get_invited_event.ok"),
     )
 }
 
+pub async fn get_nearby_events(
+    state: State<AppState>,
+    lang: Lang,
+    Query(query): Query<GetNearbyEventRequest>,
+) -> ApiResponse<PagingResponse<EventDTO>> {
+    info!(target:"get_nearby_events","Starting...");
+    let i18n = i18n!("event", lang);
+    if let Err(err) = query.validate() {
+        info!(target:"get_nearby_events","{:?}", err);
+        return ApiResponse::error_validation(
+            err,
+            &i18n.translate("get_nearby_events.validation_error"),
+        );
+    }
+
+    let radius = query
+        .radius
+        .unwrap_or(NEARBY_DEFAULT_RADIUS)
+        .min(NEARBY_MAX_RADIUS);
+
+    let find_nearby_events = DB::get(COLLECTION_EVENTS)
+        .near("venue_location.point", query.lat, query.lng, radius, "distance")
+        .filter(vec![
+            is("status", EVENT_STATUS_PUBLISHED),
+            is("config.visibility", EVENT_VISIBILITY_PUBLIC),
+            unprotected_event_filter(),
+        ])
+        .lookup(&[
+            one(COLLECTION_USERS, "host_id", "_id", "host"),
+            one(COLLECTION_EVENT_IMAGES, "image_id", "_id", "image"),
+            one(COLLECTION_EVENT_THEMES, "theme_id", "_id", "theme"),
+        ])
+        .get_per_page::<EventDTO>(query.page.unwrap_or(0), query.size.unwrap_or(50), &state.db)
+        .await;
+    if let Err(why) = find_nearby_events {
+        info!(target:"get_nearby_events","cannot find events {}",why);
+        return ApiResponse::failed(&i18n.translate("get_nearby_events.failed"));
+    }
+
+    let mut find_nearby_events = find_nearby_events.unwrap();
+    find_nearby_events.items = find_nearby_events
+        .items
+        .into_iter()
+        .map(|event| present_event_detail(event, &lang))
+        .collect::<Vec<EventDTO>>();
+
+    info!(target:"get_nearby_events","finish");
+    ApiResponse::ok(
+        find_nearby_events,
+        &i18n.translate("get_nearby_events.ok"),
+    )
+}
+
+//caller only see public events, or the one they host or take part in
+pub async fn search_events(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: Option<AuthContext>,
+    Query(query): Query<SearchEventRequest>,
+) -> ApiResponse<PagingResponse<EventDTO>> {
+    info!(target:"search_events","Starting...");
+    let i18n = i18n!("event", lang);
+    if let Err(err) = query.validate() {
+        info!(target:"search_events","{:?}", err);
+        return ApiResponse::error_validation(
+            err,
+            &i18n.translate("search_events.validation_error"),
+        );
+    }
+    let current_user_id = auth_context.and_then(|auth_context| auth_context.get_user_id());
+    let timezone = lang
+        .timezone
+        .clone()
+        .unwrap_or(DEFAULT_EVENT_TIMEZONE.to_string());
+
+    let mut data = DB::get(COLLECTION_EVENTS);
+
+    let q = query.q.clone().unwrap_or(String::new());
+    if !q.trim().is_empty() {
+        data = data.text(q.trim().to_string()).sort_by_text_score();
+    }
+
+    let mut range = Document::new();
+    if let Some(from) = query.from.clone() {
+        let from = parse_datetime_in_timezone(&from, &timezone);
+        if let Err(why) = from {
+            info!(target:"search_events","invalid from {}",why);
+            return ApiResponse::failed(&i18n.translate("search_events.from_invalid"));
+        }
+        range.insert("$gte", from.unwrap());
+    }
+    if let Some(to) = query.to.clone() {
+        let to = parse_datetime_in_timezone(&to, &timezone);
+        if let Err(why) = to {
+            info!(target:"search_events","invalid to {}",why);
+            return ApiResponse::failed(&i18n.translate("search_events.to_invalid"));
+        }
+        range.insert("$lte", to.unwrap());
+    }
+    if !range.is_empty() {
+        data = data.filter(vec![doc! { "datetime": range }]);
+    }
+
+    if let Some(status) = query.status.clone() {
+        data = data.filter(vec![is("status", status)]);
+    }
+    if let Some(visibility) = query.visibility.clone() {
+        data = data.filter(vec![is("config.visibility", visibility)]);
+    }
+
+    let find_events = data
+        .filter(vec![visible_event_filter(&state.db, current_user_id).await])
+        .lookup(&[
+            one(COLLECTION_USERS, "host_id", "_id", "host"),
+            one(COLLECTION_EVENT_IMAGES, "image_id", "_id", "image"),
+            one(COLLECTION_EVENT_THEMES, "theme_id", "_id", "theme"),
+        ])
+        .sort(vec![("datetime", 1)])
+        .get_per_page::<EventDTO>(query.page.unwrap_or(0), query.size.unwrap_or(50), &state.db)
+        .await;
+    if let Err(why) = find_events {
+        info!(target:"search_events","cannot find events {}",why);
+        return ApiResponse::failed(&i18n.translate("search_events.failed"));
+    }
+
+    let participating_ids = match current_user_id {
+        Some(user_id) => get_participating_event_ids(&state.db, user_id).await,
+        None => Vec::new(),
+    };
+    let mut find_events = find_events.unwrap();
+    find_events.items = find_events
+        .items
+        .into_iter()
+        .map(|event| {
+            let is_member = current_user_id.is_some() && event.host_id == current_user_id
+                || event.id.is_some_and(|id| participating_ids.contains(&id));
+            if is_password_protected(&event) && !is_member {
+                return present_event_detail(mask_protected_event(event), &lang);
+            }
+            present_event_detail(event, &lang)
+        })
+        .collect::<Vec<EventDTO>>();
+
+    info!(target:"search_events","finish");
+    ApiResponse::ok(find_events, &i18n.translate("search_events.ok"))
+}
+
 pub async fn create_new_event(
     state: State<AppState>,
     lang: Lang,
@@ -153,6 +353,17 @@ pub async fn create_new_event(
         info!(target:"create_new_event","user id not found");
         return ApiResponse::failed(&i18n.translate("create_new_event.user_id_not_exist"));
     }
+    let current_user_id = auth_context.get_user_id().unwrap();
+
+    let mut template = None;
+    if let Some(template_id) = body.template_id.clone() {
+        let find_template = find_user_template(&state.db, &template_id, current_user_id).await;
+        if let Err(why) = find_template {
+            info!(target:"create_new_event","cannot use template {}",why);
+            return ApiResponse::failed(&i18n.translate(&format!("create_new_event.{}", why)));
+        }
+        template = Some(find_template.unwrap());
+    }
 
     let find_default_config = DB::get(COLLECTION_CONFIGURATION)
         .filter(vec![
@@ -175,22 +386,43 @@ pub async fn create_new_event(
     let default_config = default_config.unwrap();
 
     let create_event_id = ObjectId::new();
-    let event = Event {
+    let slug = generate_event_slug(&state.db, &body.title, &Vec::new()).await;
+    let invitation_id = generate_invitation_code(&state.db, &Vec::new()).await;
+    let mut event = Event {
         id: Some(create_event_id),
-        host_id: auth_context.get_user_id(),
+        host_id: Some(current_user_id),
         image_id: None,
         theme_id: None,
-        invitation_id: "".to_string(),
+        series_id: None,
+        invitation_id,
+        slug: Some(slug),
+        previous_slugs: Vec::new(),
         event_name: body.title.clone(),
         event_description: "".to_string(),
         datetime: None,
+        end_datetime: None,
+        timezone: None,
+        category: None,
         venue_location: None,
         status: EVENT_STATUS_DRAFT.to_string(),
         config: default_config,
+        revision: 0,
         created_at: DateTime::now(),
         updated_at: DateTime::now(),
     };
 
+    let mut questionnaire = Vec::new();
+    if let Some(template) = template {
+        event.image_id = copy_event_image(&state.db, template.image_id, Some(create_event_id)).await;
+        event.theme_id = template.theme_id;
+        event.event_description = template.event_description;
+        event.timezone = template.timezone;
+        event.category = template.category;
+        event.venue_location = template.venue_location;
+        event.config = template.config;
+        questionnaire = copy_questionnaire(template.questionnaire, Some(create_event_id));
+    }
+
     let session = state.db.start_session().await;
     if let Err(why) = session {
         info!(target:"create_new_event","{:?}",why);
@@ -209,6 +441,17 @@ pub async fn create_new_event(
         return ApiResponse::failed(&i18n.translate("create_new_event.failed"));
     }
 
+    if !questionnaire.is_empty() {
+        let save_questionnaire = DB::insert(COLLECTION_EVENT_QUESTIONNAIRE)
+            .many_with_session(questionnaire, &state.db, &mut session)
+            .await;
+        if let Err(why) = save_questionnaire {
+            info!(target:"create_new_event","{:?}",why);
+            let _ = session.abort_transaction().await;
+            return ApiResponse::failed(&i18n.translate("create_new_event.failed"));
+        }
+    }
+
     let _commit = session.commit_transaction().await;
 
     let find_hosted_events = DB::get(COLLECTION_EVENTS)
@@ -226,7 +469,7 @@ pub async fn create_new_event(
     }
     info!(target:"create_new_event","Finish");
     ApiResponse::ok(
-        find_hosted_events.unwrap(),
+        present_event(find_hosted_events.unwrap(), &lang),
         &i18n.translate("create_new_event.user_id_not_exist"),
     )
 }
@@ -361,6 +604,22 @@ pub async fn upload_event_image(
         event.image = Some(image);
     }
 
+    let activity = EventActivity::create(
+        event.id,
+        auth_context.get_user_id(),
+        event.image.clone().map_or_else(|| None, |image| image.id),
+        EVENT_ACTIVITY_IMAGE_UPDATED,
+        None,
+    );
+    let save_activity = DB::insert(COLLECTION_EVENT_ACTIVITY)
+        .one_with_session(activity, &state.db, &mut session)
+        .await;
+    if let Err(why) = save_activity {
+        info!(target:"upload_event_image","{:?}", why);
+        let _abort = session.abort_transaction().await;
+        return ApiResponse::failed(&i18n.translate("upload_event_image.failed_save_activity"));
+    }
+
     let _commit = session.commit_transaction().await;
     info!(target:"upload_event_image","sending sse");
     let find_subscriber = state
@@ -378,7 +637,7 @@ pub async fn upload_event_image(
     );
     let _send = state.sse.send(sse);
     info!(target:"upload_event_image","finish");
-    ApiResponse::ok(event, &i18n.translate("upload_event_image.ok"))
+    ApiResponse::ok(present_event(event, &lang), &i18n.translate("upload_event_image.ok"))
 }
 
 pub async fn update_event_data(
@@ -421,6 +680,38 @@ pub async fn update_event_data(
         return ApiResponse::failed(&i18n.translate("upload_event_image.event_not_found"));
     }
     let mut event = find_events.unwrap();
+
+    //local datetime without offset is read in the event timezone
+    let timezone = body
+        .timezone
+        .clone()
+        .or(event.timezone.clone())
+        .unwrap_or(DEFAULT_EVENT_TIMEZONE.to_string());
+    let mut start = event.datetime;
+    let mut end = event.end_datetime;
+    if let Some(datetime) = body.datetime.clone() {
+        let parse = parse_datetime_in_timezone(&datetime, &timezone);
+        if let Err(why) = parse {
+            info!(target:"update_event_data","{}",why);
+            return ApiResponse::failed(&i18n.translate("update_event_data.datetime_invalid"));
+        }
+        start = Some(parse.unwrap());
+    }
+    if let Some(end_datetime) = body.end_datetime.clone() {
+        let parse = parse_datetime_in_timezone(&end_datetime, &timezone);
+        if let Err(why) = parse {
+            info!(target:"update_event_data","{}",why);
+            return ApiResponse::failed(&i18n.translate("update_event_data.end_datetime_invalid"));
+        }
+        end = Some(parse.unwrap());
+    }
+    if let (Some(start), Some(end)) = (start, end) {
+        if end <= start {
+            info!(target:"update_event_data","end must be after start");
+            return ApiResponse::failed(&i18n.translate("update_event_data.end_before_start"));
+        }
+    }
+
     let session = state.db.start_session().await;
     if let Err(why) = session {
         info!(target:"upload_event_image","{:?}",why);
@@ -433,6 +724,17 @@ pub async fn update_event_data(
         DB::update(COLLECTION_EVENTS).filter(vec![is("_id", create_event_id.unwrap())]);
 
     if let Some(event_name) = body.event_name {
+        //new slug on rename, the old one keep redirecting
+        if event_name != event.event_name {
+            let slug = generate_event_slug(&state.db, &event_name, &Vec::new()).await;
+            if let Some(previous) = event.slug.clone() {
+                event.previous_slugs.push(previous);
+            }
+            event.slug = Some(slug.clone());
+            update = update
+                .set_value("slug", slug)
+                .set_value("previous_slugs", event.previous_slugs.clone());
+        }
         event.event_name = event_name.clone();
         update = update.set_value("event_name", event_name);
     }
@@ -440,14 +742,26 @@ pub async fn update_event_data(
         event.event_description = event_description.clone();
         update = update.set_value("event_description", event_description);
     }
-    if let Some(status) = body.status {
-        event.status = status.clone();
-        update = update.set_value("status", status);
+    if let Some(category) = body.category {
+        let category = category.trim().to_lowercase();
+        event.category = Some(category.clone());
+        update = update.set_value("category", category);
     }
     if let Some(theme_id) = body.theme_id {
         event.theme_id = Some(theme_id.clone());
         update = update.set_value("theme_id", theme_id);
     }
+    if body.datetime.is_some() || body.end_datetime.is_some() || body.timezone.is_some() {
+        event.datetime = start;
+        event.end_datetime = end;
+        event.timezone = Some(timezone.clone());
+        event.revision += 1;
+        update = update
+            .set_value("datetime", start)
+            .set_value("end_datetime", end)
+            .set_value("timezone", timezone)
+            .inc_value("revision", 1);
+    }
 
     let update_event = update.execute_with_session(&state.db, &mut session).await;
     if let Err(why) = update_event {
@@ -473,7 +787,7 @@ pub async fn update_event_data(
     );
     let _send = state.sse.send(sse);
     info!(target:"upload_event_image","finish");
-    ApiResponse::ok(event, &i18n.translate("upload_event_image.ok"))
+    ApiResponse::ok(present_event(event, &lang), &i18n.translate("upload_event_image.ok"))
 }
 
 pub async fn update_event_config(
@@ -529,63 +843,63 @@ pub async fn update_event_config(
 
     if let Some(cost) = body.cost {
         event.config.cost = cost;
-        update = update.set_value("cost", cost);
+        update = update.set_value("config.cost", cost);
     }
     if let Some(cost_type) = body.cost_type {
         event.config.cost_type = cost_type.clone();
-        update = update.set_value("cost_type", cost_type);
+        update = update.set_value("config.cost_type", cost_type);
     }
     if let Some(cost_currency) = body.cost_currency {
         event.config.cost_currency = cost_currency.clone();
-        update = update.set_value("cost_currency", cost_currency);
+        update = update.set_value("config.cost_currency", cost_currency);
     }
     if let Some(cost_suggested_amount) = body.cost_suggested_amount {
         event.config.cost_suggested_amount = cost_suggested_amount.clone();
-        update = update.set_value("cost_suggested_amount", cost_suggested_amount);
+        update = update.set_value("config.cost_suggested_amount", cost_suggested_amount);
     }
     if let Some(approval_required) = body.approval_required {
         event.config.approval_required = approval_required.clone();
-        update = update.set_value("approval_required", approval_required);
+        update = update.set_value("config.approval_required", approval_required);
     }
     if let Some(auto_reminder) = body.auto_reminder {
         event.config.auto_reminder = auto_reminder.clone();
-        update = update.set_value("auto_reminder", auto_reminder);
+        update = update.set_value("config.auto_reminder", auto_reminder);
     }
     if let Some(show_guest_name) = body.show_guest_name {
         event.config.show_guest_name = show_guest_name.clone();
-        update = update.set_value("show_guest_name", show_guest_name);
+        update = update.set_value("config.show_guest_name", show_guest_name);
     }
     if let Some(show_guest_count) = body.show_guest_count {
         event.config.show_guest_count = show_guest_count.clone();
-        update = update.set_value("show_guest_count", show_guest_count);
+        update = update.set_value("config.show_guest_count", show_guest_count);
     }
     if let Some(show_timestamp_activity) = body.show_timestamp_activity {
         event.config.show_timestamp_activity = show_timestamp_activity.clone();
-        update = update.set_value("show_timestamp_activity", show_timestamp_activity);
+        update = update.set_value("config.show_timestamp_activity", show_timestamp_activity);
     }
     if let Some(allow_participant_album) = body.allow_participant_album {
         event.config.allow_participant_album = allow_participant_album.clone();
-        update = update.set_value("allow_participant_album", allow_participant_album);
+        update = update.set_value("config.allow_participant_album", allow_participant_album);
     }
     if let Some(max_capacity) = body.max_capacity {
         event.config.max_capacity = max_capacity.clone();
-        update = update.set_value("max_capacity", max_capacity);
+        update = update.set_value("config.max_capacity", max_capacity);
     }
     if let Some(allow_participant_album) = body.allow_participant_album {
         event.config.allow_participant_album = allow_participant_album.clone();
-        update = update.set_value("allow_participant_album", allow_participant_album);
+        update = update.set_value("config.allow_participant_album", allow_participant_album);
     }
     if let Some(visibility) = body.visibility {
         event.config.visibility = visibility.clone();
-        update = update.set_value("visibility", visibility);
+        update = update.set_value("config.visibility", visibility);
     }
 
     if let Some(event_password) = body.event_password {
         if !event_password.is_empty() {
             let create_password = bcrypt::hash(&event_password, bcrypt::DEFAULT_COST);
             if let Ok(hash) = create_password {
-                event.config.event_password = Some(hash);
-                update = update.set_value("event_password", event_password);
+                event.config.event_password = Some(hash.clone());
+                update = update.set_value("config.event_password", hash);
             }
         }
     }
@@ -614,7 +928,7 @@ pub async fn update_event_config(
     );
     let _send = state.sse.send(sse);
     info!(target:"update_event_config","finish");
-    ApiResponse::ok(event.clone(), &i18n.translate("update_event_config.failed"))
+    ApiResponse::ok(present_event(event, &lang), &i18n.translate("update_event_config.failed"))
 }
 
 pub async fn update_event_venue(
@@ -626,21 +940,21 @@ pub async fn update_event_venue(
 ) -> ApiResponse<EventDTO> {
     let i18n = i18n!("event", lang);
     if let Err(err) = body.validate() {
-        info!(target:"update_event_config","{:?}", err);
+        info!(target:"update_event_venue","{:?}", err);
         return ApiResponse::error_validation(
             err,
-            &i18n.translate("update_event_config.validation_error"),
+            &i18n.translate("update_event_venue.validation_error"),
         );
     }
 
     if let None = auth_context.get_user_id() {
-        info!(target:"update_event_config","user id not found");
-        return ApiResponse::failed(&i18n.translate("update_event_config.user_id_not_found"));
+        info!(target:"update_event_venue","user id not found");
+        return ApiResponse::failed(&i18n.translate("update_event_venue.user_id_not_found"));
     }
     let create_event_id = create_object_id_option(&event_id);
     if let None = create_event_id {
-        info!(target:"update_event_config","event id invalid");
-        return ApiResponse::failed(&i18n.translate("update_event_config.event_id_invalid"));
+        info!(target:"update_event_venue","event id invalid");
+        return ApiResponse::failed(&i18n.translate("update_event_venue.event_id_invalid"));
     }
 
     let find_events = DB::get(COLLECTION_EVENTS)
@@ -653,8 +967,8 @@ pub async fn update_event_venue(
         .get_one::<EventDTO>(&state.db)
         .await;
     if let Err(why) = find_events {
-        info!(target:"update_event_config","cannot find events {}",why);
-        return ApiResponse::failed(&i18n.translate("update_event_config.event_not_found"));
+        info!(target:"update_event_venue","cannot find events {}",why);
+        return ApiResponse::failed(&i18n.translate("update_event_venue.event_not_found"));
     }
     let mut event = find_events.unwrap();
     let mut location = event
@@ -668,11 +982,12 @@ pub async fn update_event_venue(
             venue_detail: None,
             lat: None,
             lng: None,
+            point: None,
         });
     let session = state.db.start_session().await;
     if let Err(why) = session {
-        info!(target:"update_event_config","{:?}",why);
-        return ApiResponse::failed(&i18n.translate("update_event_config.trx_failed"));
+        info!(target:"update_event_venue","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("update_event_venue.trx_failed"));
     }
     let mut session = session.unwrap();
     let _ = session.start_transaction().await;
@@ -695,6 +1010,11 @@ pub async fn update_event_venue(
     if let Some(lng) = body.lng {
         location.lng = Some(lng.clone());
     }
+    //point is what the 2dsphere index read, lat lng kept for client
+    location.point = match (location.lat, location.lng) {
+        (Some(lat), Some(lng)) => Some(GeoPointDTO::new(lat, lng)),
+        _ => None,
+    };
 
     if let Some(map_id) = body.map_id {
         location.map_id = Some(map_id.clone());
@@ -710,17 +1030,38 @@ pub async fn update_event_venue(
         .set(doc! {
             "venue_location":bson
         })
+        .inc_value("revision", 1)
         .execute_with_session(&state.db, &mut session)
         .await;
 
     if let Err(why) = update {
-        info!(target:"update_event_config","{:?}", why);
+        info!(target:"update_event_venue","{:?}", why);
         let _abort = session.abort_transaction().await;
-        return ApiResponse::failed(&i18n.translate("update_event_config.failed"));
+        return ApiResponse::failed(&i18n.translate("update_event_venue.failed"));
+    }
+
+    let activity = EventActivity::create(
+        event.id,
+        auth_context.get_user_id(),
+        None,
+        EVENT_ACTIVITY_VENUE_CHANGED,
+        Some(json!({
+            "venue_name": location.venue_name.clone(),
+            "venue_address": location.venue_address.clone(),
+        })),
+    );
+    let save_activity = DB::insert(COLLECTION_EVENT_ACTIVITY)
+        .one_with_session(activity, &state.db, &mut session)
+        .await;
+    if let Err(why) = save_activity {
+        info!(target:"update_event_venue","{:?}", why);
+        let _abort = session.abort_transaction().await;
+        return ApiResponse::failed(&i18n.translate("update_event_venue.failed_save_activity"));
     }
     event.venue_location = Some(location);
+    event.revision += 1;
     let _commit = session.commit_transaction().await;
-    info!(target:"update_event_config","sending sse");
+    info!(target:"update_event_venue","sending sse");
     let find_subscriber = state
         .redis
         .get_list_subscriber(event_id)
@@ -735,8 +1076,8 @@ pub async fn update_event_venue(
         event.clone(),
     );
     let _send = state.sse.send(sse);
-    info!(target:"update_event_config","finish");
-    ApiResponse::ok(event.clone(), &i18n.translate("update_event_config.failed"))
+    info!(target:"update_event_venue","finish");
+    ApiResponse::ok(present_event(event, &lang), &i18n.translate("update_event_venue.ok"))
 }
 
 pub async fn update_event_guest(
@@ -769,6 +1110,32 @@ pub async fn update_event_guest(
         return ApiResponse::failed(&i18n.translate("add_event_host.event_id_invalid"));
     }
 
+    //promotion need the target consent, see feature::host
+    if body.role != EVENT_GUEST_ROLE_GUEST {
+        info!(target:"add_event_host","role change need invitation");
+        return ApiResponse::failed(&i18n.translate("add_event_host.role_requires_invitation"));
+    }
+
+    let find_event = DB::get(COLLECTION_EVENTS)
+        .filter(vec![is("_id", create_event_id.unwrap())])
+        .get_one::<EventDTO>(&state.db)
+        .await;
+    if let Err(why) = find_event {
+        info!(target:"add_event_host","cannot find event {}",why);
+        return ApiResponse::not_found(&i18n.translate("add_event_host.event_not_found"));
+    }
+    let event = find_event.unwrap();
+    let current_user_id = auth_context.get_user_id().unwrap();
+
+    if !is_event_host(&state.db, &event, current_user_id).await {
+        info!(target:"add_event_host","only host can manage guest");
+        return ApiResponse::access_denied(&i18n.translate("add_event_host.not_host"));
+    }
+    if event.host_id == create_user_id {
+        info!(target:"add_event_host","host role only change by transfer");
+        return ApiResponse::failed(&i18n.translate("add_event_host.cannot_change_host"));
+    }
+
     let find_host = DB::get(COLLECTION_EVENT_GUEST)
         .filter(vec![
             is("event_id", create_event_id.unwrap()),
@@ -781,8 +1148,28 @@ pub async fn update_event_guest(
         .get_one::<EventGuestDTO>(&state.db)
         .await;
 
+    //demoting a co-host is left to the owner
+    if let Ok(guest) = &find_host {
+        if guest.role != EVENT_GUEST_ROLE_GUEST && event.host_id != Some(current_user_id) {
+            info!(target:"add_event_host","only owner can demote co-host");
+            return ApiResponse::access_denied(&i18n.translate("add_event_host.not_owner"));
+        }
+    }
+
     let is_exist = find_host.is_ok();
-    let host = find_host.unwrap();
+    let mut host = find_host.unwrap_or_else(|_| {
+        EventGuest {
+            id: Some(ObjectId::new()),
+            user_id: create_user_id,
+            event_id: create_event_id,
+            role: body.role.clone(),
+            rsvp: None,
+            checked_in_at: None,
+            created_at: DateTime::now(),
+            updated_at: DateTime::now(),
+        }
+        .into()
+    });
     let session = state.db.start_session().await;
     if let Err(why) = session {
         info!(target:"add_event_host","{:?}",why);
@@ -804,25 +1191,43 @@ pub async fn update_event_guest(
             let _abort = session.abort_transaction().await;
             return ApiResponse::failed(&i18n.translate("add_event_host.failed"));
         }
+        host.role = body.role.clone();
     } else {
-        let host = EventGuest {
-            id: Some(ObjectId::new()),
-            user_id: create_user_id,
-            event_id: create_event_id,
-            role: body.role.clone(),
-            rsvp: None,
-            created_at: DateTime::now(),
-            updated_at: DateTime::now(),
+        let guest = EventGuest {
+            id: host.id,
+            user_id: host.user_id,
+            event_id: host.event_id,
+            role: host.role.clone(),
+            rsvp: host.rsvp.clone(),
+            checked_in_at: host.checked_in_at,
+            created_at: host.created_at,
+            updated_at: host.updated_at,
         };
 
         let save = DB::insert(COLLECTION_EVENT_GUEST)
-            .one_with_session(host, &state.db, &mut session)
+            .one_with_session(guest, &state.db, &mut session)
             .await;
         if let Err(why) = save {
             info!(target:"add_event_host","{:?}", why);
             let _abort = session.abort_transaction().await;
             return ApiResponse::failed(&i18n.translate("add_event_host.failed"));
         }
+
+        let activity = EventActivity::create(
+            create_event_id,
+            create_user_id,
+            host.id,
+            EVENT_ACTIVITY_GUEST_ADDED,
+            Some(json!({ "role": body.role.clone() })),
+        );
+        let save_activity = DB::insert(COLLECTION_EVENT_ACTIVITY)
+            .one_with_session(activity, &state.db, &mut session)
+            .await;
+        if let Err(why) = save_activity {
+            info!(target:"add_event_host","{:?}", why);
+            let _abort = session.abort_transaction().await;
+            return ApiResponse::failed(&i18n.translate("add_event_host.failed_save_activity"));
+        }
     }
 
     let _commit = session.commit_transaction().await;
@@ -845,6 +1250,389 @@ pub async fn update_event_guest(
     ApiResponse::ok(host, &i18n.translate("add_event_host.failed"))
 }
 
+pub async fn update_event_rsvp(
+    mut state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Json(body): Json<UpdateEventRsvpRequest>,
+) -> ApiResponse<EventGuestDTO> {
+    info!(target:"update_event_rsvp","Starting...");
+    let i18n = i18n!("event", lang);
+    if let Err(err) = body.validate() {
+        info!(target:"update_event_rsvp","{:?}", err);
+        return ApiResponse::error_validation(
+            err,
+            &i18n.translate("update_event_rsvp.validation_error"),
+        );
+    }
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"update_event_rsvp","user id not found");
+        return ApiResponse::failed(&i18n.translate("update_event_rsvp.user_id_not_found"));
+    }
+    let current_user_id = auth_context.get_user_id().unwrap();
+
+    let create_event_id = create_object_id_option(&body.event_id);
+    if let None = create_event_id {
+        info!(target:"update_event_rsvp","event id invalid");
+        return ApiResponse::failed(&i18n.translate("update_event_rsvp.event_id_invalid"));
+    }
+    let create_event_id = create_event_id.unwrap();
+
+    let find_event = DB::get(COLLECTION_EVENTS)
+        .filter(vec![is("_id", create_event_id)])
+        .get_one::<EventDTO>(&state.db)
+        .await;
+    if let Err(why) = find_event {
+        info!(target:"update_event_rsvp","cannot find events {}",why);
+        return ApiResponse::not_found(&i18n.translate("update_event_rsvp.event_not_found"));
+    }
+
+    let find_guest = DB::get(COLLECTION_EVENT_GUEST)
+        .filter(vec![
+            is("event_id", create_event_id),
+            is("user_id", current_user_id),
+        ])
+        .get_one::<EventGuestDTO>(&state.db)
+        .await;
+    let is_exist = find_guest.is_ok();
+    let mut join_code = None;
+
+    //only invited user can rsvp when they're not listed as guest yet,
+    //a shared link work too unless the host banned the user
+    if !is_exist {
+        let find_invitation = DB::get(COLLECTION_EVENT_INVITATION)
+            .filter(vec![
+                is("event_id", create_event_id),
+                is("user_id", current_user_id),
+            ])
+            .get_one::<EventInvitationDTO>(&state.db)
+            .await;
+        if let Err(why) = find_invitation {
+            info!(target:"update_event_rsvp","user not invited {}",why);
+            if let None = body.invitation_code {
+                return ApiResponse::access_denied(
+                    &i18n.translate("update_event_rsvp.not_invited"),
+                );
+            }
+            let event = find_event.as_ref().unwrap();
+            if !is_valid_join_link(&state.db, event, body.invitation_code.as_ref().unwrap()).await
+            {
+                info!(target:"update_event_rsvp","invitation code invalid");
+                return ApiResponse::access_denied(
+                    &i18n.translate("update_event_rsvp.not_invited"),
+                );
+            }
+            if is_banned(&state.db, event.id, current_user_id).await {
+                info!(target:"update_event_rsvp","user banned from event");
+                return ApiResponse::access_denied(&i18n.translate("update_event_rsvp.banned"));
+            }
+            join_code = body.invitation_code.clone();
+        }
+    }
+
+    let mut guest = find_guest.unwrap_or_else(|_| {
+        EventGuest {
+            id: Some(ObjectId::new()),
+            user_id: Some(current_user_id),
+            event_id: Some(create_event_id),
+            role: EVENT_GUEST_ROLE_GUEST.to_string(),
+            rsvp: None,
+            checked_in_at: None,
+            created_at: DateTime::now(),
+            updated_at: DateTime::now(),
+        }
+        .into()
+    });
+    guest.rsvp = Some(body.rsvp.clone());
+    guest.updated_at = DateTime::now();
+
+    let session = state.db.start_session().await;
+    if let Err(why) = session {
+        info!(target:"update_event_rsvp","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("update_event_rsvp.trx_failed"));
+    }
+    let mut session = session.unwrap();
+    let _ = session.start_transaction().await;
+
+    if let Some(code) = join_code {
+        let redeem = redeem_invitation_link(&state.db, &mut session, Some(create_event_id), &code).await;
+        if let Err(why) = redeem {
+            info!(target:"update_event_rsvp","{:?}", why);
+            let _abort = session.abort_transaction().await;
+            return ApiResponse::failed(&i18n.translate(&format!("update_event_rsvp.{}", why)));
+        }
+        if let Some(role) = redeem.unwrap() {
+            guest.role = role;
+        }
+    }
+
+    if is_exist {
+        let update = DB::update(COLLECTION_EVENT_GUEST)
+            .filter(vec![is("_id", guest.id)])
+            .set(doc! {
+                "rsvp":body.rsvp.clone(),
+                "updated_at":guest.updated_at
+            })
+            .execute_with_session(&state.db, &mut session)
+            .await;
+        if let Err(why) = update {
+            info!(target:"update_event_rsvp","{:?}", why);
+            let _abort = session.abort_transaction().await;
+            return ApiResponse::failed(&i18n.translate("update_event_rsvp.failed"));
+        }
+    } else {
+        let save = DB::insert(COLLECTION_EVENT_GUEST)
+            .one_with_session(
+                EventGuest {
+                    id: guest.id,
+                    user_id: guest.user_id,
+                    event_id: guest.event_id,
+                    role: guest.role.clone(),
+                    rsvp: guest.rsvp.clone(),
+                    checked_in_at: guest.checked_in_at,
+                    created_at: guest.created_at,
+                    updated_at: guest.updated_at,
+                },
+                &state.db,
+                &mut session,
+            )
+            .await;
+        if let Err(why) = save {
+            info!(target:"update_event_rsvp","{:?}", why);
+            let _abort = session.abort_transaction().await;
+            return ApiResponse::failed(&i18n.translate("update_event_rsvp.failed"));
+        }
+    }
+
+    let activity = EventActivity::create(
+        Some(create_event_id),
+        Some(current_user_id),
+        guest.id,
+        EVENT_ACTIVITY_RSVP,
+        Some(json!({ "rsvp": body.rsvp.clone() })),
+    );
+    let save_activity = DB::insert(COLLECTION_EVENT_ACTIVITY)
+        .one_with_session(activity, &state.db, &mut session)
+        .await;
+    if let Err(why) = save_activity {
+        info!(target:"update_event_rsvp","{:?}", why);
+        let _abort = session.abort_transaction().await;
+        return ApiResponse::failed(&i18n.translate("update_event_rsvp.failed_save_activity"));
+    }
+
+    let _commit = session.commit_transaction().await;
+    info!(target:"update_event_rsvp","sending sse");
+    let find_subscriber = state
+        .redis
+        .get_list_subscriber(body.event_id)
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect::<Vec<String>>();
+
+    let blocked_ids = get_blocked_user_ids(&state.db, current_user_id)
+        .await
+        .into_iter()
+        .map(|id| id.to_string())
+        .collect::<Vec<String>>();
+
+    let sse = SseBuilder::new(
+        SseTarget::create()
+            .set_event_name(SSE_EVENT_UPDATE_EVENT_RSVP.to_string())
+            .set_user_ids(find_subscriber)
+            .set_exclude_user_ids(blocked_ids),
+        guest.clone(),
+    );
+    state.sse.send(sse).await;
+    info!(target:"update_event_rsvp","finish");
+    ApiResponse::ok(guest, &i18n.translate("update_event_rsvp.ok"))
+}
+
+pub async fn update_event_status(
+    mut state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Path(event_id): Path<String>,
+    Json(body): Json<UpdateEventStatusRequest>,
+) -> ApiResponse<EventDTO> {
+    info!(target:"update_event_status","Starting...");
+    let i18n = i18n!("event", lang);
+    if let Err(err) = body.validate() {
+        info!(target:"update_event_status","{:?}", err);
+        return ApiResponse::error_validation(
+            err,
+            &i18n.translate("update_event_status.validation_error"),
+        );
+    }
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"update_event_status","user id not found");
+        return ApiResponse::failed(&i18n.translate("update_event_status.user_id_not_found"));
+    }
+    let current_user_id = auth_context.get_user_id().unwrap();
+
+    let create_event_id = create_object_id_option(&event_id);
+    if let None = create_event_id {
+        info!(target:"update_event_status","event id invalid");
+        return ApiResponse::failed(&i18n.translate("update_event_status.event_id_invalid"));
+    }
+    let create_event_id = create_event_id.unwrap();
+
+    let find_events = DB::get(COLLECTION_EVENTS)
+        .filter(vec![is("_id", create_event_id)])
+        .lookup(&[
+            one(COLLECTION_USERS, "host_id", "_id", "host"),
+            one(COLLECTION_EVENT_IMAGES, "image_id", "_id", "image"),
+            one(COLLECTION_EVENT_THEMES, "theme_id", "_id", "theme"),
+        ])
+        .get_one::<EventDTO>(&state.db)
+        .await;
+    if let Err(why) = find_events {
+        info!(target:"update_event_status","cannot find events {}",why);
+        return ApiResponse::not_found(&i18n.translate("update_event_status.event_not_found"));
+    }
+    let mut event = find_events.unwrap();
+
+    if !is_event_host(&state.db, &event, current_user_id).await {
+        info!(target:"update_event_status","only host can change status");
+        return ApiResponse::access_denied(&i18n.translate("update_event_status.not_host"));
+    }
+
+    if !can_transition(&event.status, &body.status) {
+        info!(target:"update_event_status","transition {} -> {} not allowed",event.status,body.status);
+        return ApiResponse::failed(&i18n.translate("update_event_status.transition_not_allowed"));
+    }
+
+    if let Err(guard) = check_transition_guard(&event, &body.status) {
+        info!(target:"update_event_status","guard failed {}",guard);
+        return ApiResponse::failed(&i18n.translate(&format!("update_event_status.{}", guard)));
+    }
+
+    let guests = DB::get(COLLECTION_EVENT_GUEST)
+        .filter(vec![is("event_id", create_event_id)])
+        .get_all::<EventGuestDTO>(&state.db)
+        .await
+        .unwrap_or(Vec::new())
+        .into_iter()
+        .filter(|guest| guest.user_id != Some(current_user_id))
+        .collect::<Vec<EventGuestDTO>>();
+
+    let previous_status = event.status.clone();
+    event.status = body.status.clone();
+    event.updated_at = DateTime::now();
+    event.revision += 1;
+
+    let session = state.db.start_session().await;
+    if let Err(why) = session {
+        info!(target:"update_event_status","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("update_event_status.trx_failed"));
+    }
+    let mut session = session.unwrap();
+    let _ = session.start_transaction().await;
+
+    let update_event = DB::update(COLLECTION_EVENTS)
+        .filter(vec![is("_id", create_event_id), is("status", previous_status.clone())])
+        .set(doc! {
+            "status":event.status.clone(),
+            "updated_at":event.updated_at
+        })
+        .inc_value("revision", 1)
+        .execute_with_session(&state.db, &mut session)
+        .await;
+    if let Err(why) = update_event {
+        info!(target:"update_event_status","{:?}", why);
+        let _abort = session.abort_transaction().await;
+        return ApiResponse::failed(&i18n.translate("update_event_status.failed"));
+    }
+    //status filter lost the race, another request already moved the event
+    if update_event.unwrap() == 0 {
+        info!(target:"update_event_status","status changed by another request");
+        let _abort = session.abort_transaction().await;
+        return ApiResponse::conflict(&i18n.translate("update_event_status.conflict"));
+    }
+
+    let save_activity = DB::insert(COLLECTION_EVENT_ACTIVITY)
+        .one_with_session(
+            EventActivity::create(
+                Some(create_event_id),
+                Some(current_user_id),
+                Some(create_event_id),
+                EVENT_ACTIVITY_STATUS_CHANGED,
+                Some(json!({ "from": previous_status, "to": event.status.clone() })),
+            ),
+            &state.db,
+            &mut session,
+        )
+        .await;
+    if let Err(why) = save_activity {
+        info!(target:"update_event_status","{:?}", why);
+        let _abort = session.abort_transaction().await;
+        return ApiResponse::failed(&i18n.translate("update_event_status.failed"));
+    }
+
+    let mut args = HashMap::new();
+    args.insert("event_name".to_string(), event.event_name.clone());
+    let side_effect = match event.status.as_str() {
+        EVENT_STATUS_CANCELLED => {
+            notify_event_guests(
+                &state.db,
+                &mut session,
+                &event,
+                NOTIFICATION_TYPE_EVENT_CANCELLED,
+                i18n.translate_with_args("update_event_status.notification_cancelled", args),
+                guests.iter().filter_map(|guest| guest.user_id).collect(),
+            )
+            .await
+        }
+        EVENT_STATUS_COMPLETED => {
+            run_post_event_flows(
+                &state.db,
+                &mut session,
+                &event,
+                i18n.translate_with_args("update_event_status.notification_completed", args),
+                guests
+                    .iter()
+                    .filter(|guest| guest.rsvp == Some(EVENT_GUEST_RSVP_GOING.to_string()))
+                    .filter_map(|guest| guest.user_id)
+                    .collect(),
+            )
+            .await
+        }
+        _ => Ok(()),
+    };
+    if let Err(why) = side_effect {
+        info!(target:"update_event_status","{:?}", why);
+        let _abort = session.abort_transaction().await;
+        return ApiResponse::failed(&i18n.translate("update_event_status.failed"));
+    }
+    let _commit = session.commit_transaction().await;
+
+    let event = present_event(event, &lang);
+    let mut find_subscriber = state
+        .redis
+        .get_list_subscriber(event_id)
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect::<Vec<String>>();
+    for guest in guests.iter() {
+        if let Some(user_id) = guest.user_id {
+            if !find_subscriber.contains(&user_id.to_string()) {
+                find_subscriber.push(user_id.to_string());
+            }
+        }
+    }
+
+    let sse = SseBuilder::new(
+        SseTarget::create()
+            .set_event_name(SSE_EVENT_UPDATE_EVENT_STATUS.to_string())
+            .set_user_ids(find_subscriber),
+        event.clone(),
+    );
+    state.sse.send(sse).await;
+    info!(target:"update_event_status","finish");
+    ApiResponse::ok(event, &i18n.translate("update_event_status.ok"))
+}
+
 pub async fn delete_event(
     mut state: State<AppState>,
     lang: Lang,
@@ -912,78 +1700,352 @@ pub async fn delete_event(
     );
     let _send = state.sse.send(sse);
     info!(target:"delete_event","finish");
-    ApiResponse::ok(event, &i18n.translate("delete_event.event_not_found"))
+    ApiResponse::ok(present_event(event, &lang), &i18n.translate("delete_event.event_not_found"))
 }
 
 //
+pub async fn get_event_detail(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: Option<AuthContext>,
+    Path(event_id): Path<String>,
+    Query(access): Query<EventAccessRequest>,
+) -> ApiResponse<EventDTO> {
+    info!(target:"get_event_detail","Starting...");
+    let i18n = i18n!("event", lang);
+
+    let create_event_id = create_object_id_option(&event_id);
+    if let None = create_event_id {
+        info!(target:"get_event_detail","event id invalid");
+        return ApiResponse::failed(&i18n.translate("get_event_detail.event_id_invalid"));
+    }
+
+    let find_event = find_event_detail(&state.db, is("_id", create_event_id.unwrap())).await;
+    if let Err(why) = find_event {
+        info!(target:"get_event_detail","cannot find events {}",why);
+        return ApiResponse::not_found(&i18n.translate("get_event_detail.event_not_found"));
+    }
+    let event = find_event.unwrap();
+
+    let current_user_id = auth_context.and_then(|context| context.get_user_id());
+    let viewer_role = check_event_access(&state.db, &event, current_user_id, &access).await;
+    if let Err(why) = viewer_role {
+        info!(target:"get_event_detail","access denied {}",why);
+        return ApiResponse::access_denied(&i18n.translate(&format!("get_event_detail.{}", why)));
+    }
+
+    info!(target:"get_event_detail","finish");
+    ApiResponse::ok(
+        present_event_detail(event, &lang),
+        &i18n.translate("get_event_detail.ok"),
+    )
+}
+
+//old slug redirect permanently to the current one
+pub async fn get_event_by_slug(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: Option<AuthContext>,
+    Path(slug): Path<String>,
+) -> Response {
+    info!(target:"get_event_by_slug","Starting...");
+    let i18n = i18n!("event", lang);
+
+    let find_event = find_event_detail(&state.db, is("slug", slug.clone())).await;
+    if let Err(why) = find_event {
+        info!(target:"get_event_by_slug","slug not current {}",why);
+        let find_renamed = DB::get(COLLECTION_EVENTS)
+            .filter(vec![is("previous_slugs", slug.clone())])
+            .get_one::<EventDTO>(&state.db)
+            .await;
+        if let Ok(Some(current)) = find_renamed.map(|event| event.slug) {
+            info!(target:"get_event_by_slug","redirect to {}",current);
+            return Redirect::permanent(&format!("/event/slug/{}", current)).into_response();
+        }
+        return ApiResponse::<EventDTO>::not_found(
+            &i18n.translate("get_event_by_slug.event_not_found"),
+        )
+        .into_response();
+    }
+    let event = find_event.unwrap();
+
+    let access = EventAccessRequest {
+        invitation_code: None,
+        slug: Some(slug),
+    };
+    let current_user_id = auth_context.and_then(|context| context.get_user_id());
+    let viewer_role = check_event_access(&state.db, &event, current_user_id, &access).await;
+    if let Err(why) = viewer_role {
+        info!(target:"get_event_by_slug","access denied {}",why);
+        return ApiResponse::<EventDTO>::access_denied(
+            &i18n.translate(&format!("get_event_by_slug.{}", why)),
+        )
+        .into_response();
+    }
+
+    info!(target:"get_event_by_slug","finish");
+    ApiResponse::ok(
+        present_event_detail(event, &lang),
+        &i18n.translate("get_event_by_slug.ok"),
+    )
+    .into_response()
+}
+
+//short code of the event itself or of an invitation link
+pub async fn get_event_by_code(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: Option<AuthContext>,
+    Path(code): Path<String>,
+) -> ApiResponse<EventDTO> {
+    info!(target:"get_event_by_code","Starting...");
+    let i18n = i18n!("event", lang);
+
+    let find_event = find_event_by_code(&state.db, &code).await;
+    if let Err(why) = find_event {
+        info!(target:"get_event_by_code","cannot find events {}",why);
+        return ApiResponse::not_found(&i18n.translate("get_event_by_code.event_not_found"));
+    }
+    let event = find_event.unwrap();
+
+    let access = EventAccessRequest {
+        invitation_code: Some(code.clone()),
+        slug: None,
+    };
+    let current_user_id = auth_context.and_then(|context| context.get_user_id());
+    let viewer_role = check_event_access(&state.db, &event, current_user_id, &access).await;
+    if let Err(why) = viewer_role {
+        info!(target:"get_event_by_code","access denied {}",why);
+        return ApiResponse::access_denied(&i18n.translate(&format!("get_event_by_code.{}", why)));
+    }
+    record_invitation_open(&state.db, &code).await;
+
+    info!(target:"get_event_by_code","finish");
+    ApiResponse::ok(
+        present_event_detail(event, &lang),
+        &i18n.translate("get_event_by_code.ok"),
+    )
+}
+
+pub async fn find_event_detail(db: &Client, filter: FilterGroup) -> Result<EventDTO, String> {
+    DB::get(COLLECTION_EVENTS)
+        .filter(vec![filter])
+        .lookup(&[
+            one(COLLECTION_USERS, "host_id", "_id", "host"),
+            one(COLLECTION_EVENT_IMAGES, "image_id", "_id", "image"),
+            one(COLLECTION_EVENT_THEMES, "theme_id", "_id", "theme"),
+        ])
+        .get_one::<EventDTO>(db)
+        .await
+}
+
+pub async fn find_event_by_code(db: &Client, code: &String) -> Result<EventDTO, String> {
+    let find_event = find_event_detail(db, is("invitation_id", code.clone())).await;
+    if find_event.is_ok() {
+        return find_event;
+    }
+    let find_invitation = DB::get(COLLECTION_EVENT_INVITATION)
+        .filter(vec![is("invitation_code", code.clone())])
+        .get_one::<EventInvitationDTO>(db)
+        .await?;
+    find_event_detail(db, is("_id", find_invitation.event_id)).await
+}
+
+//an event copied from another one get its own image, upload_event_image replace the file in place
+//so a shared image would change every copy. the event is still saved without cover when this fail
+pub async fn copy_event_image(
+    db: &Client,
+    image_id: Option<ObjectId>,
+    ref_id: Option<ObjectId>,
+) -> Option<ObjectId> {
+    if let None = image_id {
+        return None;
+    }
+    if let None = ref_id {
+        return None;
+    }
+    let find_image = DB::get(COLLECTION_EVENT_IMAGES)
+        .filter(vec![is("_id", image_id)])
+        .get_one::<EventImage>(db)
+        .await;
+    if let Err(why) = find_image {
+        info!(target:"copy_event_image","cannot find image {}",why);
+        return None;
+    }
+    let image = find_image.unwrap();
+
+    let path = ref_id.unwrap().to_string();
+    let copy_file = MinIO::new()
+        .copy_file(
+            format!("{}/{}", image.path, image.file_name),
+            format!("{}/{}", path, image.file_name),
+            image.bucket.clone(),
+        )
+        .await;
+    if let Err(why) = copy_file {
+        info!(target:"copy_event_image","{}",why);
+        return None;
+    }
+
+    let copy = EventImage {
+        id: Some(ObjectId::new()),
+        file_name: image.file_name,
+        bucket: image.bucket,
+        path,
+        created_at: DateTime::now(),
+        updated_at: DateTime::now(),
+    };
+    let save_image = DB::insert(COLLECTION_EVENT_IMAGES).one(copy.clone(), db).await;
+    if let Err(why) = save_image {
+        info!(target:"copy_event_image","{}",why);
+        return None;
+    }
+    copy.id
+}
+
+//password hash never leave the server
+pub fn present_event_detail(mut event: EventDTO, lang: &Lang) -> EventDTO {
+    event.config.event_password = None;
+    present_event(event, lang)
+}
+
 pub async fn get_event_guest(
     state: State<AppState>,
     lang: Lang,
-    auth_context: AuthContext,
+    auth_context: Option<AuthContext>,
     Path(event_id): Path<String>,
-    Query(query): Query<PaginationRequest>,
+    Query(query): Query<GetEventGuestRequest>,
+    Query(access): Query<EventAccessRequest>,
 ) -> ApiResponse<PagingResponse<EventGuestDTO>> {
+    info!(target:"get_event_guest","Starting...");
     let i18n = i18n!("event", lang);
 
-    if let None = auth_context.get_user_id() {
-        info!(target:"add_event_host","user id not found");
-        return ApiResponse::failed(&i18n.translate("add_event_host.user_id_not_found"));
+    let create_event_id = create_object_id_option(&event_id);
+    if let None = create_event_id {
+        info!(target:"get_event_guest","event id invalid");
+        return ApiResponse::failed(&i18n.translate("get_event_guest.event_id_invalid"));
     }
-    let create_event = create_object_id_option(&event_id);
+    let create_event_id = create_event_id.unwrap();
 
-    let find_host = DB::get(COLLECTION_EVENT_GUEST)
-        .filter(vec![is("event_id", create_event.unwrap())])
+    let find_event = DB::get(COLLECTION_EVENTS)
+        .filter(vec![is("_id", create_event_id)])
+        .get_one::<EventDTO>(&state.db)
+        .await;
+    if let Err(why) = find_event {
+        info!(target:"get_event_guest","cannot find events {}",why);
+        return ApiResponse::not_found(&i18n.translate("get_event_guest.event_not_found"));
+    }
+    let event = find_event.unwrap();
+
+    let current_user_id = auth_context.and_then(|context| context.get_user_id());
+    let viewer_role = check_event_access(&state.db, &event, current_user_id, &access).await;
+    if let Err(why) = viewer_role {
+        info!(target:"get_event_guest","access denied {}",why);
+        return ApiResponse::access_denied(&i18n.translate(&format!("get_event_guest.{}", why)));
+    }
+    let viewer_role = viewer_role.unwrap();
+
+    let mut data = DB::get(COLLECTION_EVENT_GUEST).filter(vec![is("event_id", create_event_id)]);
+
+    if let Some(rsvp) = query.rsvp.clone() {
+        data = data.filter(vec![is("rsvp", rsvp)]);
+    }
+
+    let find_guest = data
         .lookup(&[
             one(COLLECTION_USERS, "user_id", "_id", "user"),
             one(COLLECTION_EVENTS, "event_id", "_id", "event"),
         ])
         .sort(vec![("created_at", -1)])
-        .get_per_page::<EventGuestDTO>(query.page.unwrap_or(0), query.size.unwrap_or(50), &state.db)
+        .get_per_page::<EventGuestDTO>(
+            query.page.unwrap_or(0),
+            query.size.unwrap_or(50),
+            &state.db,
+        )
         .await;
 
-    if let Err(why) = find_host {
-        info!(target:"add_event_host","{:?}",why);
-        return ApiResponse::failed(&i18n.translate("add_event_host.event_not_found"));
+    if let Err(why) = find_guest {
+        info!(target:"get_event_guest","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("get_event_guest.failed"));
     }
 
-    ApiResponse::ok(find_host.unwrap(), &i18n.translate("find_host.event_guest"))
+    let guests = shape_guest_list(&viewer_role, &event, find_guest.unwrap());
+    info!(target:"get_event_guest","finish");
+    ApiResponse::ok(guests, &i18n.translate("get_event_guest.ok"))
 }
 
 pub async fn get_event_host(
     state: State<AppState>,
     lang: Lang,
-    auth_context: AuthContext,
+    auth_context: Option<AuthContext>,
     Path(event_id): Path<String>,
-    Query(query): Query<PaginationRequest>,
+    Query(query): Query<GetEventGuestRequest>,
+    Query(access): Query<EventAccessRequest>,
 ) -> ApiResponse<PagingResponse<EventGuestDTO>> {
+    info!(target:"get_event_host","Starting...");
     let i18n = i18n!("event", lang);
 
-    if let None = auth_context.get_user_id() {
-        info!(target:"add_event_host","user id not found");
-        return ApiResponse::failed(&i18n.translate("add_event_host.user_id_not_found"));
+    let create_event_id = create_object_id_option(&event_id);
+    if let None = create_event_id {
+        info!(target:"get_event_host","event id invalid");
+        return ApiResponse::failed(&i18n.translate("get_event_host.event_id_invalid"));
     }
-    let create_event = create_object_id_option(&event_id);
+    let create_event_id = create_event_id.unwrap();
 
-    let find_host = DB::get(COLLECTION_EVENT_GUEST)
-        .filter(vec![
-            is("event_id", create_event.unwrap()),
-            is_in(
-                "role",
-                vec![EVENT_GUEST_ROLE_CO_HOST, EVENT_GUEST_ROLE_HOST],
-            ),
-        ])
+    let find_event = DB::get(COLLECTION_EVENTS)
+        .filter(vec![is("_id", create_event_id)])
+        .get_one::<EventDTO>(&state.db)
+        .await;
+    if let Err(why) = find_event {
+        info!(target:"get_event_host","cannot find events {}",why);
+        return ApiResponse::not_found(&i18n.translate("get_event_host.event_not_found"));
+    }
+    let event = find_event.unwrap();
+
+    let current_user_id = auth_context.and_then(|context| context.get_user_id());
+    let viewer_role = check_event_access(&state.db, &event, current_user_id, &access).await;
+    if let Err(why) = viewer_role {
+        info!(target:"get_event_host","access denied {}",why);
+        return ApiResponse::access_denied(&i18n.translate(&format!("get_event_host.{}", why)));
+    }
+    let viewer_role = viewer_role.unwrap();
+
+    let mut data = DB::get(COLLECTION_EVENT_GUEST).filter(vec![
+        is("event_id", create_event_id),
+        is_in(
+            "role",
+            vec![EVENT_GUEST_ROLE_CO_HOST, EVENT_GUEST_ROLE_HOST],
+        ),
+    ]);
+
+    if let Some(rsvp) = query.rsvp.clone() {
+        data = data.filter(vec![is("rsvp", rsvp)]);
+    }
+
+    let find_host = data
         .lookup(&[
             one(COLLECTION_USERS, "user_id", "_id", "user"),
             one(COLLECTION_EVENTS, "event_id", "_id", "event"),
         ])
         .sort(vec![("created_at", -1)])
-        .get_per_page::<EventGuestDTO>(query.page.unwrap_or(0), query.size.unwrap_or(50), &state.db)
+        .get_per_page::<EventGuestDTO>(
+            query.page.unwrap_or(0),
+            query.size.unwrap_or(50),
+            &state.db,
+        )
         .await;
 
     if let Err(why) = find_host {
-        info!(target:"add_event_host","{:?}",why);
-        return ApiResponse::failed(&i18n.translate("add_event_host.event_not_found"));
+        info!(target:"get_event_host","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("get_event_host.failed"));
     }
 
-    ApiResponse::ok(find_host.unwrap(), &i18n.translate("find_host.event_guest"))
+    let hosts = shape_guest_list(&viewer_role, &event, find_host.unwrap());
+    info!(target:"get_event_host","finish");
+    ApiResponse::ok(hosts, &i18n.translate("get_event_host.ok"))
+}
+
+//every EventDTO returned to client goes through here
+pub fn present_event(event: EventDTO, lang: &Lang) -> EventDTO {
+    with_schedule(with_lifecycle(event), lang)
 }
@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Validate)]
+pub struct RemoveEventGuestRequest {
+    pub ban: Option<bool>,
+    #[validate(length(max = 500))]
+    pub reason: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EventGuestChangeResponse {
+    pub event_id: String,
+    pub user_id: String,
+    pub action: String,
+    pub banned: bool,
+}
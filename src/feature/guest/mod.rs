@@ -0,0 +1,496 @@
+use crate::common::api_response::ApiResponse;
+use crate::common::app_state::AppState;
+use crate::common::constant::{
+    COLLECTION_EVENT_ACTIVITY, COLLECTION_EVENT_BAN, COLLECTION_EVENT_GUEST,
+    COLLECTION_EVENT_INVITATION, COLLECTION_EVENT_ROLE_INVITATION, COLLECTION_EVENTS,
+    EVENT_ACTIVITY_GUEST_LEFT, EVENT_ACTIVITY_GUEST_REMOVED, EVENT_GUEST_ROLE_GUEST,
+    NOTIFICATION_TYPE_GUEST_LEFT, NOTIFICATION_TYPE_GUEST_REMOVED,
+    REDIS_KEY_USER_DISPLAY_NAME, ROLE_INVITATION_STATUS_CANCELLED,
+    ROLE_INVITATION_STATUS_PENDING, SSE_EVENT_UPDATE_EVENT_GUEST,
+};
+use crate::common::jwt::AuthContext;
+use crate::common::lang::Lang;
+use crate::common::middleware::Json;
+use crate::common::mongo::DB;
+use crate::common::mongo::filter::is;
+use crate::common::sse::sse_builder::{SseBuilder, SseTarget};
+use crate::common::utils::create_object_id_option;
+use crate::dto::event_dto::EventDTO;
+use crate::dto::event_guest_dto::EventGuestDTO;
+use crate::entity::event_activity::EventActivity;
+use crate::entity::event_ban::EventBan;
+use crate::feature::event::event_lifecycle::notify_event_guests;
+use crate::feature::event::event_policy::{get_event_host_ids, is_event_host};
+use crate::feature::guest::guest_model::{EventGuestChangeResponse, RemoveEventGuestRequest};
+use crate::i18n;
+use axum::extract::{Path, State};
+use bson::oid::ObjectId;
+use bson::{DateTime, doc};
+use log::info;
+use mongodb::{Client, ClientSession};
+use serde_json::json;
+use std::collections::HashMap;
+use validator::Validate;
+
+pub mod guest_model;
+
+pub async fn leave_event(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Path(event_id): Path<String>,
+) -> ApiResponse<EventGuestChangeResponse> {
+    info!(target:"leave_event","Starting...");
+    let i18n = i18n!("guest", lang);
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"leave_event","user id not found");
+        return ApiResponse::failed(&i18n.translate("leave_event.user_id_not_found"));
+    }
+    let current_user_id = auth_context.get_user_id().unwrap();
+
+    let create_event_id = create_object_id_option(&event_id);
+    if let None = create_event_id {
+        info!(target:"leave_event","event id invalid");
+        return ApiResponse::failed(&i18n.translate("leave_event.event_id_invalid"));
+    }
+    let create_event_id = create_event_id.unwrap();
+
+    let find_event = DB::get(COLLECTION_EVENTS)
+        .filter(vec![is("_id", create_event_id)])
+        .get_one::<EventDTO>(&state.db)
+        .await;
+    if let Err(why) = find_event {
+        info!(target:"leave_event","cannot find event {}",why);
+        return ApiResponse::not_found(&i18n.translate("leave_event.event_not_found"));
+    }
+    let event = find_event.unwrap();
+
+    //owner has to hand over the event first
+    if event.host_id == Some(current_user_id) {
+        info!(target:"leave_event","owner cannot leave");
+        return ApiResponse::failed(&i18n.translate("leave_event.owner_cannot_leave"));
+    }
+
+    let find_guest = DB::get(COLLECTION_EVENT_GUEST)
+        .filter(vec![
+            is("event_id", create_event_id),
+            is("user_id", current_user_id),
+        ])
+        .get_one::<EventGuestDTO>(&state.db)
+        .await;
+    let find_invitation = DB::get(COLLECTION_EVENT_INVITATION)
+        .filter(vec![
+            is("event_id", create_event_id),
+            is("user_id", current_user_id),
+        ])
+        .count(&state.db)
+        .await
+        .unwrap_or(0);
+    if find_guest.is_err() && find_invitation == 0 {
+        info!(target:"leave_event","user is not a participant");
+        return ApiResponse::not_found(&i18n.translate("leave_event.not_participant"));
+    }
+
+    let session = state.db.start_session().await;
+    if let Err(why) = session {
+        info!(target:"leave_event","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("leave_event.trx_failed"));
+    }
+    let mut session = session.unwrap();
+    let _ = session.start_transaction().await;
+
+    let release = release_event_guest(
+        &state.db,
+        &mut session,
+        &event,
+        current_user_id,
+        current_user_id,
+        EVENT_ACTIVITY_GUEST_LEFT,
+        json!({}),
+    )
+    .await;
+    if let Err(why) = release {
+        info!(target:"leave_event","{:?}",why);
+        let _abort = session.abort_transaction().await;
+        return ApiResponse::failed(&i18n.translate("leave_event.failed"));
+    }
+
+    let host_ids = get_event_host_ids(&state.db, &event).await;
+    let mut args = HashMap::new();
+    args.insert(
+        "display_name".to_string(),
+        auth_context
+            .get(REDIS_KEY_USER_DISPLAY_NAME)
+            .cloned()
+            .unwrap_or(i18n.translate("default_display_name")),
+    );
+    args.insert("event_name".to_string(), event.event_name.clone());
+    let notify = notify_event_guests(
+        &state.db,
+        &mut session,
+        &event,
+        NOTIFICATION_TYPE_GUEST_LEFT,
+        i18n.translate_with_args("leave_event.notification", args),
+        host_ids
+            .iter()
+            .filter_map(|id| create_object_id_option(id))
+            .collect(),
+    )
+    .await;
+    if let Err(why) = notify {
+        info!(target:"leave_event","{:?}",why);
+        let _abort = session.abort_transaction().await;
+        return ApiResponse::failed(&i18n.translate("leave_event.failed"));
+    }
+    let _commit = session.commit_transaction().await;
+
+    let response = EventGuestChangeResponse {
+        event_id: event_id.clone(),
+        user_id: current_user_id.to_string(),
+        action: EVENT_ACTIVITY_GUEST_LEFT.to_string(),
+        banned: false,
+    };
+    let sse = SseBuilder::new(
+        SseTarget::create()
+            .set_event_name(SSE_EVENT_UPDATE_EVENT_GUEST.to_string())
+            .set_user_ids(host_ids),
+        response.clone(),
+    );
+    state.sse.send(sse).await;
+
+    info!(target:"leave_event","finish");
+    ApiResponse::ok(response, &i18n.translate("leave_event.ok"))
+}
+
+//co-host can remove guest, removing another co-host is left to the owner
+pub async fn remove_event_guest(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Path((event_id, user_id)): Path<(String, String)>,
+    Json(body): Json<RemoveEventGuestRequest>,
+) -> ApiResponse<EventGuestChangeResponse> {
+    info!(target:"remove_event_guest","Starting...");
+    let i18n = i18n!("guest", lang);
+    if let Err(err) = body.validate() {
+        info!(target:"remove_event_guest","{:?}", err);
+        return ApiResponse::error_validation(
+            err,
+            &i18n.translate("remove_event_guest.validation_error"),
+        );
+    }
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"remove_event_guest","user id not found");
+        return ApiResponse::failed(&i18n.translate("remove_event_guest.user_id_not_found"));
+    }
+    let current_user_id = auth_context.get_user_id().unwrap();
+
+    let create_event_id = create_object_id_option(&event_id);
+    if let None = create_event_id {
+        info!(target:"remove_event_guest","event id invalid");
+        return ApiResponse::failed(&i18n.translate("remove_event_guest.event_id_invalid"));
+    }
+    let create_event_id = create_event_id.unwrap();
+
+    let create_user_id = create_object_id_option(&user_id);
+    if let None = create_user_id {
+        info!(target:"remove_event_guest","user id invalid");
+        return ApiResponse::failed(&i18n.translate("remove_event_guest.user_id_invalid"));
+    }
+    let create_user_id = create_user_id.unwrap();
+
+    let find_event = DB::get(COLLECTION_EVENTS)
+        .filter(vec![is("_id", create_event_id)])
+        .get_one::<EventDTO>(&state.db)
+        .await;
+    if let Err(why) = find_event {
+        info!(target:"remove_event_guest","cannot find event {}",why);
+        return ApiResponse::not_found(&i18n.translate("remove_event_guest.event_not_found"));
+    }
+    let event = find_event.unwrap();
+
+    if !is_event_host(&state.db, &event, current_user_id).await {
+        info!(target:"remove_event_guest","only host can remove guest");
+        return ApiResponse::access_denied(&i18n.translate("remove_event_guest.not_host"));
+    }
+    if event.host_id == Some(create_user_id) || create_user_id == current_user_id {
+        info!(target:"remove_event_guest","cannot remove host or self");
+        return ApiResponse::failed(&i18n.translate("remove_event_guest.cannot_remove_host"));
+    }
+
+    let find_guest = DB::get(COLLECTION_EVENT_GUEST)
+        .filter(vec![
+            is("event_id", create_event_id),
+            is("user_id", create_user_id),
+        ])
+        .get_one::<EventGuestDTO>(&state.db)
+        .await;
+    if let Ok(guest) = &find_guest {
+        if guest.role != EVENT_GUEST_ROLE_GUEST && event.host_id != Some(current_user_id) {
+            info!(target:"remove_event_guest","only owner can remove co-host");
+            return ApiResponse::access_denied(&i18n.translate("remove_event_guest.not_owner"));
+        }
+    }
+    if find_guest.is_err() {
+        let invitation_count = DB::get(COLLECTION_EVENT_INVITATION)
+            .filter(vec![is("event_id", create_event_id), is("user_id", create_user_id)])
+            .count(&state.db)
+            .await
+            .unwrap_or(0);
+        if invitation_count == 0 {
+            info!(target:"remove_event_guest","user is not part of the event");
+            return ApiResponse::not_found(&i18n.translate("remove_event_guest.guest_not_found"));
+        }
+    }
+
+    let is_ban = body.ban.unwrap_or(false);
+    let session = state.db.start_session().await;
+    if let Err(why) = session {
+        info!(target:"remove_event_guest","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("remove_event_guest.trx_failed"));
+    }
+    let mut session = session.unwrap();
+    let _ = session.start_transaction().await;
+
+    let release = release_event_guest(
+        &state.db,
+        &mut session,
+        &event,
+        create_user_id,
+        current_user_id,
+        EVENT_ACTIVITY_GUEST_REMOVED,
+        json!({ "removed_by": current_user_id.to_string(), "ban": is_ban }),
+    )
+    .await;
+    if let Err(why) = release {
+        info!(target:"remove_event_guest","{:?}",why);
+        let _abort = session.abort_transaction().await;
+        return ApiResponse::failed(&i18n.translate("remove_event_guest.failed"));
+    }
+
+    if is_ban {
+        let ban = DB::upsert(COLLECTION_EVENT_BAN)
+            .filter(vec![
+                is("event_id", create_event_id),
+                is("user_id", create_user_id),
+            ])
+            .set(doc! {
+                "banned_by":current_user_id,
+                "reason":body.reason.clone(),
+                "updated_at":DateTime::now()
+            })
+            .set_on_insert(doc! {
+                "created_at":DateTime::now()
+            })
+            .execute_with_session(&state.db, &mut session)
+            .await;
+        if let Err(why) = ban {
+            info!(target:"remove_event_guest","{:?}",why);
+            let _abort = session.abort_transaction().await;
+            return ApiResponse::failed(&i18n.translate("remove_event_guest.failed"));
+        }
+    }
+
+    let mut args = HashMap::new();
+    args.insert("event_name".to_string(), event.event_name.clone());
+    let notify = notify_event_guests(
+        &state.db,
+        &mut session,
+        &event,
+        NOTIFICATION_TYPE_GUEST_REMOVED,
+        i18n.translate_with_args("remove_event_guest.notification", args),
+        vec![create_user_id],
+    )
+    .await;
+    if let Err(why) = notify {
+        info!(target:"remove_event_guest","{:?}",why);
+        let _abort = session.abort_transaction().await;
+        return ApiResponse::failed(&i18n.translate("remove_event_guest.failed"));
+    }
+    let _commit = session.commit_transaction().await;
+
+    let response = EventGuestChangeResponse {
+        event_id: event_id.clone(),
+        user_id: user_id.clone(),
+        action: EVENT_ACTIVITY_GUEST_REMOVED.to_string(),
+        banned: is_ban,
+    };
+    let mut target_ids = get_event_host_ids(&state.db, &event).await;
+    target_ids.push(user_id.clone());
+    let sse = SseBuilder::new(
+        SseTarget::create()
+            .set_event_name(SSE_EVENT_UPDATE_EVENT_GUEST.to_string())
+            .set_user_ids(target_ids),
+        response.clone(),
+    );
+    state.sse.send(sse).await;
+
+    info!(target:"remove_event_guest","finish");
+    ApiResponse::ok(response, &i18n.translate("remove_event_guest.ok"))
+}
+
+pub async fn unban_event_guest(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Path((event_id, user_id)): Path<(String, String)>,
+) -> ApiResponse<String> {
+    info!(target:"unban_event_guest","Starting...");
+    let i18n = i18n!("guest", lang);
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"unban_event_guest","user id not found");
+        return ApiResponse::failed(&i18n.translate("unban_event_guest.user_id_not_found"));
+    }
+
+    let create_event_id = create_object_id_option(&event_id);
+    if let None = create_event_id {
+        info!(target:"unban_event_guest","event id invalid");
+        return ApiResponse::failed(&i18n.translate("unban_event_guest.event_id_invalid"));
+    }
+    let create_user_id = create_object_id_option(&user_id);
+    if let None = create_user_id {
+        info!(target:"unban_event_guest","user id invalid");
+        return ApiResponse::failed(&i18n.translate("unban_event_guest.user_id_invalid"));
+    }
+
+    let find_event = DB::get(COLLECTION_EVENTS)
+        .filter(vec![is("_id", create_event_id.unwrap())])
+        .get_one::<EventDTO>(&state.db)
+        .await;
+    if let Err(why) = find_event {
+        info!(target:"unban_event_guest","cannot find event {}",why);
+        return ApiResponse::not_found(&i18n.translate("unban_event_guest.event_not_found"));
+    }
+    let event = find_event.unwrap();
+
+    if !is_event_host(&state.db, &event, auth_context.get_user_id().unwrap()).await {
+        info!(target:"unban_event_guest","only host can unban");
+        return ApiResponse::access_denied(&i18n.translate("unban_event_guest.not_host"));
+    }
+
+    let delete_ban = DB::delete(COLLECTION_EVENT_BAN)
+        .filter(vec![
+            is("event_id", create_event_id.unwrap()),
+            is("user_id", create_user_id.unwrap()),
+        ])
+        .one(&state.db)
+        .await;
+    if let Err(why) = delete_ban {
+        info!(target:"unban_event_guest","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("unban_event_guest.failed"));
+    }
+    if delete_ban.unwrap() == 0 {
+        info!(target:"unban_event_guest","ban not found");
+        return ApiResponse::not_found(&i18n.translate("unban_event_guest.not_found"));
+    }
+
+    info!(target:"unban_event_guest","finish");
+    ApiResponse::ok(user_id, &i18n.translate("unban_event_guest.ok"))
+}
+
+pub async fn get_event_bans(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Path(event_id): Path<String>,
+) -> ApiResponse<Vec<EventBan>> {
+    info!(target:"get_event_bans","Starting...");
+    let i18n = i18n!("guest", lang);
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"get_event_bans","user id not found");
+        return ApiResponse::failed(&i18n.translate("get_event_bans.user_id_not_found"));
+    }
+
+    let create_event_id = create_object_id_option(&event_id);
+    if let None = create_event_id {
+        info!(target:"get_event_bans","event id invalid");
+        return ApiResponse::failed(&i18n.translate("get_event_bans.event_id_invalid"));
+    }
+
+    let find_event = DB::get(COLLECTION_EVENTS)
+        .filter(vec![is("_id", create_event_id.unwrap())])
+        .get_one::<EventDTO>(&state.db)
+        .await;
+    if let Err(why) = find_event {
+        info!(target:"get_event_bans","cannot find event {}",why);
+        return ApiResponse::not_found(&i18n.translate("get_event_bans.event_not_found"));
+    }
+    let event = find_event.unwrap();
+
+    if !is_event_host(&state.db, &event, auth_context.get_user_id().unwrap()).await {
+        info!(target:"get_event_bans","only host can see bans");
+        return ApiResponse::access_denied(&i18n.translate("get_event_bans.not_host"));
+    }
+
+    let find_bans = DB::get(COLLECTION_EVENT_BAN)
+        .filter(vec![is("event_id", create_event_id.unwrap())])
+        .sort(vec![("created_at", -1)])
+        .get_all::<EventBan>(&state.db)
+        .await;
+    if let Err(why) = find_bans {
+        info!(target:"get_event_bans","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("get_event_bans.failed"));
+    }
+
+    info!(target:"get_event_bans","finish");
+    ApiResponse::ok(find_bans.unwrap(), &i18n.translate("get_event_bans.ok"))
+}
+
+//drop the guest row and everything that would let the user back in without a new invitation
+async fn release_event_guest(
+    db: &Client,
+    session: &mut ClientSession,
+    event: &EventDTO,
+    user_id: ObjectId,
+    actor_id: ObjectId,
+    activity_type: &str,
+    payload: serde_json::Value,
+) -> Result<(), String> {
+    let delete_guest = DB::delete(COLLECTION_EVENT_GUEST)
+        .filter(vec![is("event_id", event.id), is("user_id", user_id)])
+        .many_with_session(db, session)
+        .await;
+    if let Err(why) = delete_guest {
+        return Err(why);
+    }
+
+    let delete_invitation = DB::delete(COLLECTION_EVENT_INVITATION)
+        .filter(vec![is("event_id", event.id), is("user_id", user_id)])
+        .many_with_session(db, session)
+        .await;
+    if let Err(why) = delete_invitation {
+        return Err(why);
+    }
+
+    let cancel_role_invitation = DB::update(COLLECTION_EVENT_ROLE_INVITATION)
+        .filter(vec![
+            is("event_id", event.id),
+            is("user_id", user_id),
+            is("status", ROLE_INVITATION_STATUS_PENDING),
+        ])
+        .set(doc! {
+            "status":ROLE_INVITATION_STATUS_CANCELLED,
+            "updated_at":DateTime::now()
+        })
+        .execute_many_with_session(db, session)
+        .await;
+    if let Err(why) = cancel_role_invitation {
+        return Err(why);
+    }
+
+    let activity = EventActivity::create(
+        event.id,
+        Some(user_id),
+        Some(actor_id),
+        activity_type,
+        Some(payload),
+    );
+    DB::insert(COLLECTION_EVENT_ACTIVITY)
+        .one_with_session(activity, db, session)
+        .await
+        .map(|_| ())
+}
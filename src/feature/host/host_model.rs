@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Validate)]
+pub struct EventRoleRequest {
+    #[validate(length(min = 1))]
+    pub user_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Validate)]
+pub struct RespondRoleInvitationRequest {
+    pub accept: bool,
+}
@@ -0,0 +1,626 @@
+use crate::common::api_response::ApiResponse;
+use crate::common::app_state::AppState;
+use crate::common::constant::{
+    COLLECTION_EVENT_ACTIVITY, COLLECTION_EVENT_GUEST, COLLECTION_EVENT_ROLE_INVITATION,
+    COLLECTION_EVENTS, COLLECTION_USERS, EVENT_ACTIVITY_GUEST_ADDED,
+    EVENT_ACTIVITY_HOST_TRANSFERRED, EVENT_GUEST_ROLE_CO_HOST, EVENT_GUEST_ROLE_HOST,
+    NOTIFICATION_TYPE_ROLE_INVITATION, REDIS_KEY_USER_DISPLAY_NAME,
+    ROLE_INVITATION_STATUS_ACCEPTED, ROLE_INVITATION_STATUS_CANCELLED,
+    ROLE_INVITATION_STATUS_DECLINED, ROLE_INVITATION_STATUS_PENDING, SSE_EVENT_UPDATE_EVENT_HOST,
+};
+use crate::common::jwt::AuthContext;
+use crate::common::lang::Lang;
+use crate::common::middleware::Json;
+use crate::common::mongo::DB;
+use crate::common::mongo::filter::{is, not_equal};
+use crate::common::mongo::lookup::one;
+use crate::common::sse::sse_builder::{SseBuilder, SseTarget};
+use crate::common::utils::create_object_id_option;
+use crate::dto::event_dto::EventDTO;
+use crate::dto::event_guest_dto::EventGuestDTO;
+use crate::dto::event_role_invitation_dto::EventRoleInvitationDTO;
+use crate::dto::user_dto::UserDTO;
+use crate::entity::event_activity::EventActivity;
+use crate::entity::event_guest::EventGuest;
+use crate::entity::event_role_invitation::EventRoleInvitation;
+use crate::feature::event::event_lifecycle::notify_event_guests;
+use crate::feature::host::host_model::{EventRoleRequest, RespondRoleInvitationRequest};
+use crate::i18n;
+use axum::extract::{Path, State};
+use bson::oid::ObjectId;
+use bson::{DateTime, doc};
+use log::info;
+use mongodb::{Client, ClientSession};
+use serde_json::json;
+use validator::Validate;
+
+pub mod host_model;
+
+pub async fn invite_co_host(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Path(event_id): Path<String>,
+    Json(body): Json<EventRoleRequest>,
+) -> ApiResponse<EventRoleInvitationDTO> {
+    info!(target:"invite_co_host","Starting...");
+    let i18n = i18n!("host", lang);
+    let title = format!(
+        "{} {}",
+        auth_context
+            .get(REDIS_KEY_USER_DISPLAY_NAME)
+            .unwrap_or(&i18n.translate("default_display_name")),
+        i18n.translate("invite_co_host.notification")
+    );
+    let invite = create_role_invitation(
+        &state.db,
+        auth_context.get_user_id(),
+        &event_id,
+        &body,
+        EVENT_GUEST_ROLE_CO_HOST,
+        title,
+    )
+    .await;
+    if let Err(why) = invite {
+        info!(target:"invite_co_host","{}",why);
+        return ApiResponse::failed(&i18n.translate(&format!("invite_co_host.{}", why)));
+    }
+
+    info!(target:"invite_co_host","finish");
+    ApiResponse::ok(invite.unwrap(), &i18n.translate("invite_co_host.ok"))
+}
+
+//ownership only move after the new host accept, see respond_role_invitation
+pub async fn transfer_event_host(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Path(event_id): Path<String>,
+    Json(body): Json<EventRoleRequest>,
+) -> ApiResponse<EventRoleInvitationDTO> {
+    info!(target:"transfer_event_host","Starting...");
+    let i18n = i18n!("host", lang);
+    let title = format!(
+        "{} {}",
+        auth_context
+            .get(REDIS_KEY_USER_DISPLAY_NAME)
+            .unwrap_or(&i18n.translate("default_display_name")),
+        i18n.translate("transfer_event_host.notification")
+    );
+    let invite = create_role_invitation(
+        &state.db,
+        auth_context.get_user_id(),
+        &event_id,
+        &body,
+        EVENT_GUEST_ROLE_HOST,
+        title,
+    )
+    .await;
+    if let Err(why) = invite {
+        info!(target:"transfer_event_host","{}",why);
+        return ApiResponse::failed(&i18n.translate(&format!("transfer_event_host.{}", why)));
+    }
+
+    info!(target:"transfer_event_host","finish");
+    ApiResponse::ok(invite.unwrap(), &i18n.translate("transfer_event_host.ok"))
+}
+
+pub async fn get_my_role_invitations(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+) -> ApiResponse<Vec<EventRoleInvitationDTO>> {
+    info!(target:"get_my_role_invitations","Starting...");
+    let i18n = i18n!("host", lang);
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"get_my_role_invitations","user id not found");
+        return ApiResponse::failed(&i18n.translate("get_my_role_invitations.user_id_not_found"));
+    }
+
+    let find_invitations = DB::get(COLLECTION_EVENT_ROLE_INVITATION)
+        .filter(vec![
+            is("user_id", auth_context.get_user_id().unwrap()),
+            is("status", ROLE_INVITATION_STATUS_PENDING),
+        ])
+        .lookup(&[
+            one(COLLECTION_EVENTS, "event_id", "_id", "event"),
+            one(COLLECTION_USERS, "invited_by", "_id", "inviter"),
+        ])
+        .sort(vec![("created_at", -1)])
+        .get_all::<EventRoleInvitationDTO>(&state.db)
+        .await;
+    if let Err(why) = find_invitations {
+        info!(target:"get_my_role_invitations","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("get_my_role_invitations.failed"));
+    }
+
+    info!(target:"get_my_role_invitations","finish");
+    ApiResponse::ok(
+        find_invitations.unwrap(),
+        &i18n.translate("get_my_role_invitations.ok"),
+    )
+}
+
+pub async fn respond_role_invitation(
+    mut state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Path(invitation_id): Path<String>,
+    Json(body): Json<RespondRoleInvitationRequest>,
+) -> ApiResponse<EventRoleInvitationDTO> {
+    info!(target:"respond_role_invitation","Starting...");
+    let i18n = i18n!("host", lang);
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"respond_role_invitation","user id not found");
+        return ApiResponse::failed(&i18n.translate("respond_role_invitation.user_id_not_found"));
+    }
+    let current_user_id = auth_context.get_user_id().unwrap();
+
+    let create_invitation_id = create_object_id_option(&invitation_id);
+    if let None = create_invitation_id {
+        info!(target:"respond_role_invitation","invitation id invalid");
+        return ApiResponse::failed(
+            &i18n.translate("respond_role_invitation.invitation_id_invalid"),
+        );
+    }
+
+    let find_invitation = DB::get(COLLECTION_EVENT_ROLE_INVITATION)
+        .filter(vec![
+            is("_id", create_invitation_id.unwrap()),
+            is("user_id", current_user_id),
+            is("status", ROLE_INVITATION_STATUS_PENDING),
+        ])
+        .get_one::<EventRoleInvitationDTO>(&state.db)
+        .await;
+    if let Err(why) = find_invitation {
+        info!(target:"respond_role_invitation","cannot find invitation {}",why);
+        return ApiResponse::not_found(
+            &i18n.translate("respond_role_invitation.invitation_not_found"),
+        );
+    }
+    let mut invitation = find_invitation.unwrap();
+
+    let find_event = DB::get(COLLECTION_EVENTS)
+        .filter(vec![is("_id", invitation.event_id)])
+        .get_one::<EventDTO>(&state.db)
+        .await;
+    if let Err(why) = find_event {
+        info!(target:"respond_role_invitation","cannot find event {}",why);
+        return ApiResponse::not_found(&i18n.translate("respond_role_invitation.event_not_found"));
+    }
+    let event = find_event.unwrap();
+
+    let session = state.db.start_session().await;
+    if let Err(why) = session {
+        info!(target:"respond_role_invitation","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("respond_role_invitation.trx_failed"));
+    }
+    let mut session = session.unwrap();
+    let _ = session.start_transaction().await;
+
+    //the host who sent it may have handed over the event since
+    let is_outdated = event.host_id != invitation.invited_by;
+    let status = if !body.accept {
+        ROLE_INVITATION_STATUS_DECLINED
+    } else if is_outdated {
+        ROLE_INVITATION_STATUS_CANCELLED
+    } else {
+        ROLE_INVITATION_STATUS_ACCEPTED
+    };
+
+    let update_invitation = DB::update(COLLECTION_EVENT_ROLE_INVITATION)
+        .filter(vec![
+            is("_id", invitation.id),
+            is("status", ROLE_INVITATION_STATUS_PENDING),
+        ])
+        .set(doc! {
+            "status":status,
+            "responded_at":DateTime::now(),
+            "updated_at":DateTime::now()
+        })
+        .execute_with_session(&state.db, &mut session)
+        .await;
+    if let Err(why) = update_invitation {
+        info!(target:"respond_role_invitation","{:?}",why);
+        let _abort = session.abort_transaction().await;
+        return ApiResponse::failed(&i18n.translate("respond_role_invitation.failed"));
+    }
+    if update_invitation.unwrap() == 0 {
+        info!(target:"respond_role_invitation","invitation already answered");
+        let _abort = session.abort_transaction().await;
+        return ApiResponse::failed(
+            &i18n.translate("respond_role_invitation.invitation_not_found"),
+        );
+    }
+
+    if status == ROLE_INVITATION_STATUS_ACCEPTED {
+        let apply = if invitation.role == EVENT_GUEST_ROLE_HOST {
+            apply_host_transfer(&state.db, &mut session, &event, current_user_id).await
+        } else {
+            apply_co_host(&state.db, &mut session, &event, current_user_id).await
+        };
+        if let Err(why) = apply {
+            info!(target:"respond_role_invitation","{}",why);
+            let _abort = session.abort_transaction().await;
+            return ApiResponse::failed(
+                &i18n.translate(&format!("respond_role_invitation.{}", why)),
+            );
+        }
+    }
+    let _commit = session.commit_transaction().await;
+
+    invitation.status = status.to_string();
+    invitation.responded_at = Some(DateTime::now());
+    if is_outdated && body.accept {
+        info!(target:"respond_role_invitation","invitation outdated");
+        return ApiResponse::failed(&i18n.translate("respond_role_invitation.invitation_outdated"));
+    }
+
+    if status == ROLE_INVITATION_STATUS_ACCEPTED {
+        let find_subscriber = state
+            .redis
+            .get_list_subscriber(event.id.unwrap().to_string())
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect::<Vec<String>>();
+        let sse = SseBuilder::new(
+            SseTarget::create()
+                .set_event_name(SSE_EVENT_UPDATE_EVENT_HOST.to_string())
+                .set_user_ids(find_subscriber),
+            invitation.clone(),
+        );
+        state.sse.send(sse).await;
+    }
+
+    info!(target:"respond_role_invitation","finish");
+    ApiResponse::ok(invitation, &i18n.translate("respond_role_invitation.ok"))
+}
+
+pub async fn cancel_role_invitation(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Path(invitation_id): Path<String>,
+) -> ApiResponse<String> {
+    info!(target:"cancel_role_invitation","Starting...");
+    let i18n = i18n!("host", lang);
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"cancel_role_invitation","user id not found");
+        return ApiResponse::failed(&i18n.translate("cancel_role_invitation.user_id_not_found"));
+    }
+
+    let create_invitation_id = create_object_id_option(&invitation_id);
+    if let None = create_invitation_id {
+        info!(target:"cancel_role_invitation","invitation id invalid");
+        return ApiResponse::failed(
+            &i18n.translate("cancel_role_invitation.invitation_id_invalid"),
+        );
+    }
+
+    let cancel_invitation = DB::update(COLLECTION_EVENT_ROLE_INVITATION)
+        .filter(vec![
+            is("_id", create_invitation_id.unwrap()),
+            is("invited_by", auth_context.get_user_id().unwrap()),
+            is("status", ROLE_INVITATION_STATUS_PENDING),
+        ])
+        .set(doc! {
+            "status":ROLE_INVITATION_STATUS_CANCELLED,
+            "updated_at":DateTime::now()
+        })
+        .execute(&state.db)
+        .await;
+    if let Err(why) = cancel_invitation {
+        info!(target:"cancel_role_invitation","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("cancel_role_invitation.failed"));
+    }
+    if cancel_invitation.unwrap() == 0 {
+        info!(target:"cancel_role_invitation","invitation not found");
+        return ApiResponse::not_found(
+            &i18n.translate("cancel_role_invitation.invitation_not_found"),
+        );
+    }
+
+    info!(target:"cancel_role_invitation","finish");
+    ApiResponse::ok(invitation_id, &i18n.translate("cancel_role_invitation.ok"))
+}
+
+//only the owner in event.host_id can promote, a pending invitation for the same role is reused
+async fn create_role_invitation(
+    db: &Client,
+    current_user_id: Option<ObjectId>,
+    event_id: &String,
+    body: &EventRoleRequest,
+    role: &str,
+    title: String,
+) -> Result<EventRoleInvitationDTO, String> {
+    if let Err(why) = body.validate() {
+        info!(target:"create_role_invitation","{:?}", why);
+        return Err("validation_error".to_string());
+    }
+    if let None = current_user_id {
+        return Err("user_id_not_found".to_string());
+    }
+    let current_user_id = current_user_id.unwrap();
+
+    let create_event_id = create_object_id_option(event_id);
+    if let None = create_event_id {
+        return Err("event_id_invalid".to_string());
+    }
+    let create_user_id = create_object_id_option(&body.user_id);
+    if let None = create_user_id {
+        return Err("user_id_invalid".to_string());
+    }
+    let create_user_id = create_user_id.unwrap();
+
+    let find_event = DB::get(COLLECTION_EVENTS)
+        .filter(vec![is("_id", create_event_id.unwrap())])
+        .get_one::<EventDTO>(db)
+        .await;
+    if let Err(why) = find_event {
+        info!(target:"create_role_invitation","cannot find event {}",why);
+        return Err("event_not_found".to_string());
+    }
+    let event = find_event.unwrap();
+
+    if event.host_id != Some(current_user_id) {
+        return Err("not_owner".to_string());
+    }
+    if create_user_id == current_user_id {
+        return Err("already_host".to_string());
+    }
+
+    let find_target = DB::get(COLLECTION_USERS)
+        .filter(vec![is("_id", create_user_id)])
+        .get_one::<UserDTO>(db)
+        .await;
+    if let Err(why) = find_target {
+        info!(target:"create_role_invitation","cannot find user {}",why);
+        return Err("user_not_found".to_string());
+    }
+
+    if role == EVENT_GUEST_ROLE_CO_HOST {
+        let find_co_host = DB::get(COLLECTION_EVENT_GUEST)
+            .filter(vec![
+                is("event_id", event.id),
+                is("user_id", create_user_id),
+                is("role", EVENT_GUEST_ROLE_CO_HOST),
+            ])
+            .get_one::<EventGuestDTO>(db)
+            .await;
+        if find_co_host.is_ok() {
+            return Err("already_co_host".to_string());
+        }
+    }
+
+    let find_pending = DB::get(COLLECTION_EVENT_ROLE_INVITATION)
+        .filter(vec![
+            is("event_id", event.id),
+            is("user_id", create_user_id),
+            is("role", role),
+            is("status", ROLE_INVITATION_STATUS_PENDING),
+        ])
+        .get_one::<EventRoleInvitationDTO>(db)
+        .await;
+    if let Ok(pending) = find_pending {
+        return Ok(pending);
+    }
+
+    let invitation = EventRoleInvitation {
+        id: Some(ObjectId::new()),
+        event_id: event.id,
+        user_id: Some(create_user_id),
+        invited_by: Some(current_user_id),
+        role: role.to_string(),
+        status: ROLE_INVITATION_STATUS_PENDING.to_string(),
+        responded_at: None,
+        created_at: DateTime::now(),
+        updated_at: DateTime::now(),
+    };
+
+    let session = db.start_session().await;
+    if let Err(why) = session {
+        info!(target:"create_role_invitation","{:?}",why);
+        return Err("trx_failed".to_string());
+    }
+    let mut session = session.unwrap();
+    let _ = session.start_transaction().await;
+
+    //only one handover can be open at a time
+    if role == EVENT_GUEST_ROLE_HOST {
+        let cancel_transfer = DB::update(COLLECTION_EVENT_ROLE_INVITATION)
+            .filter(vec![
+                is("event_id", event.id),
+                is("role", EVENT_GUEST_ROLE_HOST),
+                is("status", ROLE_INVITATION_STATUS_PENDING),
+            ])
+            .set(doc! {
+                "status":ROLE_INVITATION_STATUS_CANCELLED,
+                "updated_at":DateTime::now()
+            })
+            .execute_many_with_session(db, &mut session)
+            .await;
+        if let Err(why) = cancel_transfer {
+            info!(target:"create_role_invitation","{:?}",why);
+            let _abort = session.abort_transaction().await;
+            return Err("failed".to_string());
+        }
+    }
+
+    let save_invitation = DB::insert(COLLECTION_EVENT_ROLE_INVITATION)
+        .one_with_session(invitation.clone(), db, &mut session)
+        .await;
+    if let Err(why) = save_invitation {
+        info!(target:"create_role_invitation","{:?}",why);
+        let _abort = session.abort_transaction().await;
+        return Err("failed".to_string());
+    }
+
+    let notify = notify_event_guests(
+        db,
+        &mut session,
+        &event,
+        NOTIFICATION_TYPE_ROLE_INVITATION,
+        title,
+        vec![create_user_id],
+    )
+    .await;
+    if let Err(why) = notify {
+        info!(target:"create_role_invitation","{:?}",why);
+        let _abort = session.abort_transaction().await;
+        return Err("failed".to_string());
+    }
+    let _commit = session.commit_transaction().await;
+
+    Ok(invitation.into())
+}
+
+async fn apply_co_host(
+    db: &Client,
+    session: &mut ClientSession,
+    event: &EventDTO,
+    user_id: ObjectId,
+) -> Result<(), String> {
+    let set_role = set_guest_role(db, session, event.id, user_id, EVENT_GUEST_ROLE_CO_HOST).await;
+    if let Err(why) = set_role {
+        info!(target:"apply_co_host","{}",why);
+        return Err("failed".to_string());
+    }
+
+    let activity = EventActivity::create(
+        event.id,
+        Some(user_id),
+        event.id,
+        EVENT_ACTIVITY_GUEST_ADDED,
+        Some(json!({ "role": EVENT_GUEST_ROLE_CO_HOST })),
+    );
+    let save_activity = DB::insert(COLLECTION_EVENT_ACTIVITY)
+        .one_with_session(activity, db, session)
+        .await;
+    if let Err(why) = save_activity {
+        info!(target:"apply_co_host","{:?}",why);
+        return Err("failed".to_string());
+    }
+    Ok(())
+}
+
+//host_id and guest roles move together, the previous owner stay on as co-host
+//and every other host role is demoted so the event keep exactly one host
+async fn apply_host_transfer(
+    db: &Client,
+    session: &mut ClientSession,
+    event: &EventDTO,
+    user_id: ObjectId,
+) -> Result<(), String> {
+    if let None = event.host_id {
+        return Err("event_not_found".to_string());
+    }
+    let previous_host_id = event.host_id.unwrap();
+
+    let update_event = DB::update(COLLECTION_EVENTS)
+        .filter(vec![is("_id", event.id), is("host_id", previous_host_id)])
+        .set(doc! {
+            "host_id":user_id,
+            "updated_at":DateTime::now()
+        })
+        .execute_with_session(db, session)
+        .await;
+    if let Err(why) = update_event {
+        info!(target:"apply_host_transfer","{:?}",why);
+        return Err("failed".to_string());
+    }
+    if update_event.unwrap() == 0 {
+        return Err("invitation_outdated".to_string());
+    }
+
+    let demote_host = DB::update(COLLECTION_EVENT_GUEST)
+        .filter(vec![
+            is("event_id", event.id),
+            is("role", EVENT_GUEST_ROLE_HOST),
+            not_equal("user_id", user_id),
+        ])
+        .set(doc! {
+            "role":EVENT_GUEST_ROLE_CO_HOST,
+            "updated_at":DateTime::now()
+        })
+        .execute_many_with_session(db, session)
+        .await;
+    if let Err(why) = demote_host {
+        info!(target:"apply_host_transfer","{:?}",why);
+        return Err("failed".to_string());
+    }
+
+    let set_previous =
+        set_guest_role(db, session, event.id, previous_host_id, EVENT_GUEST_ROLE_CO_HOST).await;
+    if let Err(why) = set_previous {
+        info!(target:"apply_host_transfer","{}",why);
+        return Err("failed".to_string());
+    }
+
+    let set_host = set_guest_role(db, session, event.id, user_id, EVENT_GUEST_ROLE_HOST).await;
+    if let Err(why) = set_host {
+        info!(target:"apply_host_transfer","{}",why);
+        return Err("failed".to_string());
+    }
+
+    let activity = EventActivity::create(
+        event.id,
+        Some(user_id),
+        event.id,
+        EVENT_ACTIVITY_HOST_TRANSFERRED,
+        Some(json!({ "from": previous_host_id.to_string(), "to": user_id.to_string() })),
+    );
+    let save_activity = DB::insert(COLLECTION_EVENT_ACTIVITY)
+        .one_with_session(activity, db, session)
+        .await;
+    if let Err(why) = save_activity {
+        info!(target:"apply_host_transfer","{:?}",why);
+        return Err("failed".to_string());
+    }
+    Ok(())
+}
+
+//keep rsvp and check in when the user already has a guest row
+async fn set_guest_role(
+    db: &Client,
+    session: &mut ClientSession,
+    event_id: Option<ObjectId>,
+    user_id: ObjectId,
+    role: &str,
+) -> Result<(), String> {
+    let find_guest = DB::get(COLLECTION_EVENT_GUEST)
+        .filter(vec![is("event_id", event_id), is("user_id", user_id)])
+        .get_one::<EventGuestDTO>(db)
+        .await;
+    if let Ok(guest) = find_guest {
+        return DB::update(COLLECTION_EVENT_GUEST)
+            .filter(vec![is("_id", guest.id)])
+            .set(doc! {
+                "role":role,
+                "updated_at":DateTime::now()
+            })
+            .execute_with_session(db, session)
+            .await
+            .map(|_| ());
+    }
+
+    DB::insert(COLLECTION_EVENT_GUEST)
+        .one_with_session(
+            EventGuest {
+                id: Some(ObjectId::new()),
+                user_id: Some(user_id),
+                event_id,
+                role: role.to_string(),
+                rsvp: None,
+                checked_in_at: None,
+                created_at: DateTime::now(),
+                updated_at: DateTime::now(),
+            },
+            db,
+            session,
+        )
+        .await
+        .map(|_| ())
+}
@@ -1,16 +1,33 @@
 use serde::{Deserialize, Serialize};
 use validator::Validate;
 
+use crate::common::utils::validate_invitation_role;
+
 #[derive(Serialize, Deserialize, Validate)]
 pub struct CreateInvitationLinkRequest {
     pub expired_at: String,
     pub event_id: String,
+    #[validate(range(min = 1))]
+    pub max_uses: Option<i64>,
+    #[validate(custom(function = "validate_invitation_role"))]
+    pub default_role: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Validate)]
 pub struct SendInvitationRequest {
     pub event_id: String,
+    #[serde(default)]
     pub users: Vec<InvitationRequest>,
+    //people not on the app yet, delivered over whatsapp
+    #[serde(default)]
+    pub phone_numbers: Vec<PhoneInvitationRequest>,
+}
+
+#[derive(Serialize, Deserialize, Validate)]
+pub struct PhoneInvitationRequest {
+    pub expired_at: String,
+    #[validate(length(min = 4))]
+    pub phone_number: String,
 }
 #[derive(Serialize, Deserialize, Validate)]
 pub struct InvitationRequest {
@@ -1,34 +1,47 @@
 use std::collections::HashMap;
 use crate::common::api_response::ApiResponse;
 use crate::common::app_state::AppState;
-use crate::common::constant::{COLLECTION_EVENT_GUEST, COLLECTION_EVENT_IMAGES, COLLECTION_EVENT_INVITATION, COLLECTION_EVENTS, COLLECTION_USERS, EVENT_GUEST_ROLE_CO_HOST, EVENT_GUEST_ROLE_HOST, INVITATION_TYPE_PUBLIC, INVITATION_TYPE_USER, NOTIFICATION_TYPE_INVITATION, REDIS_KEY_USER_DISPLAY_NAME, REDIS_KEY_USER_ID, COLLECTION_NOTIFICATION, COLLECTION_NOTIFICATION_LOG};
+use crate::common::constant::{COLLECTION_EVENT_ACTIVITY, COLLECTION_EVENT_IMAGES, COLLECTION_EVENT_INVITATION, COLLECTION_EVENTS, COLLECTION_USERS, EVENT_GUEST_ROLE_CO_HOST, EVENT_GUEST_ROLE_GUEST, INVITATION_TYPE_PUBLIC, INVITATION_TYPE_USER, INVITATION_TYPE_PHONE, DEFAULT_ID_NON_MEMBER, INVITATION_PHONE_RATE_LIMIT, INVITATION_PHONE_RATE_WINDOW_SECONDS, NOTIFICATION_TYPE_INVITATION, REDIS_KEY_USER_DISPLAY_NAME, REDIS_KEY_USER_ID, COLLECTION_NOTIFICATION, COLLECTION_NOTIFICATION_LOG, EVENT_ACTIVITY_INVITATION_SENT};
+use crate::common::env_config::EnvConfig;
+use crate::common::ical::ICalendar;
 use crate::common::jwt::AuthContext;
 use crate::common::lang::Lang;
 use crate::common::middleware::Json;
 use crate::common::mongo::DB;
 use crate::common::mongo::filter::{is, is_in};
 use crate::common::mongo::lookup::one;
+use crate::common::smtp::SmtpClient;
+use crate::common::wa;
 use crate::common::utils::{
-    create_object_id_option, generate_member_code, string_to_bson_datetime,
+    create_object_id_option, generate_member_code, normalize_phone_number, phone_number_variants,
+    string_to_bson_datetime,
 };
 use crate::dto::event_dto::EventDTO;
-use crate::dto::event_guest_dto::EventGuestDTO;
 use crate::dto::event_invitation_dto::EventInvitationDTO;
+use crate::dto::user_dto::UserDTO;
 use crate::entity::event::Event;
+use crate::entity::event_activity::EventActivity;
 use crate::entity::event_invitation::EventInvitation;
 use crate::entity::notification::Notification;
 use crate::entity::notification_log::NotificationLog;
 use crate::feature::invitation::invitation_model::{
-    CreateInvitationLinkRequest, SendInvitationRequest,
+    CreateInvitationLinkRequest, InvitationRequest, SendInvitationRequest,
 };
+use crate::feature::event::event_link::generate_invitation_code;
+use crate::feature::event::present_event;
 use crate::i18n;
 use axum::extract::{Path, State};
-use bson::DateTime;
+use crate::feature::event::event_policy::is_event_host;
+use crate::feature::mutual::get_blocked_user_ids;
 use bson::oid::ObjectId;
+use bson::{Bson, DateTime, doc};
 use log::info;
+use mongodb::{Client, ClientSession};
+use validator::Validate;
+use serde_json::json;
 use std::fmt::format;
 
-mod invitation_model;
+pub mod invitation_model;
 
 pub async fn create_invitation_link(
     state: State<AppState>,
@@ -38,6 +51,13 @@ pub async fn create_invitation_link(
 ) -> ApiResponse<EventInvitationDTO> {
     info!(target:"create_invitation_link","start");
     let i18n = i18n!("invitation", lang);
+    if let Err(err) = body.validate() {
+        info!(target:"create_invitation_link","{:?}", err);
+        return ApiResponse::error_validation(
+            err,
+            &i18n.translate("create_invitation_link.validation_error"),
+        );
+    }
 
     if let None = auth_context.get_user_id() {
         info!(target:"create_invitation_link","user id not found");
@@ -51,22 +71,30 @@ pub async fn create_invitation_link(
         info!(target:"create_invitation_link","create_event_id none");
         return ApiResponse::not_found(&i18n.translate("create_invitation_link.event_id_invalid"));
     }
-    let find_event = DB::get(COLLECTION_EVENT_GUEST)
-        .filter(vec![
-            is("event_id", create_event_id.unwrap()),
-            is("user_id", auth_context.get_user_id().unwrap()),
-            is_in("role", vec![EVENT_GUEST_ROLE_HOST, EVENT_GUEST_ROLE_CO_HOST]),
-        ])
-        .lookup(&[
-            one(COLLECTION_EVENTS, "event_id", "_id", "event"),
-            one(COLLECTION_USERS, "user_id", "_id", "user"),
-        ])
-        .get_one::<EventGuestDTO>(&state.db)
+    let find_event = DB::get(COLLECTION_EVENTS)
+        .filter(vec![is("_id", create_event_id.unwrap())])
+        .get_one::<EventDTO>(&state.db)
         .await;
     if let Err(err) = find_event {
-        info!(target:"create_invitation_link","Error finding event host or user is guest: {:?}",err);
+        info!(target:"create_invitation_link","Error finding event: {:?}",err);
         return ApiResponse::not_found(&i18n.translate("create_invitation_link.invalid_role"));
     }
+    let event = find_event.unwrap();
+    let current_user_id = auth_context.get_user_id().unwrap();
+    if !is_event_host(&state.db, &event, current_user_id).await {
+        info!(target:"create_invitation_link","user is guest");
+        return ApiResponse::not_found(&i18n.translate("create_invitation_link.invalid_role"));
+    }
+
+    //a co-host link skip the acceptance step, so only the owner can hand it out
+    let default_role = body
+        .default_role
+        .clone()
+        .unwrap_or(EVENT_GUEST_ROLE_GUEST.to_string());
+    if default_role == EVENT_GUEST_ROLE_CO_HOST && event.host_id != Some(current_user_id) {
+        info!(target:"create_invitation_link","only owner can create co-host link");
+        return ApiResponse::access_denied(&i18n.translate("create_invitation_link.not_owner"));
+    }
 
     let expired_at =
         string_to_bson_datetime(body.expired_at.clone()).map_or_else(|_| None, |value| Some(value));
@@ -76,8 +104,14 @@ pub async fn create_invitation_link(
         user_id: None,
         event_id: create_event_id,
         invitation_type: INVITATION_TYPE_PUBLIC.to_string(),
-        invitation_code: ObjectId::new().to_string(),
+        invitation_code: generate_invitation_code(&state.db, &Vec::new()).await,
+        phone_number: None,
         expires_at: expired_at,
+        max_uses: body.max_uses,
+        default_role: Some(default_role),
+        open_count: 0,
+        redemption_count: 0,
+        revoked_at: None,
         created_at: DateTime::now(),
         updated_at: DateTime::now(),
     };
@@ -99,7 +133,7 @@ pub async fn create_invitation_link(
 }
 
 pub async fn send_invitation(
-    state: State<AppState>,
+    mut state: State<AppState>,
     lang: Lang,
     auth_context: AuthContext,
     Json(body): Json<SendInvitationRequest>,
@@ -120,6 +154,7 @@ pub async fn send_invitation(
 
     let find_event = DB::get(COLLECTION_EVENTS)
         .filter(vec![is("_id", create_event_id.unwrap())])
+        .lookup(&[one(COLLECTION_USERS, "host_id", "_id", "host")])
         .get_one::<EventDTO>(&state.db)
         .await;
 
@@ -129,6 +164,11 @@ pub async fn send_invitation(
     }
     let event= find_event.unwrap();
 
+    if !is_event_host(&state.db, &event, auth_context.get_user_id().unwrap()).await {
+        info!(target:"send_invitation","only host can send invitation");
+        return ApiResponse::access_denied(&i18n.translate("send_invitation.not_host"));
+    }
+
     let default_display_name = i18n.translate("default_display_name");
     let mut args = HashMap::new();
     args.insert("event_name".to_string(),event.event_name.clone());
@@ -138,10 +178,81 @@ pub async fn send_invitation(
         .get(REDIS_KEY_USER_DISPLAY_NAME)
         .unwrap_or(&default_display_name);
 
-    let build_invitation = body
-        .users
+    //numbers that already belong to an account are invited as that user
+    let mut users = body.users;
+    let mut pending_phones = Vec::new();
+    for phone in body.phone_numbers {
+        let phone_number = normalize_phone_number(&phone.phone_number);
+        let find_member = DB::get(COLLECTION_USERS)
+            .filter(vec![is_in("phone_number", phone_number_variants(&phone_number))])
+            .get_one::<UserDTO>(&state.db)
+            .await;
+        match find_member {
+            Ok(member) if member.id.is_some() => users.push(InvitationRequest {
+                expired_at: phone.expired_at,
+                user_id: member.id.unwrap().to_hex(),
+            }),
+            _ => pending_phones.push((phone_number, phone.expired_at)),
+        }
+    }
+    //invitation never reach someone on either side of a block
+    let blocked_ids = get_blocked_user_ids(&state.db, auth_context.get_user_id().unwrap())
+        .await
         .into_iter()
-        .map(|user| {
+        .map(|id| id.to_hex())
+        .collect::<Vec<String>>();
+    users.retain(|user| !blocked_ids.contains(&user.user_id));
+
+    if users.is_empty() && pending_phones.is_empty() {
+        info!(target:"send_invitation","nobody to invite");
+        return ApiResponse::failed(&i18n.translate("send_invitation.empty"));
+    }
+
+    //whatsapp to numbers outside the app cost money and can be abused for spam
+    if !pending_phones.is_empty()
+        && !state.redis.hit_rate_limit_by(
+            "invitation_phone",
+            &auth_context.get_user_id_as_string(),
+            pending_phones.len() as i64,
+            INVITATION_PHONE_RATE_LIMIT,
+            INVITATION_PHONE_RATE_WINDOW_SECONDS,
+        )
+    {
+        info!(target:"send_invitation","phone invitation rate limit reached");
+        return ApiResponse::failed(&i18n.translate("send_invitation.rate_limited"));
+    }
+
+    let mut invitation_codes = Vec::new();
+    for _ in users.iter() {
+        let code = generate_invitation_code(&state.db, &invitation_codes).await;
+        invitation_codes.push(code);
+    }
+    let mut phone_invitations = Vec::new();
+    for (phone_number, expired_at) in pending_phones {
+        let code = generate_invitation_code(&state.db, &invitation_codes).await;
+        invitation_codes.push(code.clone());
+        phone_invitations.push(EventInvitation {
+            id: Some(ObjectId::new()),
+            user_id: create_object_id_option(DEFAULT_ID_NON_MEMBER),
+            event_id: event.id,
+            invitation_type: INVITATION_TYPE_PHONE.to_string(),
+            invitation_code: code,
+            phone_number: Some(phone_number),
+            expires_at: string_to_bson_datetime(expired_at).ok(),
+            max_uses: None,
+            default_role: None,
+            open_count: 0,
+            redemption_count: 0,
+            revoked_at: None,
+            created_at: DateTime::now(),
+            updated_at: DateTime::now(),
+        });
+    }
+
+    let build_invitation = users
+        .into_iter()
+        .zip(invitation_codes)
+        .map(|(user, invitation_code)| {
             let date = string_to_bson_datetime(user.expired_at.clone())
                 .map_or_else(|_| None, |value| Some(value));
 
@@ -165,6 +276,7 @@ pub async fn send_invitation(
                 ref_id: Some(create_invitation_id.clone()),
                 notification_id: notification.id.clone(),
                 user_id: create_user_id.clone(),
+                sender_id: auth_context.get_user_id(),
                 is_read: false,
                 created_at: DateTime::now(),
                 updated_at: DateTime::now(),
@@ -175,8 +287,14 @@ pub async fn send_invitation(
                 user_id: create_user_id,
                 event_id: create_event_id,
                 invitation_type: INVITATION_TYPE_USER.to_string(),
-                invitation_code: ObjectId::new().to_string(),
+                invitation_code,
+                phone_number: None,
                 expires_at: date,
+                max_uses: None,
+                default_role: None,
+                open_count: 0,
+                redemption_count: 0,
+                revoked_at: None,
                 created_at: DateTime::now(),
                 updated_at: DateTime::now(),
             };
@@ -193,7 +311,16 @@ pub async fn send_invitation(
     let _ = session.start_transaction().await;
 
     let save_invitation = DB::insert(COLLECTION_EVENT_INVITATION)
-        .many_with_session(build_invitation.clone().into_iter().map(|(_,_,invitation)|invitation).collect(), &state.db, &mut session)
+        .many_with_session(
+            build_invitation
+                .clone()
+                .into_iter()
+                .map(|(_, _, invitation)| invitation)
+                .chain(phone_invitations.clone())
+                .collect(),
+            &state.db,
+            &mut session,
+        )
         .await;
 
     if let Err(why) = save_invitation {
@@ -202,31 +329,44 @@ pub async fn send_invitation(
         return ApiResponse::failed(&i18n.translate("upload_event_image.trx_failed"));
     }
 
-    let save_invitation = DB::insert(COLLECTION_EVENT_INVITATION)
-        .many_with_session(build_invitation.clone().into_iter().map(|(_,_,invitation)|invitation).collect(), &state.db, &mut session)
-        .await;
+    //phone invitations has no account to notify yet
+    if !build_invitation.is_empty() {
+        let save_notification = DB::insert(COLLECTION_NOTIFICATION)
+            .many_with_session(build_invitation.clone().into_iter().map(|(notification,_,_)|notification).collect(), &state.db, &mut session)
+            .await;
 
-    if let Err(why) = save_invitation {
-        info!(target:"upload_event_image","{:?}",why);
-        let _abort = session.abort_transaction().await;
-        return ApiResponse::failed(&i18n.translate("upload_event_image.trx_failed"));
-    }
+        if let Err(why) = save_notification {
+            info!(target:"upload_event_image","{:?}",why);
+            let _abort = session.abort_transaction().await;
+            return ApiResponse::failed(&i18n.translate("upload_event_image.trx_failed"));
+        }
 
-    let save_notification = DB::insert(COLLECTION_NOTIFICATION)
-        .many_with_session(build_invitation.clone().into_iter().map(|(notification,_,_)|notification).collect(), &state.db, &mut session)
-        .await;
+        let save_notification_log = DB::insert(COLLECTION_NOTIFICATION_LOG)
+            .many_with_session(build_invitation.clone().into_iter().map(|(_,log,_)|log).collect(), &state.db, &mut session)
+            .await;
 
-    if let Err(why) = save_notification {
-        info!(target:"upload_event_image","{:?}",why);
-        let _abort = session.abort_transaction().await;
-        return ApiResponse::failed(&i18n.translate("upload_event_image.trx_failed"));
+        if let Err(why) = save_notification_log {
+            info!(target:"upload_event_image","{:?}",why);
+            let _abort = session.abort_transaction().await;
+            return ApiResponse::failed(&i18n.translate("upload_event_image.trx_failed"));
+        }
     }
-
-    let save_notification_log = DB::insert(COLLECTION_NOTIFICATION_LOG)
-        .many_with_session(build_invitation.clone().into_iter().map(|(_,log,_)|log).collect(), &state.db, &mut session)
+    let save_activity = DB::insert(COLLECTION_EVENT_ACTIVITY)
+        .many_with_session(build_invitation.clone().into_iter().map(|(_,_,invitation)|invitation).chain(phone_invitations.clone()).map(|invitation|{
+            EventActivity::create(
+                invitation.event_id,
+                invitation.user_id,
+                invitation.id,
+                EVENT_ACTIVITY_INVITATION_SENT,
+                Some(json!({
+                    "invited_by": auth_context.get_user_id_as_string(),
+                    "phone_number": invitation.phone_number
+                })),
+            )
+        }).collect(), &state.db, &mut session)
         .await;
 
-    if let Err(why) = save_notification_log {
+    if let Err(why) = save_activity {
         info!(target:"upload_event_image","{:?}",why);
         let _abort = session.abort_transaction().await;
         return ApiResponse::failed(&i18n.translate("upload_event_image.trx_failed"));
@@ -236,8 +376,286 @@ pub async fn send_invitation(
 
     //todo: end push
 
+    let invited_user_ids = build_invitation
+        .iter()
+        .filter_map(|(_, _, invitation)| invitation.user_id)
+        .collect::<Vec<ObjectId>>();
+    let find_invited_user = DB::get(COLLECTION_USERS)
+        .filter(vec![is_in("_id", invited_user_ids)])
+        .get_all::<UserDTO>(&state.db)
+        .await
+        .unwrap_or(Vec::new());
+
+    let mut args = HashMap::new();
+    args.insert("event_name".to_string(), event.event_name.clone());
+    args.insert("host_name".to_string(), find_user.clone());
+    let email_subject = format!("{} {}", find_user.clone(), notification_message);
+    let email_body = i18n.translate_with_args("send_invitation.email_body", args);
+    //event without schedule has nothing to put in a calendar
+    let calendar = event.datetime.map(|_| {
+        ICalendar::new(&event.event_name)
+            .set_timezone(event.timezone.clone())
+            .add_event(&present_event(event.clone(), &lang))
+            .build()
+    });
+
+    //do not hold the response for the mail delivery
+    tokio::spawn(async move {
+        for user in find_invited_user {
+            if user.email.is_empty() {
+                continue;
+            }
+            let client = SmtpClient::new(&user.email);
+            match calendar.clone() {
+                Some(calendar) => {
+                    client
+                        .send_with_attachment(
+                            &email_subject,
+                            &email_body,
+                            &"invitation.ics".to_string(),
+                            "text/calendar; charset=utf-8; method=PUBLISH",
+                            calendar.into_bytes(),
+                        )
+                        .await
+                }
+                None => client.send(&email_subject, &email_body).await,
+            }
+        }
+    });
+
+    let base_url = EnvConfig::init().base_url.trim_end_matches('/').to_string();
+    let wa_messages = phone_invitations
+        .into_iter()
+        .filter_map(|invitation| {
+            let mut args = HashMap::new();
+            args.insert("event_name".to_string(), event.event_name.clone());
+            args.insert("host_name".to_string(), find_user.clone());
+            args.insert(
+                "link".to_string(),
+                format!("{}/i/{}", base_url, invitation.invitation_code),
+            );
+            invitation
+                .phone_number
+                .map(|phone| (phone, i18n.translate_with_args("send_invitation.wa_message", args)))
+        })
+        .collect::<Vec<(String, String)>>();
+    tokio::spawn(async move {
+        for (phone, message) in wa_messages {
+            if let Err(why) = wa::send_message(phone, message).await {
+                info!(target:"send_invitation","failed send whatsapp {}",why);
+            }
+        }
+    });
+
     ApiResponse::ok(
         "OK".to_string(),
         &i18n.translate("upload_event_image.ok"),
     )
-}
\ No newline at end of file
+}
+//public links of an event with their usage, newest first
+pub async fn get_invitation_links(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Path(event_id): Path<String>,
+) -> ApiResponse<Vec<EventInvitationDTO>> {
+    info!(target:"get_invitation_links","Starting...");
+    let i18n = i18n!("invitation", lang);
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"get_invitation_links","user id not found");
+        return ApiResponse::access_denied(&i18n.translate("get_invitation_links.user_id_invalid"));
+    }
+
+    let create_event_id = create_object_id_option(&event_id);
+    if let None = create_event_id {
+        info!(target:"get_invitation_links","event id invalid");
+        return ApiResponse::failed(&i18n.translate("get_invitation_links.event_id_invalid"));
+    }
+    let create_event_id = create_event_id.unwrap();
+
+    let find_event = DB::get(COLLECTION_EVENTS)
+        .filter(vec![is("_id", create_event_id)])
+        .get_one::<EventDTO>(&state.db)
+        .await;
+    if let Err(why) = find_event {
+        info!(target:"get_invitation_links","cannot find event {}",why);
+        return ApiResponse::not_found(&i18n.translate("get_invitation_links.event_not_found"));
+    }
+
+    if !is_event_host(&state.db, &find_event.unwrap(), auth_context.get_user_id().unwrap()).await {
+        info!(target:"get_invitation_links","only host can see links");
+        return ApiResponse::access_denied(&i18n.translate("get_invitation_links.not_host"));
+    }
+
+    let find_links = DB::get(COLLECTION_EVENT_INVITATION)
+        .filter(vec![
+            is("event_id", create_event_id),
+            is("invitation_type", INVITATION_TYPE_PUBLIC),
+        ])
+        .sort(vec![("created_at", -1)])
+        .get_all::<EventInvitationDTO>(&state.db)
+        .await;
+    if let Err(why) = find_links {
+        info!(target:"get_invitation_links","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("get_invitation_links.failed"));
+    }
+
+    let links = find_links
+        .unwrap()
+        .into_iter()
+        .map(|mut link| {
+            link.conversion_rate = Some(if link.open_count > 0 {
+                link.redemption_count as f64 / link.open_count as f64
+            } else {
+                0.0
+            });
+            link
+        })
+        .collect::<Vec<EventInvitationDTO>>();
+
+    info!(target:"get_invitation_links","finish");
+    ApiResponse::ok(links, &i18n.translate("get_invitation_links.ok"))
+}
+
+//revoked link stop resolving right away, guests who already joined stay
+pub async fn revoke_invitation_link(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Path(invitation_id): Path<String>,
+) -> ApiResponse<EventInvitationDTO> {
+    info!(target:"revoke_invitation_link","Starting...");
+    let i18n = i18n!("invitation", lang);
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"revoke_invitation_link","user id not found");
+        return ApiResponse::access_denied(
+            &i18n.translate("revoke_invitation_link.user_id_invalid"),
+        );
+    }
+
+    let create_invitation_id = create_object_id_option(&invitation_id);
+    if let None = create_invitation_id {
+        info!(target:"revoke_invitation_link","invitation id invalid");
+        return ApiResponse::failed(
+            &i18n.translate("revoke_invitation_link.invitation_id_invalid"),
+        );
+    }
+
+    let find_link = DB::get(COLLECTION_EVENT_INVITATION)
+        .filter(vec![
+            is("_id", create_invitation_id.unwrap()),
+            is("invitation_type", INVITATION_TYPE_PUBLIC),
+        ])
+        .lookup(&[one(COLLECTION_EVENTS, "event_id", "_id", "event")])
+        .get_one::<EventInvitationDTO>(&state.db)
+        .await;
+    if let Err(why) = find_link {
+        info!(target:"revoke_invitation_link","cannot find link {}",why);
+        return ApiResponse::not_found(&i18n.translate("revoke_invitation_link.not_found"));
+    }
+    let mut link = find_link.unwrap();
+
+    if let None = link.event {
+        info!(target:"revoke_invitation_link","event not found");
+        return ApiResponse::not_found(&i18n.translate("revoke_invitation_link.not_found"));
+    }
+    let event = link.event.take().unwrap();
+    if !is_event_host(&state.db, &event, auth_context.get_user_id().unwrap()).await {
+        info!(target:"revoke_invitation_link","only host can revoke link");
+        return ApiResponse::access_denied(&i18n.translate("revoke_invitation_link.not_host"));
+    }
+    if link.revoked_at.is_some() {
+        info!(target:"revoke_invitation_link","already revoked");
+        return ApiResponse::ok(link, &i18n.translate("revoke_invitation_link.ok"));
+    }
+
+    let revoked_at = DateTime::now();
+    let revoke = DB::update(COLLECTION_EVENT_INVITATION)
+        .filter(vec![is("_id", link.id)])
+        .set(doc! {
+            "revoked_at":revoked_at,
+            "updated_at":revoked_at
+        })
+        .execute(&state.db)
+        .await;
+    if let Err(why) = revoke {
+        info!(target:"revoke_invitation_link","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("revoke_invitation_link.failed"));
+    }
+    link.revoked_at = Some(revoked_at);
+    link.updated_at = revoked_at;
+
+    info!(target:"revoke_invitation_link","finish");
+    ApiResponse::ok(link, &i18n.translate("revoke_invitation_link.ok"))
+}
+
+//counted every time a link is resolved, failure only cost a lost count
+pub async fn record_invitation_open(db: &Client, code: &String) {
+    let record = DB::update(COLLECTION_EVENT_INVITATION)
+        .filter(vec![
+            is("invitation_code", code.clone()),
+            is("invitation_type", INVITATION_TYPE_PUBLIC),
+        ])
+        .inc_value("open_count", 1)
+        .execute(db)
+        .await;
+    if let Err(why) = record {
+        info!(target:"record_invitation_open","{:?}",why);
+    }
+}
+
+//take one use of the link inside the join transaction. the limit is checked in the same
+//update so two users racing for the last seat cannot both get in.
+//only public link can be redeemed, the event short code or a personal code is rejected
+pub async fn redeem_invitation_link(
+    db: &Client,
+    session: &mut ClientSession,
+    event_id: Option<ObjectId>,
+    code: &String,
+) -> Result<Option<String>, String> {
+    let find_link = DB::get(COLLECTION_EVENT_INVITATION)
+        .filter(vec![
+            is("event_id", event_id),
+            is("invitation_code", code.clone()),
+            is("invitation_type", INVITATION_TYPE_PUBLIC),
+        ])
+        .get_one::<EventInvitationDTO>(db)
+        .await;
+    if let Err(_) = find_link {
+        return Err("link_invalid".to_string());
+    }
+    let link = find_link.unwrap();
+
+    let redeem = DB::update(COLLECTION_EVENT_INVITATION)
+        .filter(vec![
+            is("_id", link.id),
+            is("revoked_at", Bson::Null),
+            //filter doc are merged by key, both or group must share one $and
+            doc! { "$and": [
+                { "$or": [
+                    { "expires_at": Bson::Null },
+                    { "expires_at": { "$gt": DateTime::now() } },
+                ] },
+                { "$or": [
+                    { "max_uses": Bson::Null },
+                    { "$expr": { "$lt": ["$redemption_count", "$max_uses"] } },
+                ] },
+            ] }
+            .into(),
+        ])
+        .inc_value("redemption_count", 1)
+        .execute_with_session(db, session)
+        .await;
+    if let Err(why) = redeem {
+        return Err(why);
+    }
+    if redeem.unwrap() == 0 {
+        if link.expires_at.map_or(false, |expires_at| expires_at <= DateTime::now()) {
+            return Err("link_expired".to_string());
+        }
+        return Err("link_used_up".to_string());
+    }
+    Ok(link.default_role)
+}
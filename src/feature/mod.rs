@@ -4,3 +4,16 @@ pub mod notification;
 pub mod sse;
 pub mod user;
 pub mod invitation;
+pub mod album;
+pub mod activity;
+pub mod check_in;
+pub mod series;
+pub mod calendar;
+pub mod discovery;
+pub mod preview;
+pub mod template;
+pub mod host;
+pub mod guest;
+pub mod contact;
+pub mod mutual;
+pub mod report;
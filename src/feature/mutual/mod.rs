@@ -0,0 +1,767 @@
+use crate::common::api_response::ApiResponse;
+use crate::common::app_state::AppState;
+use crate::common::constant::{
+    COLLECTION_MUTUAL, COLLECTION_NOTIFICATION, COLLECTION_NOTIFICATION_LOG, COLLECTION_USERS,
+    MUTUAL_CO_ATTENDEE_MAX_SIZE, MUTUAL_SOURCE_EVENT, MUTUAL_STATUS_BLOCKED, MUTUAL_STATUS_FRIEND,
+    MUTUAL_STATUS_PENDING, MUTUAL_STATUS_REQUESTED, MUTUAL_STATUS_SUGGESTED,
+    NOTIFICATION_TYPE_MUTUAL_ACCEPTED, NOTIFICATION_TYPE_MUTUAL_REQUEST,
+    REDIS_KEY_USER_DISPLAY_NAME,
+};
+use crate::common::jwt::AuthContext;
+use crate::common::lang::Lang;
+use crate::common::mongo::DB;
+use crate::common::mongo::filter::{FilterGroup, is, is_in, not_equal, or};
+use crate::common::mongo::lookup::one;
+use crate::common::utils::create_object_id_option;
+use crate::dto::mutual_dto::MutualDTO;
+use crate::dto::user_dto::UserDTO;
+use crate::entity::mutual::Mutual;
+use crate::entity::notification::Notification;
+use crate::entity::notification_log::NotificationLog;
+use crate::feature::mutual::mutual_model::MutualChangeResponse;
+use crate::i18n;
+use axum::extract::{Path, State};
+use bson::oid::ObjectId;
+use bson::{Bson, DateTime, doc};
+use log::info;
+use mongodb::{Client, ClientSession};
+use std::collections::HashMap;
+
+pub mod mutual_model;
+
+pub async fn send_mutual_request(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Path(user_id): Path<String>,
+) -> ApiResponse<MutualChangeResponse> {
+    info!(target:"send_mutual_request","Starting...");
+    let i18n = i18n!("mutual", lang);
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"send_mutual_request","user id not found");
+        return ApiResponse::access_denied(&i18n.translate("send_mutual_request.user_id_not_found"));
+    }
+    let current_user_id = auth_context.get_user_id().unwrap();
+
+    let create_user_id = create_object_id_option(&user_id);
+    if let None = create_user_id {
+        info!(target:"send_mutual_request","target id invalid");
+        return ApiResponse::failed(&i18n.translate("send_mutual_request.user_id_invalid"));
+    }
+    let create_user_id = create_user_id.unwrap();
+    if create_user_id == current_user_id {
+        info!(target:"send_mutual_request","cannot request self");
+        return ApiResponse::failed(&i18n.translate("send_mutual_request.cannot_self"));
+    }
+
+    let find_target = DB::get(COLLECTION_USERS)
+        .filter(vec![is("_id", create_user_id)])
+        .get_one::<UserDTO>(&state.db)
+        .await;
+    if let Err(why) = find_target {
+        info!(target:"send_mutual_request","target not found {}",why);
+        return ApiResponse::not_found(&i18n.translate("send_mutual_request.user_not_found"));
+    }
+    let target = find_target.unwrap();
+
+    if is_blocked_between(&state.db, current_user_id, create_user_id).await {
+        info!(target:"send_mutual_request","one side blocked the other");
+        return ApiResponse::access_denied(&i18n.translate("send_mutual_request.blocked"));
+    }
+
+    let current_status = find_relation(&state.db, current_user_id, create_user_id)
+        .await
+        .and_then(|mutual| mutual.status);
+    //they already asked, sending back is the same as accepting
+    let (my_status, their_status) = match current_status.as_deref() {
+        Some(MUTUAL_STATUS_FRIEND) => {
+            info!(target:"send_mutual_request","already friend");
+            return ApiResponse::failed(&i18n.translate("send_mutual_request.already_friend"));
+        }
+        Some(MUTUAL_STATUS_REQUESTED) => {
+            info!(target:"send_mutual_request","already requested");
+            return ApiResponse::failed(&i18n.translate("send_mutual_request.already_requested"));
+        }
+        Some(MUTUAL_STATUS_PENDING) => (MUTUAL_STATUS_FRIEND, MUTUAL_STATUS_FRIEND),
+        _ => (MUTUAL_STATUS_REQUESTED, MUTUAL_STATUS_PENDING),
+    };
+
+    let default_display_name = i18n.translate("default_display_name");
+    let display_name = auth_context
+        .get(REDIS_KEY_USER_DISPLAY_NAME)
+        .unwrap_or(&default_display_name)
+        .clone();
+
+    let session = state.db.start_session().await;
+    if let Err(why) = session {
+        info!(target:"send_mutual_request","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("send_mutual_request.failed"));
+    }
+    let mut session = session.unwrap();
+    let _ = session.start_transaction().await;
+
+    let save_relation = set_relation_pair(
+        &state.db,
+        &mut session,
+        (current_user_id, &display_name, my_status),
+        (create_user_id, &target.display_name, their_status),
+    )
+    .await;
+    if let Err(why) = save_relation {
+        info!(target:"send_mutual_request","{:?}",why);
+        let _ = session.abort_transaction().await;
+        return ApiResponse::failed(&i18n.translate("send_mutual_request.failed"));
+    }
+
+    let mut args = HashMap::new();
+    args.insert("display_name".to_string(), display_name.clone());
+    let (kind, title) = if my_status == MUTUAL_STATUS_FRIEND {
+        (
+            NOTIFICATION_TYPE_MUTUAL_ACCEPTED,
+            i18n.translate_with_args("accept_mutual_request.notification", args),
+        )
+    } else {
+        (
+            NOTIFICATION_TYPE_MUTUAL_REQUEST,
+            i18n.translate_with_args("send_mutual_request.notification", args),
+        )
+    };
+    let notify = notify_user(&state.db, &mut session, kind, title, current_user_id, create_user_id).await;
+    if let Err(why) = notify {
+        info!(target:"send_mutual_request","{:?}",why);
+        let _ = session.abort_transaction().await;
+        return ApiResponse::failed(&i18n.translate("send_mutual_request.failed"));
+    }
+    let _ = session.commit_transaction().await;
+
+    info!(target:"send_mutual_request","finish");
+    ApiResponse::ok(
+        MutualChangeResponse {
+            user_id,
+            status: Some(my_status.to_string()),
+        },
+        &i18n.translate("send_mutual_request.ok"),
+    )
+}
+
+pub async fn accept_mutual_request(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Path(user_id): Path<String>,
+) -> ApiResponse<MutualChangeResponse> {
+    info!(target:"accept_mutual_request","Starting...");
+    let i18n = i18n!("mutual", lang);
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"accept_mutual_request","user id not found");
+        return ApiResponse::access_denied(&i18n.translate("accept_mutual_request.user_id_not_found"));
+    }
+    let current_user_id = auth_context.get_user_id().unwrap();
+
+    let create_user_id = create_object_id_option(&user_id);
+    if let None = create_user_id {
+        info!(target:"accept_mutual_request","target id invalid");
+        return ApiResponse::failed(&i18n.translate("accept_mutual_request.user_id_invalid"));
+    }
+    let create_user_id = create_user_id.unwrap();
+
+    let find_request = find_relation(&state.db, current_user_id, create_user_id).await;
+    if find_request.as_ref().and_then(|mutual| mutual.status.clone())
+        != Some(MUTUAL_STATUS_PENDING.to_string())
+    {
+        info!(target:"accept_mutual_request","no pending request");
+        return ApiResponse::not_found(&i18n.translate("accept_mutual_request.request_not_found"));
+    }
+    let request = find_request.unwrap();
+
+    let default_display_name = i18n.translate("default_display_name");
+    let display_name = auth_context
+        .get(REDIS_KEY_USER_DISPLAY_NAME)
+        .unwrap_or(&default_display_name)
+        .clone();
+
+    let session = state.db.start_session().await;
+    if let Err(why) = session {
+        info!(target:"accept_mutual_request","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("accept_mutual_request.failed"));
+    }
+    let mut session = session.unwrap();
+    let _ = session.start_transaction().await;
+
+    let save_relation = set_relation_pair(
+        &state.db,
+        &mut session,
+        (current_user_id, &display_name, MUTUAL_STATUS_FRIEND),
+        (
+            create_user_id,
+            &request.display_name.unwrap_or(default_display_name.clone()),
+            MUTUAL_STATUS_FRIEND,
+        ),
+    )
+    .await;
+    if let Err(why) = save_relation {
+        info!(target:"accept_mutual_request","{:?}",why);
+        let _ = session.abort_transaction().await;
+        return ApiResponse::failed(&i18n.translate("accept_mutual_request.failed"));
+    }
+
+    let mut args = HashMap::new();
+    args.insert("display_name".to_string(), display_name);
+    let notify = notify_user(
+        &state.db,
+        &mut session,
+        NOTIFICATION_TYPE_MUTUAL_ACCEPTED,
+        i18n.translate_with_args("accept_mutual_request.notification", args),
+        current_user_id,
+        create_user_id,
+    )
+    .await;
+    if let Err(why) = notify {
+        info!(target:"accept_mutual_request","{:?}",why);
+        let _ = session.abort_transaction().await;
+        return ApiResponse::failed(&i18n.translate("accept_mutual_request.failed"));
+    }
+    let _ = session.commit_transaction().await;
+
+    info!(target:"accept_mutual_request","finish");
+    ApiResponse::ok(
+        MutualChangeResponse {
+            user_id,
+            status: Some(MUTUAL_STATUS_FRIEND.to_string()),
+        },
+        &i18n.translate("accept_mutual_request.ok"),
+    )
+}
+
+pub async fn decline_mutual_request(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Path(user_id): Path<String>,
+) -> ApiResponse<MutualChangeResponse> {
+    info!(target:"decline_mutual_request","Starting...");
+    let i18n = i18n!("mutual", lang);
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"decline_mutual_request","user id not found");
+        return ApiResponse::access_denied(&i18n.translate("decline_mutual_request.user_id_not_found"));
+    }
+    let current_user_id = auth_context.get_user_id().unwrap();
+
+    let create_user_id = create_object_id_option(&user_id);
+    if let None = create_user_id {
+        info!(target:"decline_mutual_request","target id invalid");
+        return ApiResponse::failed(&i18n.translate("decline_mutual_request.user_id_invalid"));
+    }
+    let create_user_id = create_user_id.unwrap();
+
+    let current_status = find_relation(&state.db, current_user_id, create_user_id)
+        .await
+        .and_then(|mutual| mutual.status);
+    if current_status != Some(MUTUAL_STATUS_PENDING.to_string()) {
+        info!(target:"decline_mutual_request","no pending request");
+        return ApiResponse::not_found(&i18n.translate("decline_mutual_request.request_not_found"));
+    }
+
+    let delete_relation = delete_relation_pair(
+        &state.db,
+        current_user_id,
+        create_user_id,
+        vec![MUTUAL_STATUS_PENDING, MUTUAL_STATUS_REQUESTED],
+    )
+    .await;
+    if let Err(why) = delete_relation {
+        info!(target:"decline_mutual_request","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("decline_mutual_request.failed"));
+    }
+
+    info!(target:"decline_mutual_request","finish");
+    ApiResponse::ok(
+        MutualChangeResponse { user_id, status: None },
+        &i18n.translate("decline_mutual_request.ok"),
+    )
+}
+
+//unfriend, cancel an outgoing request or dismiss a suggestion
+pub async fn remove_mutual(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Path(user_id): Path<String>,
+) -> ApiResponse<MutualChangeResponse> {
+    info!(target:"remove_mutual","Starting...");
+    let i18n = i18n!("mutual", lang);
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"remove_mutual","user id not found");
+        return ApiResponse::access_denied(&i18n.translate("remove_mutual.user_id_not_found"));
+    }
+    let current_user_id = auth_context.get_user_id().unwrap();
+
+    let create_user_id = create_object_id_option(&user_id);
+    if let None = create_user_id {
+        info!(target:"remove_mutual","target id invalid");
+        return ApiResponse::failed(&i18n.translate("remove_mutual.user_id_invalid"));
+    }
+    let create_user_id = create_user_id.unwrap();
+
+    let current_status = find_relation(&state.db, current_user_id, create_user_id)
+        .await
+        .and_then(|mutual| mutual.status.or(Some(MUTUAL_STATUS_FRIEND.to_string())));
+    let delete_relation = match current_status.as_deref() {
+        None | Some(MUTUAL_STATUS_BLOCKED) | Some(MUTUAL_STATUS_PENDING) => {
+            info!(target:"remove_mutual","nothing to remove {:?}",current_status);
+            return ApiResponse::not_found(&i18n.translate("remove_mutual.not_found"));
+        }
+        //a suggestion only belong to the caller side
+        Some(MUTUAL_STATUS_SUGGESTED) => DB::delete(COLLECTION_MUTUAL)
+            .filter(vec![
+                is("user_id", current_user_id),
+                is("mutual_id", create_user_id),
+                is("status", MUTUAL_STATUS_SUGGESTED),
+            ])
+            .one(&state.db)
+            .await,
+        Some(_) => {
+            delete_relation_pair(
+                &state.db,
+                current_user_id,
+                create_user_id,
+                vec![MUTUAL_STATUS_FRIEND, MUTUAL_STATUS_REQUESTED, MUTUAL_STATUS_PENDING],
+            )
+            .await
+        }
+    };
+    if let Err(why) = delete_relation {
+        info!(target:"remove_mutual","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("remove_mutual.failed"));
+    }
+
+    info!(target:"remove_mutual","finish");
+    ApiResponse::ok(
+        MutualChangeResponse { user_id, status: None },
+        &i18n.translate("remove_mutual.ok"),
+    )
+}
+
+//blocking drop whatever relation the other side had, their block on us stay
+pub async fn block_user(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Path(user_id): Path<String>,
+) -> ApiResponse<MutualChangeResponse> {
+    info!(target:"block_user","Starting...");
+    let i18n = i18n!("mutual", lang);
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"block_user","user id not found");
+        return ApiResponse::access_denied(&i18n.translate("block_user.user_id_not_found"));
+    }
+    let current_user_id = auth_context.get_user_id().unwrap();
+
+    let create_user_id = create_object_id_option(&user_id);
+    if let None = create_user_id {
+        info!(target:"block_user","target id invalid");
+        return ApiResponse::failed(&i18n.translate("block_user.user_id_invalid"));
+    }
+    let create_user_id = create_user_id.unwrap();
+    if create_user_id == current_user_id {
+        info!(target:"block_user","cannot block self");
+        return ApiResponse::failed(&i18n.translate("block_user.cannot_self"));
+    }
+
+    let find_target = DB::get(COLLECTION_USERS)
+        .filter(vec![is("_id", create_user_id)])
+        .get_one::<UserDTO>(&state.db)
+        .await;
+    if let Err(why) = find_target {
+        info!(target:"block_user","target not found {}",why);
+        return ApiResponse::not_found(&i18n.translate("block_user.user_not_found"));
+    }
+    let target = find_target.unwrap();
+
+    let session = state.db.start_session().await;
+    if let Err(why) = session {
+        info!(target:"block_user","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("block_user.failed"));
+    }
+    let mut session = session.unwrap();
+    let _ = session.start_transaction().await;
+
+    let save_block = set_relation(
+        &state.db,
+        &mut session,
+        current_user_id,
+        create_user_id,
+        &target.display_name,
+        MUTUAL_STATUS_BLOCKED,
+    )
+    .await;
+    if let Err(why) = save_block {
+        info!(target:"block_user","{:?}",why);
+        let _ = session.abort_transaction().await;
+        return ApiResponse::failed(&i18n.translate("block_user.failed"));
+    }
+
+    let delete_other_side = DB::delete(COLLECTION_MUTUAL)
+        .filter(vec![
+            is("user_id", create_user_id),
+            is("mutual_id", current_user_id),
+            not_equal("status", MUTUAL_STATUS_BLOCKED),
+        ])
+        .many_with_session(&state.db, &mut session)
+        .await;
+    if let Err(why) = delete_other_side {
+        info!(target:"block_user","{:?}",why);
+        let _ = session.abort_transaction().await;
+        return ApiResponse::failed(&i18n.translate("block_user.failed"));
+    }
+    let _ = session.commit_transaction().await;
+
+    info!(target:"block_user","finish");
+    ApiResponse::ok(
+        MutualChangeResponse {
+            user_id,
+            status: Some(MUTUAL_STATUS_BLOCKED.to_string()),
+        },
+        &i18n.translate("block_user.ok"),
+    )
+}
+
+pub async fn unblock_user(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Path(user_id): Path<String>,
+) -> ApiResponse<MutualChangeResponse> {
+    info!(target:"unblock_user","Starting...");
+    let i18n = i18n!("mutual", lang);
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"unblock_user","user id not found");
+        return ApiResponse::access_denied(&i18n.translate("unblock_user.user_id_not_found"));
+    }
+
+    let create_user_id = create_object_id_option(&user_id);
+    if let None = create_user_id {
+        info!(target:"unblock_user","target id invalid");
+        return ApiResponse::failed(&i18n.translate("unblock_user.user_id_invalid"));
+    }
+
+    let delete_block = DB::delete(COLLECTION_MUTUAL)
+        .filter(vec![
+            is("user_id", auth_context.get_user_id().unwrap()),
+            is("mutual_id", create_user_id.unwrap()),
+            is("status", MUTUAL_STATUS_BLOCKED),
+        ])
+        .one(&state.db)
+        .await;
+    if let Err(why) = delete_block {
+        info!(target:"unblock_user","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("unblock_user.failed"));
+    }
+    if delete_block.unwrap() == 0 {
+        info!(target:"unblock_user","user is not blocked");
+        return ApiResponse::not_found(&i18n.translate("unblock_user.not_found"));
+    }
+
+    info!(target:"unblock_user","finish");
+    ApiResponse::ok(
+        MutualChangeResponse { user_id, status: None },
+        &i18n.translate("unblock_user.ok"),
+    )
+}
+
+pub async fn get_mutual_requests(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+) -> ApiResponse<Vec<MutualDTO>> {
+    get_my_relations(state, lang, auth_context, "get_mutual_requests", MUTUAL_STATUS_PENDING).await
+}
+
+pub async fn get_mutual_suggestions(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+) -> ApiResponse<Vec<MutualDTO>> {
+    get_my_relations(state, lang, auth_context, "get_mutual_suggestions", MUTUAL_STATUS_SUGGESTED).await
+}
+
+pub async fn get_blocked_users(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+) -> ApiResponse<Vec<MutualDTO>> {
+    get_my_relations(state, lang, auth_context, "get_blocked_users", MUTUAL_STATUS_BLOCKED).await
+}
+
+async fn get_my_relations(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    target: &str,
+    status: &str,
+) -> ApiResponse<Vec<MutualDTO>> {
+    info!(target:"get_my_relations","Starting... {}",target);
+    let i18n = i18n!("mutual", lang);
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"get_my_relations","user id not found");
+        return ApiResponse::access_denied(&i18n.translate(&format!("{}.user_id_not_found", target)));
+    }
+
+    let find_relations = DB::get(COLLECTION_MUTUAL)
+        .filter(vec![
+            is("user_id", auth_context.get_user_id().unwrap()),
+            is("status", status),
+        ])
+        .lookup(&[one(COLLECTION_USERS, "mutual_id", "_id", "mutual")])
+        .sort(vec![("updated_at", -1)])
+        .get_all::<MutualDTO>(&state.db)
+        .await;
+    if let Err(why) = find_relations {
+        info!(target:"get_my_relations","{:?}",why);
+        return ApiResponse::failed(&i18n.translate(&format!("{}.failed", target)));
+    }
+
+    info!(target:"get_my_relations","finish");
+    ApiResponse::ok(find_relations.unwrap(), &i18n.translate(&format!("{}.ok", target)))
+}
+
+//friends made before status existed have no status at all
+pub fn is_friend() -> FilterGroup {
+    is_in(
+        "status",
+        vec![Bson::String(MUTUAL_STATUS_FRIEND.to_string()), Bson::Null],
+    )
+}
+
+pub async fn is_blocked_between(db: &Client, user_id: ObjectId, other_id: ObjectId) -> bool {
+    DB::get(COLLECTION_MUTUAL)
+        .filter(vec![
+            is("status", MUTUAL_STATUS_BLOCKED),
+            or(&[
+                doc! {"user_id":user_id,"mutual_id":other_id}.into(),
+                doc! {"user_id":other_id,"mutual_id":user_id}.into(),
+            ]),
+        ])
+        .count(db)
+        .await
+        .unwrap_or(0)
+        > 0
+}
+
+//both who the user blocked and who blocked the user, they should not reach each other
+pub async fn get_blocked_user_ids(db: &Client, user_id: ObjectId) -> Vec<ObjectId> {
+    DB::get(COLLECTION_MUTUAL)
+        .filter(vec![
+            is("status", MUTUAL_STATUS_BLOCKED),
+            or(&[is("user_id", user_id), is("mutual_id", user_id)]),
+        ])
+        .get_all::<MutualDTO>(db)
+        .await
+        .unwrap_or(Vec::new())
+        .into_iter()
+        .filter_map(|mutual| {
+            if mutual.user_id == Some(user_id) {
+                mutual.mutual_id
+            } else {
+                mutual.user_id
+            }
+        })
+        .collect::<Vec<ObjectId>>()
+}
+
+//people who went to the same event see each other as suggestion, existing relation is left alone
+pub async fn suggest_co_attendees(
+    db: &Client,
+    session: &mut ClientSession,
+    user_ids: Vec<ObjectId>,
+) -> Result<(), String> {
+    let mut user_ids = user_ids;
+    user_ids.sort();
+    user_ids.dedup();
+    user_ids.truncate(MUTUAL_CO_ATTENDEE_MAX_SIZE);
+    if user_ids.len() < 2 {
+        return Ok(());
+    }
+
+    let known_relations = DB::get(COLLECTION_MUTUAL)
+        .filter(vec![
+            is_in("user_id", user_ids.clone()),
+            is_in("mutual_id", user_ids.clone()),
+        ])
+        .get_all::<MutualDTO>(db)
+        .await
+        .unwrap_or(Vec::new());
+    let known_pairs = known_relations
+        .iter()
+        .map(|mutual| (mutual.user_id, mutual.mutual_id))
+        .collect::<Vec<(Option<ObjectId>, Option<ObjectId>)>>();
+    let display_names = DB::get(COLLECTION_USERS)
+        .filter(vec![is_in("_id", user_ids.clone())])
+        .get_all::<UserDTO>(db)
+        .await
+        .unwrap_or(Vec::new())
+        .into_iter()
+        .filter_map(|user| user.id.map(|id| (id, user.display_name)))
+        .collect::<HashMap<ObjectId, String>>();
+
+    //a blocked pair on either side must not come back as suggestion
+    let blocked_pairs = known_relations
+        .iter()
+        .filter(|mutual| mutual.status == Some(MUTUAL_STATUS_BLOCKED.to_string()))
+        .map(|mutual| (mutual.mutual_id, mutual.user_id))
+        .collect::<Vec<(Option<ObjectId>, Option<ObjectId>)>>();
+
+    let mut suggestions = Vec::new();
+    for user_id in user_ids.iter() {
+        for mutual_id in user_ids.iter() {
+            let pair = (Some(*user_id), Some(*mutual_id));
+            if user_id == mutual_id || known_pairs.contains(&pair) || blocked_pairs.contains(&pair) {
+                continue;
+            }
+            suggestions.push(Mutual {
+                id: Some(ObjectId::new()),
+                user_id: Some(*user_id),
+                mutual_id: Some(*mutual_id),
+                display_name: display_names.get(mutual_id).cloned(),
+                status: Some(MUTUAL_STATUS_SUGGESTED.to_string()),
+                source: Some(MUTUAL_SOURCE_EVENT.to_string()),
+                created_at: DateTime::now(),
+                updated_at: DateTime::now(),
+            });
+        }
+    }
+    if suggestions.is_empty() {
+        return Ok(());
+    }
+
+    let save_suggestions = DB::insert(COLLECTION_MUTUAL)
+        .many_with_session(suggestions, db, session)
+        .await;
+    if let Err(why) = save_suggestions {
+        return Err(why);
+    }
+    Ok(())
+}
+
+async fn find_relation(db: &Client, user_id: ObjectId, mutual_id: ObjectId) -> Option<MutualDTO> {
+    DB::get(COLLECTION_MUTUAL)
+        .filter(vec![is("user_id", user_id), is("mutual_id", mutual_id)])
+        .get_one::<MutualDTO>(db)
+        .await
+        .ok()
+}
+
+//side is (user, display name of that user, status seen by that user)
+async fn set_relation_pair(
+    db: &Client,
+    session: &mut ClientSession,
+    side: (ObjectId, &String, &str),
+    other_side: (ObjectId, &String, &str),
+) -> Result<(), String> {
+    let (user_id, display_name, status) = side;
+    let (other_id, other_display_name, other_status) = other_side;
+    let save_side = set_relation(db, session, user_id, other_id, other_display_name, status).await;
+    if let Err(why) = save_side {
+        return Err(why);
+    }
+    let save_other_side = set_relation(db, session, other_id, user_id, display_name, other_status).await;
+    if let Err(why) = save_other_side {
+        return Err(why);
+    }
+    Ok(())
+}
+
+async fn set_relation(
+    db: &Client,
+    session: &mut ClientSession,
+    user_id: ObjectId,
+    mutual_id: ObjectId,
+    display_name: &String,
+    status: &str,
+) -> Result<u64, String> {
+    DB::upsert(COLLECTION_MUTUAL)
+        .filter(vec![is("user_id", user_id), is("mutual_id", mutual_id)])
+        .set(doc! {
+            "display_name":display_name,
+            "status":status,
+            "updated_at":DateTime::now()
+        })
+        .set_on_insert(doc! {
+            "created_at":DateTime::now()
+        })
+        .execute_with_session(db, session)
+        .await
+}
+
+async fn delete_relation_pair(
+    db: &Client,
+    user_id: ObjectId,
+    other_id: ObjectId,
+    statuses: Vec<&str>,
+) -> Result<u64, String> {
+    let mut statuses = statuses.into_iter().map(Bson::from).collect::<Vec<Bson>>();
+    //legacy friend document
+    statuses.push(Bson::Null);
+    DB::delete(COLLECTION_MUTUAL)
+        .filter(vec![
+            is_in("status", statuses),
+            or(&[
+                doc! {"user_id":user_id,"mutual_id":other_id}.into(),
+                doc! {"user_id":other_id,"mutual_id":user_id}.into(),
+            ]),
+        ])
+        .many(db)
+        .await
+}
+
+async fn notify_user(
+    db: &Client,
+    session: &mut ClientSession,
+    kind: &str,
+    title: String,
+    ref_id: ObjectId,
+    user_id: ObjectId,
+) -> Result<(), String> {
+    let notification = Notification {
+        id: Some(ObjectId::new()),
+        ref_id: Some(ref_id),
+        kind: kind.to_string(),
+        title,
+        body: None,
+        notification_type: "".to_string(),
+        created_at: DateTime::now(),
+        updated_at: DateTime::now(),
+    };
+    let log = NotificationLog {
+        id: Some(ObjectId::new()),
+        notification_id: notification.id,
+        ref_id: Some(ref_id),
+        user_id: Some(user_id),
+        sender_id: Some(ref_id),
+        is_read: false,
+        created_at: DateTime::now(),
+        updated_at: DateTime::now(),
+    };
+
+    let save_notification = DB::insert(COLLECTION_NOTIFICATION)
+        .one_with_session(notification, db, session)
+        .await;
+    if let Err(why) = save_notification {
+        return Err(why);
+    }
+
+    let save_log = DB::insert(COLLECTION_NOTIFICATION_LOG)
+        .one_with_session(log, db, session)
+        .await;
+    if let Err(why) = save_log {
+        return Err(why);
+    }
+    Ok(())
+}
@@ -0,0 +1,8 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MutualChangeResponse {
+    pub user_id: String,
+    //status seen from the caller side, none when the relation is gone
+    pub status: Option<String>,
+}
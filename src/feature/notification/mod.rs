@@ -9,10 +9,16 @@ use crate::{
         app_state::AppState,
         jwt::AuthContext,
         lang::Lang,
-        mongo::{DB, filter::is, lookup::one},
+        constant::{COLLECTION_NOTIFICATION, COLLECTION_NOTIFICATION_LOG, COLLECTION_USERS},
+        mongo::{
+            DB,
+            filter::{is, is_not_in},
+            lookup::one,
+        },
         utils::create_object_id_option,
     },
     dto::notification_log_dto::NotificationLogDTO,
+    feature::mutual::get_blocked_user_ids,
     i18n,
 };
 
@@ -36,12 +42,13 @@ pub async fn get_notification_list(
 
     let page = query.clone().page.unwrap_or(0);
     let size = query.clone().size.unwrap_or(10);
-    let find_all_notification = DB::get("notification-log")
+    let blocked_ids = get_blocked_user_ids(&state.db, user_id).await;
+    let find_all_notification = DB::get(COLLECTION_NOTIFICATION_LOG)
         .lookup(&[
-            one("user", "user_id", "_id", "user"),
-            one("notification", "notification_id", "_id", "notification"),
+            one(COLLECTION_USERS, "user_id", "_id", "user"),
+            one(COLLECTION_NOTIFICATION, "notification_id", "_id", "notification"),
         ])
-        .filter(vec![is("user_id", user_id)])
+        .filter(vec![is("user_id", user_id), is_not_in("sender_id", blocked_ids)])
         .sort(vec![("created_at", -1)])
         .get_per_page::<NotificationLogDTO>(page, size, &state.db)
         .await;
@@ -68,14 +75,16 @@ pub async fn get_detail_notification(
     let user_id = auth_context.get_user_id().clone().unwrap();
     let notification_id = create_object_id_option(&notification_id);
 
-    let find_detail_notification = DB::get("notification-log")
+    let blocked_ids = get_blocked_user_ids(&state.db, user_id).await;
+    let find_detail_notification = DB::get(COLLECTION_NOTIFICATION_LOG)
         .lookup(&[
-            one("user", "user_id", "_id", "user"),
-            one("notification", "notification_id", "_id", "notification"),
+            one(COLLECTION_USERS, "user_id", "_id", "user"),
+            one(COLLECTION_NOTIFICATION, "notification_id", "_id", "notification"),
         ])
         .filter(vec![
             is("user_id", user_id),
             is("notification_id", notification_id),
+            is_not_in("sender_id", blocked_ids),
         ])
         .sort(vec![("created_at", -1)])
         .get_one::<NotificationLogDTO>(&state.db)
@@ -103,10 +112,10 @@ pub async fn set_read_status_notification(
     let user_id = auth_context.get_user_id().clone().unwrap();
     let notification_id = create_object_id_option(&notification_log_id);
 
-    let find_detail_notification = DB::get("notification-log")
+    let find_detail_notification = DB::get(COLLECTION_NOTIFICATION_LOG)
         .lookup(&[
-            one("user", "user_id", "_id", "user"),
-            one("notification", "notification_id", "_id", "notification"),
+            one(COLLECTION_USERS, "user_id", "_id", "user"),
+            one(COLLECTION_NOTIFICATION, "notification_id", "_id", "notification"),
         ])
         .filter(vec![
             is("user_id", user_id),
@@ -121,7 +130,7 @@ pub async fn set_read_status_notification(
         return ApiResponse::failed("");
     }
 
-    let update_status = DB::update("notification-log")
+    let update_status = DB::update(COLLECTION_NOTIFICATION_LOG)
         .filter(vec![is("_id", notification_id)])
         .set_value("is_read", true)
         .execute(&state.db)
@@ -149,10 +158,10 @@ pub async fn delete_notification(
     let user_id = auth_context.get_user_id().clone().unwrap();
     let notification_id = create_object_id_option(&notification_log_id);
 
-    let find_detail_notification = DB::get("notification-log")
+    let find_detail_notification = DB::get(COLLECTION_NOTIFICATION_LOG)
         .lookup(&[
-            one("user", "user_id", "_id", "user"),
-            one("notification", "notification_id", "_id", "notification"),
+            one(COLLECTION_USERS, "user_id", "_id", "user"),
+            one(COLLECTION_NOTIFICATION, "notification_id", "_id", "notification"),
         ])
         .filter(vec![
             is("user_id", user_id),
@@ -167,7 +176,7 @@ pub async fn delete_notification(
         return ApiResponse::failed("");
     }
 
-    let delete_notification = DB::delete("notification-log")
+    let delete_notification = DB::delete(COLLECTION_NOTIFICATION_LOG)
         .filter(vec![is("_id", notification_id)])
         .one(&state.db)
         .await;
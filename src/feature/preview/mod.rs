@@ -0,0 +1,221 @@
+use crate::common::app_state::AppState;
+use crate::common::constant::{APP_DEEP_LINK_SCHEME, APP_NAME, COLLECTION_EVENTS};
+use crate::common::env_config::EnvConfig;
+use crate::common::i18n::{I18n, replace_placeholders};
+use crate::common::lang::Lang;
+use crate::common::mongo::DB;
+use crate::common::mongo::filter::is;
+use crate::dto::event_dto::EventDTO;
+use crate::feature::event::event_model::EventAccessRequest;
+use crate::feature::event::event_policy::check_event_access;
+use crate::feature::event::{find_event_by_code, find_event_detail, present_event};
+use crate::feature::invitation::record_invitation_open;
+use crate::i18n;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{Html, IntoResponse, Redirect, Response};
+use log::info;
+use std::collections::HashMap;
+
+const PREVIEW_TEMPLATE: &str = include_str!("event_preview.html");
+
+//link shared to chat apps, crawler are anonymous so only what a stranger may see is rendered
+pub async fn get_event_preview_by_slug(
+    state: State<AppState>,
+    lang: Lang,
+    Path(slug): Path<String>,
+) -> Response {
+    info!(target:"get_event_preview_by_slug","Starting...");
+    let i18n = i18n!("preview", lang);
+
+    let find_event = find_event_detail(&state.db, is("slug", slug.clone())).await;
+    if let Err(why) = find_event {
+        info!(target:"get_event_preview_by_slug","slug not current {}",why);
+        let find_renamed = DB::get(COLLECTION_EVENTS)
+            .filter(vec![is("previous_slugs", slug.clone())])
+            .get_one::<EventDTO>(&state.db)
+            .await;
+        if let Ok(Some(current)) = find_renamed.map(|event| event.slug) {
+            return Redirect::permanent(&format!("/p/{}", current)).into_response();
+        }
+        return render_not_found(&i18n, &lang);
+    }
+
+    let access = EventAccessRequest {
+        invitation_code: None,
+        slug: Some(slug.clone()),
+    };
+    info!(target:"get_event_preview_by_slug","finish");
+    render_preview(
+        &state,
+        &i18n,
+        &lang,
+        find_event.unwrap(),
+        &access,
+        None,
+        format!("/p/{}", slug),
+        format!("event/slug/{}", slug),
+    )
+    .await
+}
+
+pub async fn get_event_preview_by_code(
+    state: State<AppState>,
+    lang: Lang,
+    Path(code): Path<String>,
+) -> Response {
+    info!(target:"get_event_preview_by_code","Starting...");
+    let i18n = i18n!("preview", lang);
+
+    let find_event = find_event_by_code(&state.db, &code).await;
+    if let Err(why) = find_event {
+        info!(target:"get_event_preview_by_code","cannot find event {}",why);
+        return render_not_found(&i18n, &lang);
+    }
+
+    let access = EventAccessRequest {
+        invitation_code: Some(code.clone()),
+        slug: None,
+    };
+    info!(target:"get_event_preview_by_code","finish");
+    render_preview(
+        &state,
+        &i18n,
+        &lang,
+        find_event.unwrap(),
+        &access,
+        Some(&code),
+        format!("/i/{}", code),
+        format!("event/code/{}", code),
+    )
+    .await
+}
+
+async fn render_preview(
+    state: &AppState,
+    i18n: &I18n,
+    lang: &Lang,
+    event: EventDTO,
+    access: &EventAccessRequest,
+    open_code: Option<&String>,
+    path: String,
+    deep_link_path: String,
+) -> Response {
+    let mut values = HashMap::new();
+    values.insert("url", format!("{}{}", base_url(), path));
+    values.insert("deep_link", format!("{}{}", APP_DEEP_LINK_SCHEME, deep_link_path));
+
+    let access = check_event_access(&state.db, &event, None, access).await;
+    if let Err(why) = access {
+        info!(target:"render_preview","access denied {}",why);
+        if why == "event_not_found" {
+            return render_not_found(i18n, lang);
+        }
+        values.insert("title", i18n.translate("preview.private_title"));
+        values.insert("description", i18n.translate("preview.private_description"));
+        return render(i18n, lang, StatusCode::OK, values);
+    }
+    //only count opens of a link that actually show the event
+    if let Some(code) = open_code {
+        record_invitation_open(&state.db, code).await;
+    }
+
+    //password protected event only tell there is something behind the link
+    let is_protected = event
+        .config
+        .event_password
+        .as_ref()
+        .is_some_and(|password| !password.is_empty());
+    if is_protected {
+        values.insert("title", i18n.translate("preview.protected_title"));
+        values.insert("description", i18n.translate("preview.protected_description"));
+        return render(i18n, lang, StatusCode::OK, values);
+    }
+
+    let event = present_event(event, lang);
+    values.insert("title", event.event_name.clone());
+    values.insert("description", event.event_description.clone());
+    values.insert(
+        "schedule",
+        event
+            .schedule
+            .clone()
+            .and_then(|schedule| schedule.display)
+            .unwrap_or(String::new()),
+    );
+    values.insert(
+        "venue",
+        event
+            .venue_location
+            .clone()
+            .and_then(|venue| venue.venue_name.or(venue.venue_address))
+            .unwrap_or(String::new()),
+    );
+    if let Some(image) = event.image.clone() {
+        let env = EnvConfig::init();
+        values.insert(
+            "image",
+            format!(
+                "{}/{}/{}/{}",
+                env.minio_url.trim_end_matches('/'),
+                image.bucket,
+                image.path,
+                image.file_name
+            ),
+        );
+    }
+    render(i18n, lang, StatusCode::OK, values)
+}
+
+fn render_not_found(i18n: &I18n, lang: &Lang) -> Response {
+    let mut values = HashMap::new();
+    values.insert("url", base_url());
+    values.insert("deep_link", APP_DEEP_LINK_SCHEME.to_string());
+    values.insert("title", i18n.translate("preview.not_found_title"));
+    values.insert("description", i18n.translate("preview.not_found_description"));
+    render(i18n, lang, StatusCode::NOT_FOUND, values)
+}
+
+fn render(
+    i18n: &I18n,
+    lang: &Lang,
+    status: StatusCode,
+    mut values: HashMap<&str, String>,
+) -> Response {
+    let image_tag = values
+        .get("image")
+        .map(|image| format!("<img src=\"{}\" alt=\"\">", escape_html(image)))
+        .unwrap_or(String::new());
+
+    values.insert("lang", lang.get().to_string());
+    values.insert("app_name", APP_NAME.to_string());
+    values.insert("open_in_app", i18n.translate("preview.open_in_app"));
+    for key in ["schedule", "venue", "image"] {
+        values.entry(key).or_insert(String::new());
+    }
+
+    let escaped = values
+        .iter()
+        .map(|(key, value)| (*key, escape_html(value)))
+        .collect::<HashMap<&str, String>>();
+    let mut placeholders = escaped
+        .iter()
+        .map(|(key, value)| (*key, value.as_str()))
+        .collect::<HashMap<&str, &str>>();
+    placeholders.insert("image_tag", image_tag.as_str());
+
+    (status, Html(replace_placeholders(PREVIEW_TEMPLATE, &placeholders))).into_response()
+}
+
+fn base_url() -> String {
+    EnvConfig::init().base_url.trim_end_matches('/').to_string()
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
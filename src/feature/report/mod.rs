@@ -0,0 +1,336 @@
+use crate::common::api_response::{ApiResponse, PaginationRequest, PagingResponse};
+use crate::common::app_state::AppState;
+use crate::common::constant::{
+    COLLECTION_EVENT_ALBUM, COLLECTION_EVENTS, COLLECTION_USER_REPORT, COLLECTION_USERS,
+    REPORT_STATUS_OPEN, REPORT_STATUS_RESOLVED, REPORT_TARGET_ALBUM, REPORT_TARGET_EVENT,
+    USER_STATUS_SUSPENDED,
+};
+use crate::common::jwt::AuthContext;
+use crate::common::lang::Lang;
+use crate::common::middleware::Json;
+use crate::common::mongo::DB;
+use crate::common::mongo::filter::is;
+use crate::common::mongo::lookup::one;
+use crate::common::permission::permission::app;
+use crate::common::utils::create_object_id_option;
+use crate::dto::event_album_dto::EventAlbumDTO;
+use crate::dto::event_dto::EventDTO;
+use crate::dto::user_dto::UserDTO;
+use crate::dto::user_report_dto::UserReportDTO;
+use crate::entity::user_report::UserReport;
+use crate::feature::report::report_model::{CreateReportRequest, ResolveReportRequest};
+use crate::feature::user::set_user_status;
+use crate::i18n;
+use axum::extract::{Path, Query, State};
+use bson::oid::ObjectId;
+use bson::{DateTime, doc};
+use log::info;
+use mongodb::Client;
+use validator::Validate;
+
+pub mod report_model;
+
+pub async fn create_report(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Json(body): Json<CreateReportRequest>,
+) -> ApiResponse<UserReportDTO> {
+    info!(target:"create_report","Starting...");
+    let i18n = i18n!("report", lang);
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"create_report","user id not found");
+        return ApiResponse::access_denied(&i18n.translate("create_report.user_id_not_found"));
+    }
+    let current_user_id = auth_context.get_user_id().unwrap();
+
+    let validate = body.validate();
+    if let Err(err) = validate {
+        info!(target:"create_report","validation error {:?}",err);
+        return ApiResponse::error_validation(err, &i18n.translate("create_report.validation_error"));
+    }
+
+    let create_target_id = create_object_id_option(&body.target_id);
+    if let None = create_target_id {
+        info!(target:"create_report","target id invalid");
+        return ApiResponse::failed(&i18n.translate("create_report.target_id_invalid"));
+    }
+    let create_target_id = create_target_id.unwrap();
+
+    let find_owner = find_target_owner(&state.db, &body.target_type, create_target_id).await;
+    if let Err(why) = find_owner {
+        info!(target:"create_report","{}",why);
+        return ApiResponse::not_found(&i18n.translate(&format!("create_report.{}", why)));
+    }
+    let reported_user_id = find_owner.unwrap();
+    if reported_user_id == current_user_id {
+        info!(target:"create_report","cannot report own content");
+        return ApiResponse::failed(&i18n.translate("create_report.cannot_self"));
+    }
+
+    let find_duplicate = DB::get(COLLECTION_USER_REPORT)
+        .filter(vec![
+            is("reporter_id", current_user_id),
+            is("target_type", body.target_type.clone()),
+            is("target_id", create_target_id),
+            is("status", REPORT_STATUS_OPEN),
+        ])
+        .count(&state.db)
+        .await
+        .unwrap_or(0);
+    if find_duplicate > 0 {
+        info!(target:"create_report","already reported");
+        return ApiResponse::failed(&i18n.translate("create_report.already_reported"));
+    }
+
+    let report = UserReport {
+        id: Some(ObjectId::new()),
+        reporter_id: Some(current_user_id),
+        target_type: body.target_type,
+        target_id: Some(create_target_id),
+        reported_user_id: Some(reported_user_id),
+        reason: body.reason,
+        detail: body.detail,
+        status: REPORT_STATUS_OPEN.to_string(),
+        resolution_note: None,
+        resolved_by: None,
+        resolved_at: None,
+        created_at: DateTime::now(),
+        updated_at: DateTime::now(),
+    };
+    let save_report = DB::insert(COLLECTION_USER_REPORT)
+        .one(report.clone(), &state.db)
+        .await;
+    if let Err(why) = save_report {
+        info!(target:"create_report","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("create_report.failed"));
+    }
+
+    info!(target:"create_report","finish");
+    ApiResponse::ok(report.into(), &i18n.translate("create_report.ok"))
+}
+
+//moderation queue, filter is the report status
+pub async fn get_reports(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Query(query): Query<PaginationRequest>,
+) -> ApiResponse<PagingResponse<UserReportDTO>> {
+    info!(target:"get_reports","Starting...");
+    let i18n = i18n!("report", lang);
+
+    if !auth_context.authorize(app::moderation::REPORT) {
+        info!(target:"get_reports","not a moderator");
+        return ApiResponse::access_denied(&i18n.translate("get_reports.not_allowed"));
+    }
+
+    let mut data = DB::get(COLLECTION_USER_REPORT);
+    if let Some(status) = query.filter.clone() {
+        data = data.filter(vec![is("status", status)]);
+    }
+
+    let find_reports = data
+        .lookup(&[
+            one(COLLECTION_USERS, "reporter_id", "_id", "reporter"),
+            one(COLLECTION_USERS, "reported_user_id", "_id", "reported_user"),
+        ])
+        .sort(vec![("created_at", -1)])
+        .get_per_page::<UserReportDTO>(
+            query.page.unwrap_or(0),
+            query.size.unwrap_or(20),
+            &state.db,
+        )
+        .await;
+    if let Err(why) = find_reports {
+        info!(target:"get_reports","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("get_reports.failed"));
+    }
+
+    info!(target:"get_reports","finish");
+    ApiResponse::ok(find_reports.unwrap(), &i18n.translate("get_reports.ok"))
+}
+
+pub async fn get_report_detail(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Path(report_id): Path<String>,
+) -> ApiResponse<UserReportDTO> {
+    info!(target:"get_report_detail","Starting...");
+    let i18n = i18n!("report", lang);
+
+    if !auth_context.authorize(app::moderation::REPORT) {
+        info!(target:"get_report_detail","not a moderator");
+        return ApiResponse::access_denied(&i18n.translate("get_report_detail.not_allowed"));
+    }
+
+    let create_report_id = create_object_id_option(&report_id);
+    if let None = create_report_id {
+        info!(target:"get_report_detail","report id invalid");
+        return ApiResponse::failed(&i18n.translate("get_report_detail.report_id_invalid"));
+    }
+
+    let find_report = find_report_by_id(&state.db, create_report_id.unwrap()).await;
+    if let Err(why) = find_report {
+        info!(target:"get_report_detail","{}",why);
+        return ApiResponse::not_found(&i18n.translate("get_report_detail.not_found"));
+    }
+
+    info!(target:"get_report_detail","finish");
+    ApiResponse::ok(find_report.unwrap(), &i18n.translate("get_report_detail.ok"))
+}
+
+pub async fn resolve_report(
+    mut state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Path(report_id): Path<String>,
+    Json(body): Json<ResolveReportRequest>,
+) -> ApiResponse<UserReportDTO> {
+    info!(target:"resolve_report","Starting...");
+    let i18n = i18n!("report", lang);
+
+    if !auth_context.authorize(app::moderation::REPORT) {
+        info!(target:"resolve_report","not a moderator");
+        return ApiResponse::access_denied(&i18n.translate("resolve_report.not_allowed"));
+    }
+
+    let validate = body.validate();
+    if let Err(err) = validate {
+        info!(target:"resolve_report","validation error {:?}",err);
+        return ApiResponse::error_validation(err, &i18n.translate("resolve_report.validation_error"));
+    }
+
+    let create_report_id = create_object_id_option(&report_id);
+    if let None = create_report_id {
+        info!(target:"resolve_report","report id invalid");
+        return ApiResponse::failed(&i18n.translate("resolve_report.report_id_invalid"));
+    }
+    let create_report_id = create_report_id.unwrap();
+
+    let find_report = find_report_by_id(&state.db, create_report_id).await;
+    if let Err(why) = find_report {
+        info!(target:"resolve_report","{}",why);
+        return ApiResponse::not_found(&i18n.translate("resolve_report.not_found"));
+    }
+    let report = find_report.unwrap();
+    if report.status != REPORT_STATUS_OPEN {
+        info!(target:"resolve_report","report already closed");
+        return ApiResponse::failed(&i18n.translate("resolve_report.already_closed"));
+    }
+
+    let session = state.db.start_session().await;
+    if let Err(why) = session {
+        info!(target:"resolve_report","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("resolve_report.failed"));
+    }
+    let mut session = session.unwrap();
+    let _ = session.start_transaction().await;
+
+    //status filter keep two moderators from closing the same report
+    let update_report = DB::update(COLLECTION_USER_REPORT)
+        .filter(vec![is("_id", create_report_id), is("status", REPORT_STATUS_OPEN)])
+        .set(doc! {
+            "status":body.status.clone(),
+            "resolution_note":body.note.clone(),
+            "resolved_by":auth_context.get_user_id(),
+            "resolved_at":DateTime::now(),
+            "updated_at":DateTime::now()
+        })
+        .execute_with_session(&state.db, &mut session)
+        .await;
+    if let Err(why) = update_report {
+        info!(target:"resolve_report","{:?}",why);
+        let _ = session.abort_transaction().await;
+        return ApiResponse::failed(&i18n.translate("resolve_report.failed"));
+    }
+    if update_report.unwrap() == 0 {
+        info!(target:"resolve_report","report closed by someone else");
+        let _ = session.abort_transaction().await;
+        return ApiResponse::failed(&i18n.translate("resolve_report.already_closed"));
+    }
+
+    if body.suspend_user && body.status == REPORT_STATUS_RESOLVED {
+        if let None = report.reported_user_id {
+            info!(target:"resolve_report","reported user not found");
+            let _ = session.abort_transaction().await;
+            return ApiResponse::failed(&i18n.translate("resolve_report.failed_suspend"));
+        }
+        let suspend_user = set_user_status(
+            &state.db,
+            &mut session,
+            report.reported_user_id.unwrap(),
+            USER_STATUS_SUSPENDED,
+            Some(body.note.clone().unwrap_or(report.reason.clone())),
+            auth_context.get_user_id(),
+        )
+        .await;
+        if let Err(why) = suspend_user {
+            info!(target:"resolve_report","{:?}",why);
+            let _ = session.abort_transaction().await;
+            return ApiResponse::failed(&i18n.translate("resolve_report.failed_suspend"));
+        }
+    }
+    let _ = session.commit_transaction().await;
+    if body.suspend_user && body.status == REPORT_STATUS_RESOLVED {
+        let _clear_status = state
+            .redis
+            .delete_cache_user_status(&report.reported_user_id.unwrap().to_string());
+        let _revoke = state
+            .redis
+            .revoke_session_user(&report.reported_user_id.unwrap().to_string());
+    }
+
+    let find_report = find_report_by_id(&state.db, create_report_id).await;
+    if let Err(why) = find_report {
+        info!(target:"resolve_report","{}",why);
+        return ApiResponse::not_found(&i18n.translate("resolve_report.not_found"));
+    }
+
+    info!(target:"resolve_report","finish");
+    ApiResponse::ok(find_report.unwrap(), &i18n.translate("resolve_report.ok"))
+}
+
+async fn find_report_by_id(db: &Client, report_id: ObjectId) -> Result<UserReportDTO, String> {
+    DB::get(COLLECTION_USER_REPORT)
+        .filter(vec![is("_id", report_id)])
+        .lookup(&[
+            one(COLLECTION_USERS, "reporter_id", "_id", "reporter"),
+            one(COLLECTION_USERS, "reported_user_id", "_id", "reported_user"),
+        ])
+        .get_one::<UserReportDTO>(db)
+        .await
+}
+
+//the user accountable for the reported target, error is the translation key suffix
+async fn find_target_owner(
+    db: &Client,
+    target_type: &String,
+    target_id: ObjectId,
+) -> Result<ObjectId, String> {
+    match target_type.as_str() {
+        REPORT_TARGET_EVENT => DB::get(COLLECTION_EVENTS)
+            .filter(vec![is("_id", target_id)])
+            .get_one::<EventDTO>(db)
+            .await
+            .ok()
+            .and_then(|event| event.host_id)
+            .ok_or("event_not_found".to_string()),
+        REPORT_TARGET_ALBUM => DB::get(COLLECTION_EVENT_ALBUM)
+            .filter(vec![is("_id", target_id)])
+            .get_one::<EventAlbumDTO>(db)
+            .await
+            .ok()
+            .and_then(|photo| photo.user_id)
+            .ok_or("album_not_found".to_string()),
+        _ => DB::get(COLLECTION_USERS)
+            .filter(vec![is("_id", target_id)])
+            .get_one::<UserDTO>(db)
+            .await
+            .ok()
+            .and_then(|user| user.id)
+            .ok_or("user_not_found".to_string()),
+    }
+}
@@ -0,0 +1,25 @@
+use crate::common::utils::{validate_report_resolution, validate_report_target};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Validate)]
+pub struct CreateReportRequest {
+    #[validate(custom(function = "validate_report_target"))]
+    pub target_type: String,
+    pub target_id: String,
+    #[validate(length(min = 1, max = 100))]
+    pub reason: String,
+    #[validate(length(max = 1000))]
+    pub detail: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Validate)]
+pub struct ResolveReportRequest {
+    #[validate(custom(function = "validate_report_resolution"))]
+    pub status: String,
+    #[validate(length(max = 1000))]
+    pub note: Option<String>,
+    //only applied when the report is resolved
+    #[serde(default)]
+    pub suspend_user: bool,
+}
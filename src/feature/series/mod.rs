@@ -0,0 +1,580 @@
+use crate::common::api_response::ApiResponse;
+use crate::common::app_state::AppState;
+use crate::common::constant::{
+    COLLECTION_EVENT_GUEST, COLLECTION_EVENT_IMAGES, COLLECTION_EVENT_INVITATION,
+    COLLECTION_EVENT_SERIES, COLLECTION_EVENT_THEMES, COLLECTION_EVENTS, COLLECTION_USERS,
+    DEFAULT_EVENT_TIMEZONE, EVENT_STATUS_DRAFT, SERIES_EDIT_SCOPE_ALL, SERIES_EDIT_SCOPE_FOLLOWING,
+    SSE_EVENT_UPDATE_EVENT_DATA,
+};
+use crate::common::jwt::AuthContext;
+use crate::common::lang::Lang;
+use crate::common::middleware::Json;
+use crate::common::mongo::DB;
+use crate::common::mongo::filter::{greater_than_equal, is, is_in};
+use crate::common::mongo::lookup::{many, one};
+use crate::common::sse::sse_builder::{SseBuilder, SseTarget};
+use crate::common::utils::{
+    create_object_id_option, parse_bson_datetime, parse_datetime_in_timezone,
+};
+use crate::dto::event_dto::EventDTO;
+use crate::dto::event_guest_dto::EventGuestDTO;
+use crate::dto::event_invitation_dto::EventInvitationDTO;
+use crate::dto::event_series_dto::EventSeriesDTO;
+use crate::entity::event::Event;
+use crate::entity::event_guest::EventGuest;
+use crate::entity::event_invitation::EventInvitation;
+use crate::entity::event_series::{EventSeries, RecurrenceRule};
+use crate::feature::event::event_link::{generate_event_slug, generate_invitation_code};
+use crate::feature::event::event_model::EventAccessRequest;
+use crate::feature::event::event_policy::{check_event_access, is_event_host};
+use crate::feature::event::{copy_event_image, present_event};
+use crate::feature::series::series_model::{
+    CreateEventSeriesRequest, UpdateSeriesOccurrenceRequest,
+};
+use crate::i18n;
+use axum::extract::{Path, State};
+use bson::oid::ObjectId;
+use bson::{DateTime, doc};
+use log::info;
+use mongodb::Client;
+use validator::Validate;
+
+pub mod series_model;
+
+pub async fn create_event_series(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Json(body): Json<CreateEventSeriesRequest>,
+) -> ApiResponse<EventSeriesDTO> {
+    info!(target:"create_event_series","Starting...");
+    let i18n = i18n!("series", lang);
+    if let Err(err) = body.validate() {
+        info!(target:"create_event_series","{:?}", err);
+        return ApiResponse::error_validation(
+            err,
+            &i18n.translate("create_event_series.validation_error"),
+        );
+    }
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"create_event_series","user id not found");
+        return ApiResponse::failed(&i18n.translate("create_event_series.user_id_not_found"));
+    }
+    let current_user_id = auth_context.get_user_id().unwrap();
+
+    let create_event_id = create_object_id_option(&body.event_id);
+    if let None = create_event_id {
+        info!(target:"create_event_series","event id invalid");
+        return ApiResponse::failed(&i18n.translate("create_event_series.event_id_invalid"));
+    }
+    let create_event_id = create_event_id.unwrap();
+
+    let find_event = DB::get(COLLECTION_EVENTS)
+        .filter(vec![is("_id", create_event_id)])
+        .get_one::<EventDTO>(&state.db)
+        .await;
+    if let Err(why) = find_event {
+        info!(target:"create_event_series","cannot find events {}",why);
+        return ApiResponse::not_found(&i18n.translate("create_event_series.event_not_found"));
+    }
+    let template = find_event.unwrap();
+
+    if !is_event_host(&state.db, &template, current_user_id).await {
+        info!(target:"create_event_series","only host can create series");
+        return ApiResponse::access_denied(&i18n.translate("create_event_series.not_host"));
+    }
+    if template.series_id.is_some() {
+        info!(target:"create_event_series","event already part of series");
+        return ApiResponse::failed(&i18n.translate("create_event_series.already_in_series"));
+    }
+    if let None = template.datetime {
+        info!(target:"create_event_series","event has no datetime");
+        return ApiResponse::failed(&i18n.translate("create_event_series.datetime_required"));
+    }
+    let start = template.datetime.unwrap();
+
+    let until = match body.until.clone() {
+        None => None,
+        Some(until) => match parse_bson_datetime(&until) {
+            Ok(until) => Some(until),
+            Err(why) => {
+                info!(target:"create_event_series","until invalid {}",why);
+                return ApiResponse::failed(&i18n.translate("create_event_series.until_invalid"));
+            }
+        },
+    };
+
+    let mut exceptions = Vec::new();
+    for exception in body.exceptions.clone().unwrap_or(Vec::new()) {
+        let parse = parse_bson_datetime(&exception);
+        if let Err(why) = parse {
+            info!(target:"create_event_series","exception invalid {}",why);
+            return ApiResponse::failed(&i18n.translate("create_event_series.exception_invalid"));
+        }
+        exceptions.push(parse.unwrap());
+    }
+
+    let series = EventSeries {
+        id: Some(ObjectId::new()),
+        host_id: template.host_id,
+        template_event_id: template.id,
+        rule: RecurrenceRule {
+            frequency: body.frequency.clone(),
+            interval: body.interval.unwrap_or(1),
+            count: body.count,
+            until,
+        },
+        exceptions,
+        created_at: DateTime::now(),
+        updated_at: DateTime::now(),
+    };
+
+    //first occurrence is the template itself
+    let mut occurrences = series
+        .rule
+        .occurrences(start, &series.exceptions, template.timezone.as_ref())
+        .into_iter()
+        .skip(1)
+        .map(|datetime| build_occurrence(&template, series.id, datetime))
+        .collect::<Vec<Event>>();
+
+    let mut reserved_slugs = Vec::new();
+    let mut reserved_codes = Vec::new();
+    for occurrence in occurrences.iter_mut() {
+        occurrence.image_id = copy_event_image(&state.db, template.image_id, occurrence.id).await;
+        let slug = generate_event_slug(&state.db, &occurrence.event_name, &reserved_slugs).await;
+        reserved_slugs.push(slug.clone());
+        occurrence.slug = Some(slug);
+        occurrence.invitation_id = generate_invitation_code(&state.db, &reserved_codes).await;
+        reserved_codes.push(occurrence.invitation_id.clone());
+    }
+
+    let guests = DB::get(COLLECTION_EVENT_GUEST)
+        .filter(vec![is("event_id", create_event_id)])
+        .get_all::<EventGuestDTO>(&state.db)
+        .await
+        .unwrap_or(Vec::new());
+    let invitations = DB::get(COLLECTION_EVENT_INVITATION)
+        .filter(vec![is("event_id", create_event_id)])
+        .get_all::<EventInvitationDTO>(&state.db)
+        .await
+        .unwrap_or(Vec::new());
+
+    let mut occurrence_guests = Vec::new();
+    let mut occurrence_invitations = Vec::new();
+    for occurrence in occurrences.iter() {
+        let mut invitation_codes = Vec::new();
+        for _ in invitations.iter() {
+            let code = generate_invitation_code(&state.db, &reserved_codes).await;
+            reserved_codes.push(code.clone());
+            invitation_codes.push(code);
+        }
+        //keep invitation expiry relative to the occurrence date
+        let shift = occurrence.datetime.unwrap().timestamp_millis() - start.timestamp_millis();
+        for guest in guests.iter() {
+            occurrence_guests.push(EventGuest {
+                id: Some(ObjectId::new()),
+                user_id: guest.user_id,
+                event_id: occurrence.id,
+                role: guest.role.clone(),
+                rsvp: None,
+                checked_in_at: None,
+                created_at: DateTime::now(),
+                updated_at: DateTime::now(),
+            });
+        }
+        for (invitation, invitation_code) in invitations.iter().zip(invitation_codes) {
+            occurrence_invitations.push(EventInvitation {
+                id: Some(ObjectId::new()),
+                user_id: invitation.user_id,
+                event_id: occurrence.id,
+                invitation_type: invitation.invitation_type.clone(),
+                invitation_code,
+                phone_number: invitation.phone_number.clone(),
+                expires_at: invitation
+                    .expires_at
+                    .map(|expires_at| {
+                        DateTime::from_millis(expires_at.timestamp_millis() + shift)
+                    }),
+                max_uses: invitation.max_uses,
+                default_role: invitation.default_role.clone(),
+                open_count: 0,
+                redemption_count: 0,
+                revoked_at: invitation.revoked_at,
+                created_at: DateTime::now(),
+                updated_at: DateTime::now(),
+            });
+        }
+    }
+
+    let session = state.db.start_session().await;
+    if let Err(why) = session {
+        info!(target:"create_event_series","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("create_event_series.trx_failed"));
+    }
+    let mut session = session.unwrap();
+    let _ = session.start_transaction().await;
+
+    let save_series = DB::insert(COLLECTION_EVENT_SERIES)
+        .one_with_session(series.clone(), &state.db, &mut session)
+        .await;
+    if let Err(why) = save_series {
+        info!(target:"create_event_series","{:?}", why);
+        let _abort = session.abort_transaction().await;
+        return ApiResponse::failed(&i18n.translate("create_event_series.failed"));
+    }
+
+    let update_template = DB::update(COLLECTION_EVENTS)
+        .filter(vec![is("_id", create_event_id)])
+        .set(doc! {
+            "series_id":series.id,
+            "updated_at":DateTime::now()
+        })
+        .execute_with_session(&state.db, &mut session)
+        .await;
+    if let Err(why) = update_template {
+        info!(target:"create_event_series","{:?}", why);
+        let _abort = session.abort_transaction().await;
+        return ApiResponse::failed(&i18n.translate("create_event_series.failed"));
+    }
+
+    if !occurrences.is_empty() {
+        let save_occurrence = DB::insert(COLLECTION_EVENTS)
+            .many_with_session(occurrences, &state.db, &mut session)
+            .await;
+        if let Err(why) = save_occurrence {
+            info!(target:"create_event_series","{:?}", why);
+            let _abort = session.abort_transaction().await;
+            return ApiResponse::failed(&i18n.translate("create_event_series.failed"));
+        }
+    }
+
+    if !occurrence_guests.is_empty() {
+        let save_guest = DB::insert(COLLECTION_EVENT_GUEST)
+            .many_with_session(occurrence_guests, &state.db, &mut session)
+            .await;
+        if let Err(why) = save_guest {
+            info!(target:"create_event_series","{:?}", why);
+            let _abort = session.abort_transaction().await;
+            return ApiResponse::failed(&i18n.translate("create_event_series.failed"));
+        }
+    }
+
+    if !occurrence_invitations.is_empty() {
+        let save_invitation = DB::insert(COLLECTION_EVENT_INVITATION)
+            .many_with_session(occurrence_invitations, &state.db, &mut session)
+            .await;
+        if let Err(why) = save_invitation {
+            info!(target:"create_event_series","{:?}", why);
+            let _abort = session.abort_transaction().await;
+            return ApiResponse::failed(&i18n.translate("create_event_series.failed"));
+        }
+    }
+    let _commit = session.commit_transaction().await;
+
+    let find_series = find_series_with_occurrence(&state.db, series.id.unwrap(), &lang).await;
+    if let Err(why) = find_series {
+        info!(target:"create_event_series","{:?}", why);
+        return ApiResponse::ok(series.into(), &i18n.translate("create_event_series.ok"));
+    }
+
+    info!(target:"create_event_series","finish");
+    ApiResponse::ok(find_series.unwrap(), &i18n.translate("create_event_series.ok"))
+}
+
+pub async fn get_event_series(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Path(series_id): Path<String>,
+) -> ApiResponse<EventSeriesDTO> {
+    info!(target:"get_event_series","Starting...");
+    let i18n = i18n!("series", lang);
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"get_event_series","user id not found");
+        return ApiResponse::failed(&i18n.translate("get_event_series.user_id_not_found"));
+    }
+    let current_user_id = auth_context.get_user_id().unwrap();
+
+    let create_series_id = create_object_id_option(&series_id);
+    if let None = create_series_id {
+        info!(target:"get_event_series","series id invalid");
+        return ApiResponse::failed(&i18n.translate("get_event_series.series_id_invalid"));
+    }
+
+    let find_series = find_series_with_occurrence(&state.db, create_series_id.unwrap(), &lang).await;
+    if let Err(why) = find_series {
+        info!(target:"get_event_series","{:?}", why);
+        return ApiResponse::not_found(&i18n.translate("get_event_series.series_not_found"));
+    }
+    let mut series = find_series.unwrap();
+
+    //non host only see occurrence they are allowed to read, draft stay hidden
+    if series.host_id != Some(current_user_id) {
+        let mut occurrences = Vec::new();
+        for event in series.occurrences {
+            let access = check_event_access(
+                &state.db,
+                &event,
+                Some(current_user_id),
+                &EventAccessRequest::default(),
+            )
+            .await;
+            if access.is_ok() {
+                occurrences.push(event);
+            }
+        }
+        series.occurrences = occurrences;
+    }
+
+    info!(target:"get_event_series","finish");
+    ApiResponse::ok(series, &i18n.translate("get_event_series.ok"))
+}
+
+pub async fn update_series_occurrence(
+    mut state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Path(event_id): Path<String>,
+    Json(body): Json<UpdateSeriesOccurrenceRequest>,
+) -> ApiResponse<Vec<EventDTO>> {
+    info!(target:"update_series_occurrence","Starting...");
+    let i18n = i18n!("series", lang);
+    if let Err(err) = body.validate() {
+        info!(target:"update_series_occurrence","{:?}", err);
+        return ApiResponse::error_validation(
+            err,
+            &i18n.translate("update_series_occurrence.validation_error"),
+        );
+    }
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"update_series_occurrence","user id not found");
+        return ApiResponse::failed(&i18n.translate("update_series_occurrence.user_id_not_found"));
+    }
+    let current_user_id = auth_context.get_user_id().unwrap();
+
+    let create_event_id = create_object_id_option(&event_id);
+    if let None = create_event_id {
+        info!(target:"update_series_occurrence","event id invalid");
+        return ApiResponse::failed(&i18n.translate("update_series_occurrence.event_id_invalid"));
+    }
+    let create_event_id = create_event_id.unwrap();
+
+    let find_event = DB::get(COLLECTION_EVENTS)
+        .filter(vec![is("_id", create_event_id)])
+        .get_one::<EventDTO>(&state.db)
+        .await;
+    if let Err(why) = find_event {
+        info!(target:"update_series_occurrence","cannot find events {}",why);
+        return ApiResponse::not_found(
+            &i18n.translate("update_series_occurrence.event_not_found"),
+        );
+    }
+    let event = find_event.unwrap();
+
+    if let None = event.series_id {
+        info!(target:"update_series_occurrence","event is not part of series");
+        return ApiResponse::failed(&i18n.translate("update_series_occurrence.not_in_series"));
+    }
+    if !is_event_host(&state.db, &event, current_user_id).await {
+        info!(target:"update_series_occurrence","only host can update series");
+        return ApiResponse::access_denied(&i18n.translate("update_series_occurrence.not_host"));
+    }
+
+    //local datetime without offset is read in the event timezone, same as update_event_data
+    let timezone = event
+        .timezone
+        .clone()
+        .unwrap_or(DEFAULT_EVENT_TIMEZONE.to_string());
+    let new_datetime = match body.datetime.clone() {
+        None => None,
+        Some(datetime) => match parse_datetime_in_timezone(&datetime, &timezone) {
+            Ok(datetime) => Some(datetime),
+            Err(why) => {
+                info!(target:"update_series_occurrence","datetime invalid {}",why);
+                return ApiResponse::failed(
+                    &i18n.translate("update_series_occurrence.datetime_invalid"),
+                );
+            }
+        },
+    };
+
+    let mut targets = DB::get(COLLECTION_EVENTS).filter(vec![is("series_id", event.series_id)]);
+    targets = match body.scope.as_str() {
+        SERIES_EDIT_SCOPE_ALL => targets,
+        SERIES_EDIT_SCOPE_FOLLOWING => {
+            targets.filter(vec![greater_than_equal("datetime", event.datetime)])
+        }
+        _ => targets.filter(vec![is("_id", create_event_id)]),
+    };
+    let targets = targets.get_all::<EventDTO>(&state.db).await;
+    if let Err(why) = targets {
+        info!(target:"update_series_occurrence","{:?}", why);
+        return ApiResponse::failed(&i18n.translate("update_series_occurrence.failed"));
+    }
+    let targets = targets.unwrap();
+    let target_ids = targets
+        .iter()
+        .filter_map(|target| target.id)
+        .collect::<Vec<ObjectId>>();
+
+    let mut set = doc! { "updated_at":DateTime::now() };
+    if let Some(event_name) = body.event_name.clone() {
+        set.insert("event_name", event_name);
+    }
+    if let Some(event_description) = body.event_description.clone() {
+        set.insert("event_description", event_description);
+    }
+    if let Some(theme_id) = body.theme_id {
+        set.insert("theme_id", theme_id);
+    }
+
+    let session = state.db.start_session().await;
+    if let Err(why) = session {
+        info!(target:"update_series_occurrence","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("update_series_occurrence.trx_failed"));
+    }
+    let mut session = session.unwrap();
+    let _ = session.start_transaction().await;
+
+    let update_event = DB::update(COLLECTION_EVENTS)
+        .filter(vec![is_in("_id", target_ids.clone())])
+        .set(set)
+        .execute_many_with_session(&state.db, &mut session)
+        .await;
+    if let Err(why) = update_event {
+        info!(target:"update_series_occurrence","{:?}", why);
+        let _abort = session.abort_transaction().await;
+        return ApiResponse::failed(&i18n.translate("update_series_occurrence.failed"));
+    }
+
+    //moving one occurrence shift every targeted occurrence by the same amount
+    if let (Some(new_datetime), Some(old_datetime)) = (new_datetime, event.datetime) {
+        let shift = new_datetime.timestamp_millis() - old_datetime.timestamp_millis();
+        for target in targets.iter() {
+            if let None = target.datetime {
+                continue;
+            }
+            let datetime =
+                DateTime::from_millis(target.datetime.unwrap().timestamp_millis() + shift);
+            let mut shifted = doc! { "datetime":datetime };
+            //end move along so the occurrence keep its duration
+            if let Some(end_datetime) = target.end_datetime {
+                shifted.insert(
+                    "end_datetime",
+                    DateTime::from_millis(end_datetime.timestamp_millis() + shift),
+                );
+            }
+            let update_datetime = DB::update(COLLECTION_EVENTS)
+                .filter(vec![is("_id", target.id)])
+                .set(shifted)
+                .inc_value("revision", 1)
+                .execute_with_session(&state.db, &mut session)
+                .await;
+            if let Err(why) = update_datetime {
+                info!(target:"update_series_occurrence","{:?}", why);
+                let _abort = session.abort_transaction().await;
+                return ApiResponse::failed(&i18n.translate("update_series_occurrence.failed"));
+            }
+        }
+    }
+    let _commit = session.commit_transaction().await;
+
+    let find_updated = DB::get(COLLECTION_EVENTS)
+        .filter(vec![is_in("_id", target_ids)])
+        .lookup(&[
+            one(COLLECTION_USERS, "host_id", "_id", "host"),
+            one(COLLECTION_EVENT_IMAGES, "image_id", "_id", "image"),
+            one(COLLECTION_EVENT_THEMES, "theme_id", "_id", "theme"),
+        ])
+        .sort(vec![("datetime", 1)])
+        .get_all::<EventDTO>(&state.db)
+        .await;
+    if let Err(why) = find_updated {
+        info!(target:"update_series_occurrence","{:?}", why);
+        return ApiResponse::failed(&i18n.translate("update_series_occurrence.failed"));
+    }
+    let updated = find_updated
+        .unwrap()
+        .into_iter()
+        .map(|event| present_event(event, &lang))
+        .collect::<Vec<EventDTO>>();
+
+    for occurrence in updated.iter() {
+        let find_subscriber = state
+            .redis
+            .get_list_subscriber(occurrence.id.unwrap().to_string())
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect::<Vec<String>>();
+
+        let sse = SseBuilder::new(
+            SseTarget::create()
+                .set_event_name(SSE_EVENT_UPDATE_EVENT_DATA.to_string())
+                .set_user_ids(find_subscriber),
+            occurrence.clone(),
+        );
+        state.sse.send(sse).await;
+    }
+
+    info!(target:"update_series_occurrence","finish");
+    ApiResponse::ok(updated, &i18n.translate("update_series_occurrence.ok"))
+}
+
+fn build_occurrence(template: &EventDTO, series_id: Option<ObjectId>, datetime: DateTime) -> Event {
+    //keep the same duration as the template
+    let end_datetime = match (template.datetime, template.end_datetime) {
+        (Some(start), Some(end)) => Some(DateTime::from_millis(
+            datetime.timestamp_millis() + end.timestamp_millis() - start.timestamp_millis(),
+        )),
+        _ => None,
+    };
+    Event {
+        id: Some(ObjectId::new()),
+        host_id: template.host_id,
+        image_id: None,
+        theme_id: template.theme_id,
+        series_id,
+        invitation_id: "".to_string(),
+        slug: None,
+        previous_slugs: Vec::new(),
+        event_name: template.event_name.clone(),
+        event_description: template.event_description.clone(),
+        datetime: Some(datetime),
+        end_datetime,
+        timezone: template.timezone.clone(),
+        category: template.category.clone(),
+        venue_location: template.venue_location.clone(),
+        status: EVENT_STATUS_DRAFT.to_string(),
+        config: template.config.clone(),
+        revision: 0,
+        created_at: DateTime::now(),
+        updated_at: DateTime::now(),
+    }
+}
+
+async fn find_series_with_occurrence(
+    db: &Client,
+    series_id: ObjectId,
+    lang: &Lang,
+) -> Result<EventSeriesDTO, String> {
+    let find_series = DB::get(COLLECTION_EVENT_SERIES)
+        .filter(vec![is("_id", series_id)])
+        .lookup(&[many(COLLECTION_EVENTS, "series_id", "_id", "occurrences")])
+        .get_one::<EventSeriesDTO>(db)
+        .await;
+    if let Err(why) = find_series {
+        return Err(why);
+    }
+    let mut series = find_series.unwrap();
+    series.occurrences.sort_by_key(|event| event.datetime);
+    series.occurrences = series
+        .occurrences
+        .into_iter()
+        .map(|event| present_event(event, lang))
+        .collect::<Vec<EventDTO>>();
+    Ok(series)
+}
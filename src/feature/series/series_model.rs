@@ -0,0 +1,29 @@
+use bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::common::utils::{validate_series_frequency, validate_series_scope};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Validate)]
+pub struct CreateEventSeriesRequest {
+    #[validate(length(min = 1))]
+    pub event_id: String,
+    #[validate(custom(function = "validate_series_frequency"))]
+    pub frequency: String,
+    #[validate(range(min = 1, max = 12))]
+    pub interval: Option<i64>,
+    #[validate(range(min = 2, max = 52))]
+    pub count: Option<i64>,
+    pub until: Option<String>,
+    pub exceptions: Option<Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Validate)]
+pub struct UpdateSeriesOccurrenceRequest {
+    #[validate(custom(function = "validate_series_scope"))]
+    pub scope: String,
+    pub theme_id: Option<ObjectId>,
+    pub event_name: Option<String>,
+    pub event_description: Option<String>,
+    pub datetime: Option<String>,
+}
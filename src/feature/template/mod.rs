@@ -0,0 +1,483 @@
+use crate::common::api_response::ApiResponse;
+use crate::common::app_state::AppState;
+use crate::common::constant::{
+    COLLECTION_EVENT_GUEST, COLLECTION_EVENT_IMAGES, COLLECTION_EVENT_QUESTIONNAIRE,
+    COLLECTION_EVENT_ROLE_INVITATION, COLLECTION_EVENT_TEMPLATE, COLLECTION_EVENT_THEMES,
+    COLLECTION_EVENTS, COLLECTION_USERS, EVENT_GUEST_ROLE_CO_HOST, EVENT_GUEST_ROLE_GUEST,
+    EVENT_STATUS_DRAFT, NOTIFICATION_TYPE_ROLE_INVITATION, REDIS_KEY_USER_DISPLAY_NAME,
+    ROLE_INVITATION_STATUS_PENDING,
+};
+use crate::common::jwt::AuthContext;
+use crate::common::lang::Lang;
+use crate::common::middleware::Json;
+use crate::common::mongo::DB;
+use crate::common::mongo::filter::{is, not_equal};
+use crate::common::mongo::lookup::one;
+use crate::common::utils::create_object_id_option;
+use crate::dto::event_dto::EventDTO;
+use crate::dto::event_guest_dto::EventGuestDTO;
+use crate::dto::event_template_dto::EventTemplateDTO;
+use crate::entity::event::Event;
+use crate::entity::event_guest::EventGuest;
+use crate::entity::event_questionnaire::EventQuestionnaire;
+use crate::entity::event_role_invitation::EventRoleInvitation;
+use crate::entity::event_template::EventTemplate;
+use crate::feature::event::event_link::{generate_event_slug, generate_invitation_code};
+use crate::feature::event::event_lifecycle::notify_event_guests;
+use crate::feature::event::event_policy::is_event_host;
+use crate::feature::event::{copy_event_image, present_event};
+use crate::feature::template::template_model::{CreateEventTemplateRequest, DuplicateEventRequest};
+use crate::i18n;
+use axum::extract::{Path, State};
+use bson::DateTime;
+use bson::oid::ObjectId;
+use log::info;
+use mongodb::Client;
+use validator::Validate;
+
+pub mod template_model;
+
+//copy into a new draft owned by the caller, dates are cleared so the host pick the new schedule
+pub async fn duplicate_event(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Path(event_id): Path<String>,
+    Json(body): Json<DuplicateEventRequest>,
+) -> ApiResponse<EventDTO> {
+    info!(target:"duplicate_event","Starting...");
+    let i18n = i18n!("template", lang);
+    if let Err(err) = body.validate() {
+        info!(target:"duplicate_event","{:?}", err);
+        return ApiResponse::error_validation(
+            err,
+            &i18n.translate("duplicate_event.validation_error"),
+        );
+    }
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"duplicate_event","user id not found");
+        return ApiResponse::failed(&i18n.translate("duplicate_event.user_id_not_found"));
+    }
+    let current_user_id = auth_context.get_user_id().unwrap();
+
+    let create_event_id = create_object_id_option(&event_id);
+    if let None = create_event_id {
+        info!(target:"duplicate_event","event id invalid");
+        return ApiResponse::failed(&i18n.translate("duplicate_event.event_id_invalid"));
+    }
+    let create_event_id = create_event_id.unwrap();
+
+    let find_event = DB::get(COLLECTION_EVENTS)
+        .filter(vec![is("_id", create_event_id)])
+        .get_one::<EventDTO>(&state.db)
+        .await;
+    if let Err(why) = find_event {
+        info!(target:"duplicate_event","cannot find event {}",why);
+        return ApiResponse::not_found(&i18n.translate("duplicate_event.event_not_found"));
+    }
+    let source = find_event.unwrap();
+
+    if !is_event_host(&state.db, &source, current_user_id).await {
+        info!(target:"duplicate_event","only host can duplicate event");
+        return ApiResponse::access_denied(&i18n.translate("duplicate_event.not_host"));
+    }
+
+    let event_name = body.event_name.clone().unwrap_or(source.event_name.clone());
+    let slug = generate_event_slug(&state.db, &event_name, &Vec::new()).await;
+    let invitation_id = generate_invitation_code(&state.db, &Vec::new()).await;
+    let mut event = Event {
+        id: Some(ObjectId::new()),
+        host_id: Some(current_user_id),
+        image_id: None,
+        theme_id: source.theme_id,
+        series_id: None,
+        invitation_id,
+        slug: Some(slug),
+        previous_slugs: Vec::new(),
+        event_name,
+        event_description: source.event_description.clone(),
+        datetime: None,
+        end_datetime: None,
+        timezone: source.timezone.clone(),
+        category: source.category.clone(),
+        venue_location: source.venue_location.clone(),
+        status: EVENT_STATUS_DRAFT.to_string(),
+        config: source.config.clone(),
+        revision: 0,
+        created_at: DateTime::now(),
+        updated_at: DateTime::now(),
+    };
+
+    event.image_id = copy_event_image(&state.db, source.image_id, event.id).await;
+
+    let questionnaire = copy_questionnaire(
+        find_questionnaire(&state.db, create_event_id).await,
+        event.id,
+    );
+
+    //rsvp and check in belong to the old event, everyone is asked again
+    let guests = if body.include_guests.unwrap_or(false) {
+        find_copied_guests(&state.db, create_event_id, EVENT_GUEST_ROLE_GUEST, current_user_id)
+            .await
+            .into_iter()
+            .map(|guest| EventGuest {
+                id: Some(ObjectId::new()),
+                user_id: guest.user_id,
+                event_id: event.id,
+                role: guest.role,
+                rsvp: None,
+                checked_in_at: None,
+                created_at: DateTime::now(),
+                updated_at: DateTime::now(),
+            })
+            .collect::<Vec<EventGuest>>()
+    } else {
+        Vec::new()
+    };
+
+    //co-hosts go through the same acceptance as invite_co_host instead of getting the role directly
+    let co_host_invitations = if body.include_co_hosts.unwrap_or(false) {
+        find_copied_guests(&state.db, create_event_id, EVENT_GUEST_ROLE_CO_HOST, current_user_id)
+            .await
+            .into_iter()
+            .map(|guest| EventRoleInvitation {
+                id: Some(ObjectId::new()),
+                event_id: event.id,
+                user_id: guest.user_id,
+                invited_by: Some(current_user_id),
+                role: EVENT_GUEST_ROLE_CO_HOST.to_string(),
+                status: ROLE_INVITATION_STATUS_PENDING.to_string(),
+                responded_at: None,
+                created_at: DateTime::now(),
+                updated_at: DateTime::now(),
+            })
+            .collect::<Vec<EventRoleInvitation>>()
+    } else {
+        Vec::new()
+    };
+
+    let session = state.db.start_session().await;
+    if let Err(why) = session {
+        info!(target:"duplicate_event","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("duplicate_event.trx_failed"));
+    }
+    let mut session = session.unwrap();
+    let _ = session.start_transaction().await;
+
+    let save_event = DB::insert(COLLECTION_EVENTS)
+        .one_with_session(event.clone(), &state.db, &mut session)
+        .await;
+    if let Err(why) = save_event {
+        info!(target:"duplicate_event","{:?}",why);
+        let _abort = session.abort_transaction().await;
+        return ApiResponse::failed(&i18n.translate("duplicate_event.failed"));
+    }
+
+    if !questionnaire.is_empty() {
+        let save_questionnaire = DB::insert(COLLECTION_EVENT_QUESTIONNAIRE)
+            .many_with_session(questionnaire, &state.db, &mut session)
+            .await;
+        if let Err(why) = save_questionnaire {
+            info!(target:"duplicate_event","{:?}",why);
+            let _abort = session.abort_transaction().await;
+            return ApiResponse::failed(&i18n.translate("duplicate_event.failed"));
+        }
+    }
+
+    if !guests.is_empty() {
+        let save_guest = DB::insert(COLLECTION_EVENT_GUEST)
+            .many_with_session(guests, &state.db, &mut session)
+            .await;
+        if let Err(why) = save_guest {
+            info!(target:"duplicate_event","{:?}",why);
+            let _abort = session.abort_transaction().await;
+            return ApiResponse::failed(&i18n.translate("duplicate_event.failed"));
+        }
+    }
+    if !co_host_invitations.is_empty() {
+        let invited_ids = co_host_invitations
+            .iter()
+            .filter_map(|invitation| invitation.user_id)
+            .collect::<Vec<ObjectId>>();
+        let save_invitation = DB::insert(COLLECTION_EVENT_ROLE_INVITATION)
+            .many_with_session(co_host_invitations, &state.db, &mut session)
+            .await;
+        if let Err(why) = save_invitation {
+            info!(target:"duplicate_event","{:?}",why);
+            let _abort = session.abort_transaction().await;
+            return ApiResponse::failed(&i18n.translate("duplicate_event.failed"));
+        }
+
+        let title = format!(
+            "{} {}",
+            auth_context
+                .get(REDIS_KEY_USER_DISPLAY_NAME)
+                .unwrap_or(&i18n.translate("default_display_name")),
+            i18n.translate("duplicate_event.co_host_notification")
+        );
+        let target = EventDTO {
+            id: event.id,
+            ..source.clone()
+        };
+        let notify = notify_event_guests(
+            &state.db,
+            &mut session,
+            &target,
+            NOTIFICATION_TYPE_ROLE_INVITATION,
+            title,
+            invited_ids,
+        )
+        .await;
+        if let Err(why) = notify {
+            info!(target:"duplicate_event","{:?}",why);
+            let _abort = session.abort_transaction().await;
+            return ApiResponse::failed(&i18n.translate("duplicate_event.failed"));
+        }
+    }
+    let _commit = session.commit_transaction().await;
+
+    let find_duplicate = DB::get(COLLECTION_EVENTS)
+        .filter(vec![is("_id", event.id)])
+        .lookup(&[
+            one(COLLECTION_USERS, "host_id", "_id", "host"),
+            one(COLLECTION_EVENT_IMAGES, "image_id", "_id", "image"),
+            one(COLLECTION_EVENT_THEMES, "theme_id", "_id", "theme"),
+        ])
+        .get_one::<EventDTO>(&state.db)
+        .await;
+    if let Err(why) = find_duplicate {
+        info!(target:"duplicate_event","cannot find duplicate {}",why);
+        return ApiResponse::failed(&i18n.translate("duplicate_event.failed"));
+    }
+
+    info!(target:"duplicate_event","finish");
+    ApiResponse::ok(
+        present_event(find_duplicate.unwrap(), &lang),
+        &i18n.translate("duplicate_event.ok"),
+    )
+}
+
+//snapshot of an event the user host, later changes to the event do not touch the template
+pub async fn create_event_template(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Json(body): Json<CreateEventTemplateRequest>,
+) -> ApiResponse<EventTemplateDTO> {
+    info!(target:"create_event_template","Starting...");
+    let i18n = i18n!("template", lang);
+    if let Err(err) = body.validate() {
+        info!(target:"create_event_template","{:?}", err);
+        return ApiResponse::error_validation(
+            err,
+            &i18n.translate("create_event_template.validation_error"),
+        );
+    }
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"create_event_template","user id not found");
+        return ApiResponse::failed(&i18n.translate("create_event_template.user_id_not_found"));
+    }
+    let current_user_id = auth_context.get_user_id().unwrap();
+
+    let create_event_id = create_object_id_option(&body.event_id);
+    if let None = create_event_id {
+        info!(target:"create_event_template","event id invalid");
+        return ApiResponse::failed(&i18n.translate("create_event_template.event_id_invalid"));
+    }
+    let create_event_id = create_event_id.unwrap();
+
+    let find_event = DB::get(COLLECTION_EVENTS)
+        .filter(vec![is("_id", create_event_id)])
+        .get_one::<EventDTO>(&state.db)
+        .await;
+    if let Err(why) = find_event {
+        info!(target:"create_event_template","cannot find event {}",why);
+        return ApiResponse::not_found(&i18n.translate("create_event_template.event_not_found"));
+    }
+    let source = find_event.unwrap();
+
+    if !is_event_host(&state.db, &source, current_user_id).await {
+        info!(target:"create_event_template","only host can save template");
+        return ApiResponse::access_denied(&i18n.translate("create_event_template.not_host"));
+    }
+
+    let template_id = Some(ObjectId::new());
+    let template = EventTemplate {
+        id: template_id,
+        user_id: Some(current_user_id),
+        name: body.name.clone(),
+        image_id: copy_event_image(&state.db, source.image_id, template_id).await,
+        theme_id: source.theme_id,
+        event_name: source.event_name.clone(),
+        event_description: source.event_description.clone(),
+        timezone: source.timezone.clone(),
+        category: source.category.clone(),
+        venue_location: source.venue_location.clone(),
+        config: source.config.clone(),
+        questionnaire: copy_questionnaire(
+            find_questionnaire(&state.db, create_event_id).await,
+            None,
+        ),
+        created_at: DateTime::now(),
+        updated_at: DateTime::now(),
+    };
+
+    let save_template = DB::insert(COLLECTION_EVENT_TEMPLATE)
+        .one(template.clone(), &state.db)
+        .await;
+    if let Err(why) = save_template {
+        info!(target:"create_event_template","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("create_event_template.failed"));
+    }
+
+    info!(target:"create_event_template","finish");
+    ApiResponse::ok(
+        hide_template_password(template.into()),
+        &i18n.translate("create_event_template.ok"),
+    )
+}
+
+pub async fn get_event_templates(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+) -> ApiResponse<Vec<EventTemplateDTO>> {
+    info!(target:"get_event_templates","Starting...");
+    let i18n = i18n!("template", lang);
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"get_event_templates","user id not found");
+        return ApiResponse::failed(&i18n.translate("get_event_templates.user_id_not_found"));
+    }
+    let current_user_id = auth_context.get_user_id().unwrap();
+
+    let find_templates = DB::get(COLLECTION_EVENT_TEMPLATE)
+        .filter(vec![is("user_id", current_user_id)])
+        .sort(vec![("updated_at", -1)])
+        .get_all::<EventTemplateDTO>(&state.db)
+        .await;
+    if let Err(why) = find_templates {
+        info!(target:"get_event_templates","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("get_event_templates.failed"));
+    }
+
+    info!(target:"get_event_templates","finish");
+    ApiResponse::ok(
+        find_templates
+            .unwrap()
+            .into_iter()
+            .map(hide_template_password)
+            .collect::<Vec<EventTemplateDTO>>(),
+        &i18n.translate("get_event_templates.ok"),
+    )
+}
+
+pub async fn delete_event_template(
+    state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Path(template_id): Path<String>,
+) -> ApiResponse<String> {
+    info!(target:"delete_event_template","Starting...");
+    let i18n = i18n!("template", lang);
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"delete_event_template","user id not found");
+        return ApiResponse::failed(&i18n.translate("delete_event_template.user_id_not_found"));
+    }
+    let current_user_id = auth_context.get_user_id().unwrap();
+
+    let create_template_id = create_object_id_option(&template_id);
+    if let None = create_template_id {
+        info!(target:"delete_event_template","template id invalid");
+        return ApiResponse::failed(&i18n.translate("delete_event_template.template_id_invalid"));
+    }
+
+    let delete_template = DB::delete(COLLECTION_EVENT_TEMPLATE)
+        .filter(vec![
+            is("_id", create_template_id.unwrap()),
+            is("user_id", current_user_id),
+        ])
+        .one(&state.db)
+        .await;
+    if let Err(why) = delete_template {
+        info!(target:"delete_event_template","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("delete_event_template.failed"));
+    }
+    if delete_template.unwrap() == 0 {
+        info!(target:"delete_event_template","template not found");
+        return ApiResponse::not_found(&i18n.translate("delete_event_template.not_found"));
+    }
+
+    info!(target:"delete_event_template","finish");
+    ApiResponse::ok(template_id, &i18n.translate("delete_event_template.ok"))
+}
+
+//only the owner can start from a template
+pub async fn find_user_template(
+    db: &Client,
+    template_id: &String,
+    user_id: ObjectId,
+) -> Result<EventTemplate, String> {
+    let create_template_id = create_object_id_option(template_id);
+    if let None = create_template_id {
+        return Err("template_id_invalid".to_string());
+    }
+    DB::get(COLLECTION_EVENT_TEMPLATE)
+        .filter(vec![
+            is("_id", create_template_id.unwrap()),
+            is("user_id", user_id),
+        ])
+        .get_one::<EventTemplate>(db)
+        .await
+        .map_err(|_| "template_not_found".to_string())
+}
+
+pub fn copy_questionnaire(
+    questionnaire: Vec<EventQuestionnaire>,
+    event_id: Option<ObjectId>,
+) -> Vec<EventQuestionnaire> {
+    questionnaire
+        .into_iter()
+        .map(|question| EventQuestionnaire {
+            id: Some(ObjectId::new()),
+            event_id,
+            created_at: DateTime::now(),
+            updated_at: DateTime::now(),
+            ..question
+        })
+        .collect::<Vec<EventQuestionnaire>>()
+}
+
+async fn find_questionnaire(db: &Client, event_id: ObjectId) -> Vec<EventQuestionnaire> {
+    DB::get(COLLECTION_EVENT_QUESTIONNAIRE)
+        .filter(vec![is("event_id", event_id)])
+        .sort(vec![("ordering", 1)])
+        .get_all::<EventQuestionnaire>(db)
+        .await
+        .unwrap_or(Vec::new())
+}
+
+async fn find_copied_guests(
+    db: &Client,
+    event_id: ObjectId,
+    role: &str,
+    current_user_id: ObjectId,
+) -> Vec<EventGuestDTO> {
+    DB::get(COLLECTION_EVENT_GUEST)
+        .filter(vec![
+            is("event_id", event_id),
+            is("role", role),
+            not_equal("user_id", current_user_id),
+        ])
+        .get_all::<EventGuestDTO>(db)
+        .await
+        .unwrap_or(Vec::new())
+}
+
+fn hide_template_password(mut template: EventTemplateDTO) -> EventTemplateDTO {
+    template.config.event_password = None;
+    template
+}
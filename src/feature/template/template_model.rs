@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Validate)]
+pub struct DuplicateEventRequest {
+    #[validate(length(min = 1))]
+    pub event_name: Option<String>,
+    pub include_co_hosts: Option<bool>,
+    pub include_guests: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Validate)]
+pub struct CreateEventTemplateRequest {
+    #[validate(length(min = 1))]
+    pub event_id: String,
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+}
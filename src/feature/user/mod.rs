@@ -1,26 +1,41 @@
 use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::Html;
 use bson::oid::ObjectId;
 use bson::{DateTime, Document, doc};
 use log::info;
 
 use crate::common::api_response::{PaginationRequest, PagingResponse};
 use crate::common::constant::{
-    BUCKET_PROFILE_PICTURE, COLLECTION_MUTUAL, COLLECTION_USERS, PATH_PROFILE_PICTURE,
+    BUCKET_PROFILE_PICTURE, COLLECTION_MUTUAL, COLLECTION_USER_STATUS_LOG, COLLECTION_USERS,
+    PATH_PROFILE_PICTURE, REDIS_KEY_USER_DISPLAY_NAME, REDIS_KEY_USER_EMAIL, REDIS_KEY_USER_ID,
+    USER_STATUS_ACTIVE, USER_STATUS_INACTIVE, USER_STATUS_SUSPENDED,
 };
+use crate::common::env_config::EnvConfig;
+use crate::common::i18n::{I18n, replace_placeholders};
+use crate::common::middleware::Json;
 use crate::common::minio::MinIO;
 use crate::common::mongo::DB;
-use crate::common::mongo::filter::{equal, is};
+use crate::common::mongo::filter::{is, not_equal};
 use crate::common::mongo::lookup::one;
 use crate::common::multipart_file::SingleFileExtractor;
-use crate::common::utils::create_object_id_option;
+use crate::common::permission::permission::app;
+use crate::common::smtp::SmtpClient;
+use crate::common::utils::{create_object_id_option, generate_secret_token};
 use crate::dto::mutual_dto::MutualDTO;
 use crate::dto::profile_picture_dto::ProfilePictureDTO;
+use crate::dto::user_metadata_dto::UserMetaDataDTO;
+use crate::entity::user_status_log::UserStatusLog;
+use crate::feature::mutual::{is_blocked_between, is_friend};
+use crate::feature::user::user_model::{UpdateProfileRequest, UpdateUserStatusRequest};
+use std::collections::HashMap;
+use mongodb::{Client, ClientSession};
+use validator::Validate;
 
 use crate::dto::user_dto::UserDTO;
 use crate::{
     common::{
-        api_response::ApiResponse, app_state::AppState, constant::REDIS_KEY_USER_EMAIL,
-        jwt::AuthContext, lang::Lang,
+        api_response::ApiResponse, app_state::AppState, jwt::AuthContext, lang::Lang,
     },
     i18n,
 };
@@ -33,13 +48,13 @@ pub async fn get_my_profile(
     auth_context: AuthContext,
 ) -> ApiResponse<UserDTO> {
     let i18n = i18n!("user", lang);
-    //getting connection from pool
-    let user_email = auth_context
-        .get(REDIS_KEY_USER_EMAIL)
-        .map_or_else(|| "".to_string(), |v| v.clone());
+    if let None = auth_context.get_user_id() {
+        info!(target:"user::profile::failed","session not found");
+        return ApiResponse::not_found(i18n.translate("user.get-profile.not-found").as_str());
+    }
 
     let find_user = DB::get(COLLECTION_USERS)
-        .filter(vec![equal("email", &user_email)])
+        .filter(vec![is("_id", auth_context.get_user_id().unwrap())])
         .get_one::<UserDTO>(&state.db)
         .await;
 
@@ -61,17 +76,15 @@ pub async fn update_profile_picture(
 ) -> ApiResponse<UserDTO> {
     let i18n = i18n!("user", lang);
 
-    let user_email: Option<&String> = auth_context.get(REDIS_KEY_USER_EMAIL);
-    if let None = user_email {
+    if let None = auth_context.get_user_id() {
         info!(target:"user::profile-picture::failed","session not found");
         return ApiResponse::access_denied(
             i18n.translate("user.update-profile-picture.not-found")
                 .as_str(),
         );
     }
-    let user_email = user_email.unwrap();
     let find_user = DB::get(COLLECTION_USERS)
-        .filter(vec![equal("email", &user_email)])
+        .filter(vec![is("_id", auth_context.get_user_id().unwrap())])
         .get_one::<UserDTO>(&state.db)
         .await;
 
@@ -147,6 +160,305 @@ pub async fn update_profile_picture(
     ApiResponse::ok(user, i18n.translate("user.profile.failed").as_str())
 }
 
+pub async fn update_profile(
+    mut state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Json(body): Json<UpdateProfileRequest>,
+) -> ApiResponse<UserDTO> {
+    info!(target:"update_profile","Starting...");
+    let i18n = i18n!("user", lang);
+
+    if let None = auth_context.get_user_id() {
+        info!(target:"update_profile","session not found");
+        return ApiResponse::access_denied(&i18n.translate("user.update-profile.not-found"));
+    }
+    let current_user_id = auth_context.get_user_id().unwrap();
+
+    let validate = body.validate();
+    if let Err(err) = validate {
+        info!(target:"update_profile","validation error {:?}",err);
+        return ApiResponse::error_validation(
+            err,
+            &i18n.translate("user.update-profile.validation.error"),
+        );
+    }
+
+    let find_user = DB::get(COLLECTION_USERS)
+        .filter(vec![is("_id", current_user_id)])
+        .get_one::<UserDTO>(&state.db)
+        .await;
+    if let Err(why) = find_user {
+        info!(target:"update_profile","{}",why);
+        return ApiResponse::not_found(&i18n.translate("user.update-profile.not-found"));
+    }
+    let user = find_user.unwrap();
+
+    let mut update = doc! {
+        "updated_at":DateTime::now()
+    };
+    let display_name = body
+        .display_name
+        .clone()
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty() && name != &user.display_name);
+    if let Some(name) = display_name.clone() {
+        update.insert("display_name", name);
+    }
+    if let Some(meta_data) = body.user_meta_data.clone() {
+        let mut current = user.user_meta_data.clone().unwrap_or(UserMetaDataDTO {
+            providers: None,
+            bio: None,
+            gender: None,
+            date_of_birth: None,
+        });
+        if let Some(bio) = meta_data.bio {
+            current.bio = Some(bio);
+        }
+        if let Some(gender) = meta_data.gender {
+            current.gender = Some(gender);
+        }
+        if let Some(date_of_birth) = meta_data.date_of_birth {
+            current.date_of_birth = Some(date_of_birth);
+        }
+        update.insert(
+            "user_meta_data",
+            bson::to_document(&current).unwrap_or(Document::new()),
+        );
+    }
+
+    let email = body
+        .email
+        .clone()
+        .map(|email| email.trim().to_lowercase())
+        .filter(|email| email != &user.email);
+    let mut verification_token: Option<String> = None;
+    if let Some(email) = email.clone() {
+        let find_duplicate_email = DB::get(COLLECTION_USERS)
+            .filter(vec![is("email", email.clone()), not_equal("_id", current_user_id)])
+            .count(&state.db)
+            .await
+            .unwrap_or(0);
+        if find_duplicate_email > 0 {
+            info!(target:"update_profile","email already used");
+            return ApiResponse::failed(&i18n.translate("user.update-profile.email.in-use"));
+        }
+
+        let token = generate_secret_token();
+        let save_verification = state.redis.set_session_email_verification(
+            &token,
+            &[
+                (REDIS_KEY_USER_ID, current_user_id.to_string()),
+                (REDIS_KEY_USER_EMAIL, email),
+            ],
+        );
+        if let Err(why) = save_verification {
+            info!(target:"update_profile","{:?}",why);
+            return ApiResponse::failed(&i18n.translate("user.update-profile.failed"));
+        }
+        update.insert("confirmation_sent_at", DateTime::now());
+        verification_token = Some(token);
+    }
+
+    let session = state.db.start_session().await;
+    if let Err(why) = session {
+        info!(target:"update_profile","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("user.update-profile.failed"));
+    }
+    let mut session = session.unwrap();
+    let _ = session.start_transaction().await;
+
+    let update_user = DB::update(COLLECTION_USERS)
+        .filter(vec![is("_id", current_user_id)])
+        .set(update)
+        .execute_with_session(&state.db, &mut session)
+        .await;
+    if let Err(why) = update_user {
+        info!(target:"update_profile","{}",why);
+        let _ = session.abort_transaction().await;
+        return ApiResponse::failed(&i18n.translate("user.update-profile.failed"));
+    }
+
+    //mutual keep a copy of the name for text search
+    if let Some(name) = display_name.clone() {
+        let update_mutual = DB::update(COLLECTION_MUTUAL)
+            .filter(vec![is("mutual_id", current_user_id)])
+            .set(doc! {
+                "display_name":name,
+                "updated_at":DateTime::now()
+            })
+            .execute_many_with_session(&state.db, &mut session)
+            .await;
+        if let Err(why) = update_mutual {
+            info!(target:"update_profile","{}",why);
+            let _ = session.abort_transaction().await;
+            return ApiResponse::failed(&i18n.translate("user.update-profile.failed"));
+        }
+    }
+    let _ = session.commit_transaction().await;
+
+    if let Some(name) = display_name {
+        let _refresh = state.redis.refresh_session_user(
+            &current_user_id.to_string(),
+            &[(REDIS_KEY_USER_DISPLAY_NAME, name)],
+        );
+    }
+
+    if let (Some(email), Some(token)) = (email, verification_token) {
+        let base_url = EnvConfig::init().base_url.trim_end_matches('/').to_string();
+        let mut args = HashMap::new();
+        args.insert(
+            "link".to_string(),
+            format!("{}/user/email/verify/{}", base_url, token),
+        );
+        let email_subject = i18n.translate("user.update-profile.email.subject");
+        let email_body = i18n.translate_with_args("user.update-profile.email.body", args);
+
+        //do not hold the response for the mail delivery
+        tokio::spawn(async move {
+            SmtpClient::new(&email)
+                .send(&email_subject, &email_body)
+                .await;
+        });
+    }
+
+    let find_user = DB::get(COLLECTION_USERS)
+        .filter(vec![is("_id", current_user_id)])
+        .get_one::<UserDTO>(&state.db)
+        .await;
+    if let Err(why) = find_user {
+        info!(target:"update_profile","{}",why);
+        return ApiResponse::not_found(&i18n.translate("user.update-profile.not-found"));
+    }
+
+    info!(target:"update_profile","finish");
+    ApiResponse::ok(
+        find_user.unwrap(),
+        &i18n.translate("user.update-profile.success"),
+    )
+}
+
+const VERIFY_EMAIL_TEMPLATE: &str = include_str!("verify_email.html");
+const VERIFY_EMAIL_RESULT_TEMPLATE: &str = include_str!("verify_email_result.html");
+
+//opened from the verification email. link scanner prefetch GET, so the page only
+//ask for a confirmation and the token is consumed by the POST of the same url
+pub async fn get_verify_email_page(lang: Lang, Path(_token): Path<String>) -> Html<String> {
+    let i18n = i18n!("user", lang);
+    let title = i18n.translate("user.verify-email.page.title");
+    let description = i18n.translate("user.verify-email.page.description");
+    let confirm = i18n.translate("user.verify-email.page.confirm");
+    let mut values = HashMap::new();
+    values.insert("lang", lang.get());
+    values.insert("title", title.as_str());
+    values.insert("description", description.as_str());
+    values.insert("confirm", confirm.as_str());
+    Html(replace_placeholders(VERIFY_EMAIL_TEMPLATE, &values))
+}
+
+//the token is the only credential
+pub async fn verify_email(
+    mut state: State<AppState>,
+    lang: Lang,
+    Path(token): Path<String>,
+) -> (StatusCode, Html<String>) {
+    info!(target:"verify_email","Starting...");
+    let i18n = i18n!("user", lang);
+
+    let get_session = state.redis.get_session_email_verification(&token);
+    if let Err(why) = get_session {
+        info!(target:"verify_email","{:?}",why);
+        return verify_email_result(
+            &i18n,
+            &lang,
+            StatusCode::NOT_FOUND,
+            "user.verify-email.expired",
+        );
+    }
+    let verification = get_session.unwrap();
+    let user_id = verification
+        .get(REDIS_KEY_USER_ID)
+        .map_or_else(|| None, |id| create_object_id_option(id));
+    let email = verification.get(REDIS_KEY_USER_EMAIL).cloned();
+    if user_id.is_none() || email.is_none() {
+        info!(target:"verify_email","verification expired or invalid");
+        return verify_email_result(
+            &i18n,
+            &lang,
+            StatusCode::NOT_FOUND,
+            "user.verify-email.expired",
+        );
+    }
+    let user_id = user_id.unwrap();
+    let email = email.unwrap();
+
+    //another account may take the email while this link is pending
+    let find_duplicate_email = DB::get(COLLECTION_USERS)
+        .filter(vec![is("email", email.clone()), not_equal("_id", user_id)])
+        .count(&state.db)
+        .await
+        .unwrap_or(0);
+    if find_duplicate_email > 0 {
+        info!(target:"verify_email","email already used");
+        let _ = state.redis.delete_session_email_verification(&token);
+        return verify_email_result(
+            &i18n,
+            &lang,
+            StatusCode::CONFLICT,
+            "user.update-profile.email.in-use",
+        );
+    }
+
+    let update_user = DB::update(COLLECTION_USERS)
+        .filter(vec![is("_id", user_id)])
+        .set(doc! {
+            "email":email.clone(),
+            "confirmation_at":DateTime::now(),
+            "updated_at":DateTime::now()
+        })
+        .execute(&state.db)
+        .await;
+    if let Err(why) = update_user {
+        info!(target:"verify_email","{}",why);
+        return verify_email_result(
+            &i18n,
+            &lang,
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "user.verify-email.failed",
+        );
+    }
+    let _ = state.redis.delete_session_email_verification(&token);
+    let _refresh = state
+        .redis
+        .refresh_session_user(&user_id.to_string(), &[(REDIS_KEY_USER_EMAIL, email)]);
+
+    info!(target:"verify_email","finish");
+    verify_email_result(&i18n, &lang, StatusCode::OK, "user.verify-email.success")
+}
+
+//the POST is submitted by the browser form, so the outcome is a page not json
+fn verify_email_result(
+    i18n: &I18n,
+    lang: &Lang,
+    status: StatusCode,
+    key: &str,
+) -> (StatusCode, Html<String>) {
+    let title = if status == StatusCode::OK {
+        i18n.translate("user.verify-email.page.success-title")
+    } else {
+        i18n.translate("user.verify-email.page.failed-title")
+    };
+    let description = i18n.translate(key);
+    let mut values = HashMap::new();
+    values.insert("lang", lang.get());
+    values.insert("title", title.as_str());
+    values.insert("description", description.as_str());
+    (
+        status,
+        Html(replace_placeholders(VERIFY_EMAIL_RESULT_TEMPLATE, &values)),
+    )
+}
+
 pub async fn get_user_profile(
     state: State<AppState>,
     lang: Lang,
@@ -165,6 +477,18 @@ pub async fn get_user_profile(
         return ApiResponse::failed(i18n.translate("user.profile.not-found").as_str());
     }
 
+    //blocked profile look the same as missing one
+    if is_blocked_between(
+        &state.db,
+        auth_context.get_user_id().unwrap(),
+        create_user_id.unwrap(),
+    )
+    .await
+    {
+        info!(target:"user::profile::failed","blocked");
+        return ApiResponse::not_found(i18n.translate("user.profile.not-found").as_str());
+    }
+
     let data = DB::get(COLLECTION_USERS)
         .lookup(&[one("user-profile", "_id", "_id", "profile")])
         .filter(vec![is("_id", create_user_id.unwrap())])
@@ -204,8 +528,8 @@ pub async fn get_list_mutual(
     }
 
     let data = data
-        .lookup(&[one(COLLECTION_USERS, "user_id", "_id", "mutual")])
-        .filter(vec![is("user_id", create_user_id.unwrap())])
+        .lookup(&[one(COLLECTION_USERS, "mutual_id", "_id", "mutual")])
+        .filter(vec![is("user_id", create_user_id.unwrap()), is_friend()])
         .sort(vec![("mutual.display_name", 1)])
         .get_per_page::<MutualDTO>(query.page.unwrap_or(1), query.size.unwrap_or(10), &state.db)
         .await;
@@ -217,3 +541,219 @@ pub async fn get_list_mutual(
 
     ApiResponse::ok(data.unwrap(), &i18n.translate("user.profile.not-found"))
 }
+
+pub async fn suspend_user(
+    mut state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Path(user_id): Path<String>,
+    Json(body): Json<UpdateUserStatusRequest>,
+) -> ApiResponse<UserDTO> {
+    info!(target:"suspend_user","Starting...");
+    let i18n = i18n!("user", lang);
+
+    if !auth_context.authorize(app::moderation::USER) {
+        info!(target:"suspend_user","not a moderator");
+        return ApiResponse::access_denied(&i18n.translate("suspend_user.not_allowed"));
+    }
+
+    let validate = body.validate();
+    if let Err(err) = validate {
+        info!(target:"suspend_user","validation error {:?}",err);
+        return ApiResponse::error_validation(err, &i18n.translate("suspend_user.validation_error"));
+    }
+
+    let create_user_id = create_object_id_option(&user_id);
+    if let None = create_user_id {
+        info!(target:"suspend_user","user id invalid");
+        return ApiResponse::failed(&i18n.translate("suspend_user.user_id_invalid"));
+    }
+    let create_user_id = create_user_id.unwrap();
+    if auth_context.get_user_id() == Some(create_user_id) {
+        info!(target:"suspend_user","cannot suspend self");
+        return ApiResponse::failed(&i18n.translate("suspend_user.cannot_self"));
+    }
+
+    let find_user = DB::get(COLLECTION_USERS)
+        .filter(vec![is("_id", create_user_id)])
+        .get_one::<UserDTO>(&state.db)
+        .await;
+    if let Err(why) = find_user {
+        info!(target:"suspend_user","{}",why);
+        return ApiResponse::not_found(&i18n.translate("suspend_user.not_found"));
+    }
+    if find_user.unwrap().status == Some(USER_STATUS_SUSPENDED.to_string()) {
+        info!(target:"suspend_user","already suspended");
+        return ApiResponse::failed(&i18n.translate("suspend_user.already_suspended"));
+    }
+
+    let session = state.db.start_session().await;
+    if let Err(why) = session {
+        info!(target:"suspend_user","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("suspend_user.failed"));
+    }
+    let mut session = session.unwrap();
+    let _ = session.start_transaction().await;
+
+    let update_status = set_user_status(
+        &state.db,
+        &mut session,
+        create_user_id,
+        USER_STATUS_SUSPENDED,
+        Some(body.reason),
+        auth_context.get_user_id(),
+    )
+    .await;
+    if let Err(why) = update_status {
+        info!(target:"suspend_user","{}",why);
+        let _ = session.abort_transaction().await;
+        return ApiResponse::failed(&i18n.translate("suspend_user.failed"));
+    }
+    let _ = session.commit_transaction().await;
+
+    let _clear_status = state
+        .redis
+        .delete_cache_user_status(&create_user_id.to_string());
+    //signed in devices are logged out right away instead of waiting for the session to expire
+    let _revoke = state.redis.revoke_session_user(&create_user_id.to_string());
+
+    let find_user = DB::get(COLLECTION_USERS)
+        .filter(vec![is("_id", create_user_id)])
+        .get_one::<UserDTO>(&state.db)
+        .await;
+    if let Err(why) = find_user {
+        info!(target:"suspend_user","{}",why);
+        return ApiResponse::not_found(&i18n.translate("suspend_user.not_found"));
+    }
+
+    info!(target:"suspend_user","finish");
+    ApiResponse::ok(find_user.unwrap(), &i18n.translate("suspend_user.ok"))
+}
+
+pub async fn reactivate_user(
+    mut state: State<AppState>,
+    lang: Lang,
+    auth_context: AuthContext,
+    Path(user_id): Path<String>,
+    Json(body): Json<UpdateUserStatusRequest>,
+) -> ApiResponse<UserDTO> {
+    info!(target:"reactivate_user","Starting...");
+    let i18n = i18n!("user", lang);
+
+    if !auth_context.authorize(app::moderation::USER) {
+        info!(target:"reactivate_user","not a moderator");
+        return ApiResponse::access_denied(&i18n.translate("reactivate_user.not_allowed"));
+    }
+
+    let validate = body.validate();
+    if let Err(err) = validate {
+        info!(target:"reactivate_user","validation error {:?}",err);
+        return ApiResponse::error_validation(
+            err,
+            &i18n.translate("reactivate_user.validation_error"),
+        );
+    }
+
+    let create_user_id = create_object_id_option(&user_id);
+    if let None = create_user_id {
+        info!(target:"reactivate_user","user id invalid");
+        return ApiResponse::failed(&i18n.translate("reactivate_user.user_id_invalid"));
+    }
+    let create_user_id = create_user_id.unwrap();
+
+    let find_user = DB::get(COLLECTION_USERS)
+        .filter(vec![is("_id", create_user_id)])
+        .get_one::<UserDTO>(&state.db)
+        .await;
+    if let Err(why) = find_user {
+        info!(target:"reactivate_user","{}",why);
+        return ApiResponse::not_found(&i18n.translate("reactivate_user.not_found"));
+    }
+    let disabled = [USER_STATUS_SUSPENDED, USER_STATUS_INACTIVE];
+    let status = find_user.unwrap().status.unwrap_or_default();
+    if !disabled.contains(&status.as_str()) {
+        info!(target:"reactivate_user","user is not disabled {}",status);
+        return ApiResponse::failed(&i18n.translate("reactivate_user.not_disabled"));
+    }
+
+    let session = state.db.start_session().await;
+    if let Err(why) = session {
+        info!(target:"reactivate_user","{:?}",why);
+        return ApiResponse::failed(&i18n.translate("reactivate_user.failed"));
+    }
+    let mut session = session.unwrap();
+    let _ = session.start_transaction().await;
+
+    let update_status = set_user_status(
+        &state.db,
+        &mut session,
+        create_user_id,
+        USER_STATUS_ACTIVE,
+        Some(body.reason),
+        auth_context.get_user_id(),
+    )
+    .await;
+    if let Err(why) = update_status {
+        info!(target:"reactivate_user","{}",why);
+        let _ = session.abort_transaction().await;
+        return ApiResponse::failed(&i18n.translate("reactivate_user.failed"));
+    }
+    let _ = session.commit_transaction().await;
+    let _clear_status = state
+        .redis
+        .delete_cache_user_status(&create_user_id.to_string());
+
+    let find_user = DB::get(COLLECTION_USERS)
+        .filter(vec![is("_id", create_user_id)])
+        .get_one::<UserDTO>(&state.db)
+        .await;
+    if let Err(why) = find_user {
+        info!(target:"reactivate_user","{}",why);
+        return ApiResponse::not_found(&i18n.translate("reactivate_user.not_found"));
+    }
+
+    info!(target:"reactivate_user","finish");
+    ApiResponse::ok(find_user.unwrap(), &i18n.translate("reactivate_user.ok"))
+}
+
+//change account status and keep the history, caller revoke the redis session after commit
+pub async fn set_user_status(
+    db: &Client,
+    session: &mut ClientSession,
+    user_id: ObjectId,
+    status: &str,
+    reason: Option<String>,
+    changed_by: Option<ObjectId>,
+) -> Result<(), String> {
+    let find_user = DB::get(COLLECTION_USERS)
+        .filter(vec![is("_id", user_id)])
+        .get_one::<UserDTO>(db)
+        .await?;
+
+    let update_user = DB::update(COLLECTION_USERS)
+        .filter(vec![is("_id", user_id)])
+        .set(doc! {
+            "status":status,
+            "status_reason":reason.clone(),
+            "updated_at":DateTime::now()
+        })
+        .execute_with_session(db, session)
+        .await?;
+    if update_user == 0 {
+        return Err("user not updated".to_string());
+    }
+
+    let log = UserStatusLog {
+        id: Some(ObjectId::new()),
+        user_id: Some(user_id),
+        previous_status: find_user.status,
+        status: status.to_string(),
+        reason,
+        changed_by,
+        created_at: DateTime::now(),
+    };
+    DB::insert(COLLECTION_USER_STATUS_LOG)
+        .one_with_session(log, db, session)
+        .await?;
+    Ok(())
+}
@@ -1,3 +1,4 @@
+use crate::common::utils::{validate_date_of_birth, validate_gender};
 use crate::dto::user_dto::UserDTO;
 use serde::{Deserialize, Serialize};
 use validator::Validate;
@@ -14,3 +15,30 @@ pub struct ChangePasswordRequest {
 pub struct UserWithProfiledResponse {
     pub user: Option<UserDTO>,
 }
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, Validate)]
+pub struct UpdateUserStatusRequest {
+    #[validate(length(min = 1, max = 1000))]
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, Validate)]
+pub struct UpdateProfileRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub display_name: Option<String>,
+    //a changed email only applied after the verification link is opened
+    #[validate(email)]
+    pub email: Option<String>,
+    #[validate(nested)]
+    pub user_meta_data: Option<UpdateUserMetaDataRequest>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, Validate)]
+pub struct UpdateUserMetaDataRequest {
+    #[validate(length(max = 500))]
+    pub bio: Option<String>,
+    #[validate(custom(function = "validate_gender"))]
+    pub gender: Option<String>,
+    #[validate(custom(function = "validate_date_of_birth"))]
+    pub date_of_birth: Option<String>,
+}
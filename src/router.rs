@@ -9,6 +9,9 @@ pub fn init_routes(state: AppState) -> Router {
     Router::new()
         .route("/", get(routes::index::index))
         .route("/sync-locales", get(routes::index::generate_locales))
+        //PREVIEW
+        .route("/p/{slug}", get(feature::preview::get_event_preview_by_slug))
+        .route("/i/{code}", get(feature::preview::get_event_preview_by_code))
         //SSE
         .route("/sse/list", get(feature::sse::sse::get_active_subscriber))
         .route("/sse/register", get(feature::sse::sse::register_sse))
@@ -31,8 +34,23 @@ pub fn init_routes(state: AppState) -> Router {
         .route("/auth/otp", post(feature::auth::auth_otp))
         .route("/auth/verify-otp", post(feature::auth::verify_otp))
         .route("/auth/resend-otp", post(feature::auth::resend_otp))
+        .route(
+            "/auth/change-phone",
+            post(feature::auth::request_change_phone),
+        )
+        .route(
+            "/auth/change-phone/verify",
+            post(feature::auth::verify_change_phone),
+        )
         //USER
-        .route("/user/profile", get(feature::user::get_my_profile))
+        .route(
+            "/user/profile",
+            get(feature::user::get_my_profile).put(feature::user::update_profile),
+        )
+        .route(
+            "/user/email/verify/{token}",
+            get(feature::user::get_verify_email_page).post(feature::user::verify_email),
+        )
         .route("/user/public-profile", get(feature::user::get_user_profile))
         .route(
             "/user/update-profile-picture",
@@ -41,9 +59,28 @@ pub fn init_routes(state: AppState) -> Router {
         //EVENT
         .route("/event/hosted", get(feature::event::get_hosted_events))
         .route("/event/invited", get(feature::event::get_invited_event))
-        .route("/event/get-host",get(feature::event::get_event_host))
-        .route("/event/get-guest",get(feature::event::get_event_guest))
+        .route("/event/nearby", get(feature::event::get_nearby_events))
+        .route("/event/search", get(feature::event::search_events))
+        .route("/event/discover", get(feature::discovery::get_discovery_feed))
+        .route("/event/{event_id}", get(feature::event::get_event_detail))
+        .route("/event/slug/{slug}", get(feature::event::get_event_by_slug))
+        .route("/event/code/{code}", get(feature::event::get_event_by_code))
+        .route("/event/{event_id}/host", get(feature::event::get_event_host))
+        .route("/event/{event_id}/guest", get(feature::event::get_event_guest))
         .route("/event/create", post(feature::event::create_new_event))
+        .route(
+            "/event/{event_id}/duplicate",
+            post(feature::template::duplicate_event),
+        )
+        //TEMPLATE
+        .route(
+            "/event/template",
+            get(feature::template::get_event_templates).post(feature::template::create_event_template),
+        )
+        .route(
+            "/event/template/{template_id}",
+            delete(feature::template::delete_event_template),
+        )
         .route(
             "/event/upload-image",
             post(feature::event::upload_event_image),
@@ -64,14 +101,177 @@ pub fn init_routes(state: AppState) -> Router {
             "/event/update-event-guest",
             post(feature::event::update_event_guest),
         )
+        .route("/event/update-rsvp", post(feature::event::update_event_rsvp))
+        .route(
+            "/event/{event_id}/status",
+            post(feature::event::update_event_status),
+        )
         .route("/event/delete", delete(feature::event::delete_event))
         .route("/event/create-invitation-link",post(feature::invitation::create_invitation_link))
         .route("/event/send-invitation",post(feature::invitation::send_invitation))
+        .route(
+            "/event/{event_id}/invitation-link",
+            get(feature::invitation::get_invitation_links),
+        )
+        .route(
+            "/event/invitation-link/{invitation_id}/revoke",
+            post(feature::invitation::revoke_invitation_link),
+        )
+        //GUEST
+        .route("/event/{event_id}/leave", post(feature::guest::leave_event))
+        .route(
+            "/event/{event_id}/guest/{user_id}/remove",
+            post(feature::guest::remove_event_guest),
+        )
+        .route("/event/{event_id}/ban", get(feature::guest::get_event_bans))
+        .route(
+            "/event/{event_id}/ban/{user_id}",
+            delete(feature::guest::unban_event_guest),
+        )
+        //HOST
+        .route(
+            "/event/{event_id}/co-host/invite",
+            post(feature::host::invite_co_host),
+        )
+        .route(
+            "/event/{event_id}/transfer-host",
+            post(feature::host::transfer_event_host),
+        )
+        .route(
+            "/event/role-invitation",
+            get(feature::host::get_my_role_invitations),
+        )
+        .route(
+            "/event/role-invitation/{invitation_id}/respond",
+            post(feature::host::respond_role_invitation),
+        )
+        .route(
+            "/event/role-invitation/{invitation_id}",
+            delete(feature::host::cancel_role_invitation),
+        )
+        //SERIES
+        .route(
+            "/event/series/create",
+            post(feature::series::create_event_series),
+        )
+        .route(
+            "/event/series/{series_id}",
+            get(feature::series::get_event_series),
+        )
+        .route(
+            "/event/series/occurrence/{event_id}",
+            put(feature::series::update_series_occurrence),
+        )
+        //ACTIVITY
+        .route(
+            "/event/{event_id}/timeline",
+            get(feature::activity::get_event_timeline),
+        )
+        //CHECK IN
+        .route(
+            "/event/{event_id}/check-in/token",
+            get(feature::check_in::get_check_in_token),
+        )
+        .route(
+            "/event/{event_id}/check-in/qr",
+            get(feature::check_in::get_check_in_qr),
+        )
+        .route(
+            "/event/check-in/scan",
+            post(feature::check_in::scan_check_in),
+        )
+        //CALENDAR
+        .route(
+            "/event/{event_id}/ics",
+            get(feature::calendar::export_event_ics),
+        )
+        .route(
+            "/calendar/feed-url",
+            get(feature::calendar::get_calendar_feed_url),
+        )
+        .route(
+            "/calendar/feed-url/reset",
+            post(feature::calendar::reset_calendar_feed_url),
+        )
+        .route(
+            "/calendar/feed/{token}",
+            get(feature::calendar::get_calendar_feed),
+        )
+        //ALBUM
+        .route(
+            "/event/{event_id}/album",
+            get(feature::album::get_event_album),
+        )
+        .route(
+            "/event/album/upload",
+            post(feature::album::upload_album_photo),
+        )
+        .route(
+            "/event/album/{album_id}/visibility",
+            put(feature::album::update_album_photo_visibility),
+        )
+        .route(
+            "/event/album/{album_id}",
+            delete(feature::album::delete_album_photo),
+        )
+        //CONTACT
+        .route(
+            "/contact",
+            get(feature::contact::get_contact_matches).delete(feature::contact::delete_contacts),
+        )
+        .route("/contact/sync", post(feature::contact::sync_contacts))
+        .route(
+            "/contact/suggestion",
+            get(feature::contact::get_contact_suggestions),
+        )
+        .route("/contact/invite", post(feature::contact::invite_contacts))
         //MUTUALS
         .route(
             "/user/{user_id}/mutual",
             get(feature::user::get_list_mutual),
         )
+        .route("/mutual/request", get(feature::mutual::get_mutual_requests))
+        .route("/mutual/suggestion", get(feature::mutual::get_mutual_suggestions))
+        .route("/mutual/block", get(feature::mutual::get_blocked_users))
+        .route(
+            "/mutual/{user_id}",
+            delete(feature::mutual::remove_mutual),
+        )
+        .route(
+            "/mutual/{user_id}/request",
+            post(feature::mutual::send_mutual_request),
+        )
+        .route(
+            "/mutual/{user_id}/accept",
+            post(feature::mutual::accept_mutual_request),
+        )
+        .route(
+            "/mutual/{user_id}/decline",
+            post(feature::mutual::decline_mutual_request),
+        )
+        .route(
+            "/mutual/{user_id}/block",
+            post(feature::mutual::block_user).delete(feature::mutual::unblock_user),
+        )
+        //REPORT
+        .route("/report", post(feature::report::create_report))
+        .route("/admin/report", get(feature::report::get_reports))
+        .route(
+            "/admin/report/{report_id}",
+            get(feature::report::get_report_detail),
+        )
+        .route(
+            "/admin/report/{report_id}/resolve",
+            post(feature::report::resolve_report),
+        )
+        .route(
+            "/admin/user/{user_id}/suspend",
+            post(feature::user::suspend_user),
+        )
+        .route(
+            "/admin/user/{user_id}/reactivate",
+            post(feature::user::reactivate_user),
+        )
         //NOTIFICATION
         .route(
             "/notification/me",